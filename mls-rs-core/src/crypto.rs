@@ -15,6 +15,9 @@ use zeroize::{ZeroizeOnDrop, Zeroizing};
 mod cipher_suite;
 pub use self::cipher_suite::*;
 
+mod self_test;
+pub use self::self_test::*;
+
 #[cfg(feature = "test_suite")]
 pub mod test_suite;
 
@@ -40,6 +43,18 @@ impl Debug for HpkeCiphertext {
     }
 }
 
+impl HpkeCiphertext {
+    /// Length in bytes of the encapsulated key.
+    pub fn kem_output_len(&self) -> usize {
+        self.kem_output.len()
+    }
+
+    /// Length in bytes of the AEAD ciphertext, including the authentication tag.
+    pub fn ciphertext_len(&self) -> usize {
+        self.ciphertext.len()
+    }
+}
+
 /// Byte representation of an HPKE public key. For ciphersuites using elliptic curves,
 /// the public key should be represented in the uncompressed format.
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, MlsSize, MlsDecode, MlsEncode)]
@@ -299,6 +314,16 @@ pub trait CryptoProvider: Send + Sync {
         -> Option<Self::CipherSuiteProvider>;
 }
 
+/// Length of the authentication tag of the AEAD with identifier `aead_id`, as given in
+/// RFC 9180, Table 5. Returns `None` for unknown and export-only AEADs.
+pub fn aead_tag_len(aead_id: u16) -> Option<usize> {
+    match aead_id {
+        // AES-128-GCM, AES-256-GCM and ChaCha20-Poly1305
+        0x0001..=0x0003 => Some(16),
+        _ => None,
+    }
+}
+
 /// Provides all cryptographic operations required by MLS for a given cipher suite.
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 #[cfg_attr(all(target_arch = "wasm32", mls_build_async), maybe_async::must_be_async(?Send))]
@@ -346,6 +371,48 @@ pub trait CipherSuiteProvider: Send + Sync {
         nonce: &[u8],
     ) -> Result<Zeroizing<Vec<u8>>, Self::Error>;
 
+    /// Same as [aead_seal](CipherSuiteProvider::aead_seal), except that the authentication tag
+    /// is returned separately as `(ciphertext, tag)`.
+    ///
+    /// The default implementation splits the output of [aead_seal](CipherSuiteProvider::aead_seal)
+    /// using the [tag length](aead_tag_len) of the AEAD of the cipher suite. If the AEAD is
+    /// unknown, the whole output is returned as ciphertext along with an empty tag.
+    async fn aead_seal_detached(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Self::Error> {
+        let mut ciphertext = self.aead_seal(key, data, aad, nonce).await?;
+        let tag_len = self
+            .cipher_suite()
+            .aead_id()
+            .and_then(aead_tag_len)
+            .unwrap_or(0);
+
+        let tag = ciphertext.split_off(ciphertext.len().saturating_sub(tag_len));
+
+        Ok((ciphertext, tag))
+    }
+
+    /// Decrypt the `ciphertext` and `tag` generated by
+    /// [aead_seal_detached](CipherSuiteProvider::aead_seal_detached).
+    ///
+    /// The default implementation appends `tag` to `ciphertext` and calls
+    /// [aead_open](CipherSuiteProvider::aead_open).
+    async fn aead_open_detached(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.aead_open(key, &[ciphertext, tag].concat(), aad, nonce)
+            .await
+    }
+
     /// Return the length of the secret key `key` passed to [aead_seal](CipherSuiteProvider::aead_seal)
     /// and [aead_open](CipherSuiteProvider::aead_open).
     fn aead_key_size(&self) -> usize;
@@ -500,4 +567,14 @@ pub trait CipherSuiteProvider: Send + Sync {
         signature: &[u8],
         data: &[u8],
     ) -> Result<(), Self::Error>;
+
+    /// Run known-answer tests of the hash, MAC and AEAD functions of this cipher suite,
+    /// followed by pairwise consistency tests of its AEAD, HPKE and signature functions.
+    ///
+    /// Known answers are only available for the cipher suites defined in RFC 9420. Other
+    /// cipher suites, as well as custom vectors, can be checked with [self_test_with_vectors].
+    async fn self_test(&self) -> Result<(), ProviderSelfTestError> {
+        let vectors = SelfTestVectors::for_cipher_suite(self.cipher_suite());
+        self_test_with_vectors(self, vectors.as_ref()).await
+    }
 }
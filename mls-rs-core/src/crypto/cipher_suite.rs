@@ -81,6 +81,54 @@ impl CipherSuite {
     pub fn all() -> impl Iterator<Item = CipherSuite> {
         (1..=7).map(CipherSuite)
     }
+
+    /// Security level in bits of the ciphersuite, as given in RFC 9420. Returns `None`
+    /// for ciphersuites not defined in RFC 9420.
+    pub fn security_level(&self) -> Option<u16> {
+        match *self {
+            Self::CURVE25519_AES128 | Self::P256_AES128 | Self::CURVE25519_CHACHA => Some(128),
+            Self::CURVE448_AES256
+            | Self::P521_AES256
+            | Self::CURVE448_CHACHA
+            | Self::P384_AES256 => Some(256),
+            _ => None,
+        }
+    }
+
+    /// HPKE KDF identifier of the ciphersuite, as given in RFC 9180, Table 3. Returns
+    /// `None` for ciphersuites not defined in RFC 9420.
+    pub fn kdf_id(&self) -> Option<u16> {
+        match *self {
+            Self::CURVE25519_AES128 | Self::P256_AES128 | Self::CURVE25519_CHACHA => Some(0x0001),
+            Self::P384_AES256 => Some(0x0002),
+            Self::CURVE448_AES256 | Self::P521_AES256 | Self::CURVE448_CHACHA => Some(0x0003),
+            _ => None,
+        }
+    }
+
+    /// HPKE AEAD identifier of the ciphersuite, as given in RFC 9180, Table 5. Returns
+    /// `None` for ciphersuites not defined in RFC 9420.
+    pub fn aead_id(&self) -> Option<u16> {
+        match *self {
+            Self::CURVE25519_AES128 | Self::P256_AES128 => Some(0x0001),
+            Self::CURVE448_AES256 | Self::P521_AES256 | Self::P384_AES256 => Some(0x0002),
+            Self::CURVE25519_CHACHA | Self::CURVE448_CHACHA => Some(0x0003),
+            _ => None,
+        }
+    }
+
+    /// TLS signature scheme of the ciphersuite, as given in RFC 8446, Section 4.2.3.
+    /// Returns `None` for ciphersuites not defined in RFC 9420.
+    pub fn signature_scheme(&self) -> Option<u16> {
+        match *self {
+            Self::CURVE25519_AES128 | Self::CURVE25519_CHACHA => Some(0x0807),
+            Self::P256_AES128 => Some(0x0403),
+            Self::CURVE448_AES256 | Self::CURVE448_CHACHA => Some(0x0808),
+            Self::P521_AES256 => Some(0x0603),
+            Self::P384_AES256 => Some(0x0503),
+            _ => None,
+        }
+    }
 }
 
 /// Modes of HPKE operation.
@@ -96,3 +144,52 @@ pub enum HpkeModeId {
     /// Authenticated variant that authenticates possession of a PSK as well as a KEM private key.
     AuthPsk = 0x03,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::CipherSuite;
+
+    #[test]
+    fn algorithm_identifiers_match_rfc_9420() {
+        let expected = [
+            (CipherSuite::CURVE25519_AES128, 0x0001, 0x0001, 0x0807),
+            (CipherSuite::P256_AES128, 0x0001, 0x0001, 0x0403),
+            (CipherSuite::CURVE25519_CHACHA, 0x0001, 0x0003, 0x0807),
+            (CipherSuite::CURVE448_AES256, 0x0003, 0x0002, 0x0808),
+            (CipherSuite::P521_AES256, 0x0003, 0x0002, 0x0603),
+            (CipherSuite::CURVE448_CHACHA, 0x0003, 0x0003, 0x0808),
+            (CipherSuite::P384_AES256, 0x0002, 0x0002, 0x0503),
+        ];
+
+        for (cipher_suite, kdf, aead, signature) in expected {
+            assert_eq!(cipher_suite.kdf_id(), Some(kdf));
+            assert_eq!(cipher_suite.aead_id(), Some(aead));
+            assert_eq!(cipher_suite.signature_scheme(), Some(signature));
+        }
+
+        let unknown = CipherSuite::new(0xf000);
+
+        assert_eq!(unknown.kdf_id(), None);
+        assert_eq!(unknown.aead_id(), None);
+        assert_eq!(unknown.signature_scheme(), None);
+    }
+
+    #[test]
+    fn security_levels_match_rfc_9420() {
+        let expected = [
+            (CipherSuite::CURVE25519_AES128, 128),
+            (CipherSuite::P256_AES128, 128),
+            (CipherSuite::CURVE25519_CHACHA, 128),
+            (CipherSuite::CURVE448_AES256, 256),
+            (CipherSuite::P521_AES256, 256),
+            (CipherSuite::CURVE448_CHACHA, 256),
+            (CipherSuite::P384_AES256, 256),
+        ];
+
+        for (cipher_suite, level) in expected {
+            assert_eq!(cipher_suite.security_level(), Some(level));
+        }
+
+        assert_eq!(CipherSuite::new(0xf000).security_level(), None);
+    }
+}
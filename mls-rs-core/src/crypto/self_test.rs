@@ -0,0 +1,215 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+
+use crate::error::{AnyError, IntoAnyError};
+
+use super::{CipherSuite, CipherSuiteProvider};
+
+const HASH_INPUT: &[u8] = b"abc";
+
+// RFC 4231 test case 2
+const MAC_KEY: &[u8] = b"Jefe";
+const MAC_INPUT: &[u8] = b"what do ya want for nothing?";
+
+const AEAD_PLAINTEXT: &[u8] = b"known answer";
+const AEAD_AAD: &[u8] = b"mls-rs self-test";
+
+const SIGNATURE_INPUT: &[u8] = b"mls-rs self-test signature";
+const HPKE_INFO: &[u8] = b"mls-rs self-test hpke";
+
+/// Cryptographic primitive checked by [`CipherSuiteProvider::self_test`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelfTestPrimitive {
+    Hash,
+    Mac,
+    Aead,
+    Hpke,
+    Signature,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum ProviderSelfTestError {
+    #[cfg_attr(
+        feature = "std",
+        error("{0:?} self-test produced an unexpected result")
+    )]
+    Mismatch(SelfTestPrimitive),
+    #[cfg_attr(feature = "std", error(transparent))]
+    ProviderError(AnyError),
+}
+
+impl IntoAnyError for ProviderSelfTestError {
+    #[cfg(feature = "std")]
+    fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+        Ok(self.into())
+    }
+}
+
+/// Hex encoded known answers checked by [`self_test_with_vectors`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfTestVectors {
+    /// Hash of `"abc"`.
+    pub hash: &'static str,
+    /// MAC of RFC 4231 test case 2.
+    pub mac: &'static str,
+    /// AEAD encryption, including the tag, of `"known answer"` with additional data
+    /// `"mls-rs self-test"`, where the key and the nonce are the bytes `0, 1, 2, ...`.
+    pub aead: &'static str,
+}
+
+const SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+const SHA384: &str = "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+                      8086072ba1e7cc2358baeca134c825a7";
+
+const SHA512: &str = "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                      2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f";
+
+const HMAC_SHA256: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+const HMAC_SHA384: &str = "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e\
+                           8e2240ca5e69e2c78b3239ecfab21649";
+
+const HMAC_SHA512: &str = "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                           9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737";
+
+const AES128_GCM: &str = "f802c8b9083b963a38a504f8ce14437a8b44ce7ba868d8f77cfe456f";
+const AES256_GCM: &str = "2c6cb96cabc5a375fe36f2f93a9775dd19a3de3af1cf5ec75fb38792";
+const CHACHA20_POLY1305: &str = "e29567774737c42ec4f45a8140998343349bc2e4aa5fb8d0d914ab9e";
+
+impl SelfTestVectors {
+    /// Known answers for the cipher suites defined in RFC 9420.
+    pub fn for_cipher_suite(cipher_suite: CipherSuite) -> Option<Self> {
+        let (hash, mac, aead) = match cipher_suite {
+            CipherSuite::CURVE25519_AES128 | CipherSuite::P256_AES128 => {
+                (SHA256, HMAC_SHA256, AES128_GCM)
+            }
+            CipherSuite::CURVE25519_CHACHA => (SHA256, HMAC_SHA256, CHACHA20_POLY1305),
+            CipherSuite::CURVE448_AES256 | CipherSuite::P521_AES256 => {
+                (SHA512, HMAC_SHA512, AES256_GCM)
+            }
+            CipherSuite::CURVE448_CHACHA => (SHA512, HMAC_SHA512, CHACHA20_POLY1305),
+            CipherSuite::P384_AES256 => (SHA384, HMAC_SHA384, AES256_GCM),
+            _ => return None,
+        };
+
+        Some(Self { hash, mac, aead })
+    }
+}
+
+/// Run the known-answer tests in `vectors`, if any, followed by pairwise consistency
+/// tests of the AEAD, HPKE and signature functions of `provider`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn self_test_with_vectors<P: CipherSuiteProvider + ?Sized>(
+    provider: &P,
+    vectors: Option<&SelfTestVectors>,
+) -> Result<(), ProviderSelfTestError> {
+    let key = counting_bytes(provider.aead_key_size());
+    let nonce = counting_bytes(provider.aead_nonce_size());
+
+    let ciphertext = provider
+        .aead_seal(&key, AEAD_PLAINTEXT, Some(AEAD_AAD), &nonce)
+        .await
+        .map_err(provider_error)?;
+
+    if let Some(vectors) = vectors {
+        let hash = provider.hash(HASH_INPUT).await.map_err(provider_error)?;
+        check(SelfTestPrimitive::Hash, &hash, vectors.hash)?;
+
+        let mac = provider
+            .mac(MAC_KEY, MAC_INPUT)
+            .await
+            .map_err(provider_error)?;
+
+        check(SelfTestPrimitive::Mac, &mac, vectors.mac)?;
+        check(SelfTestPrimitive::Aead, &ciphertext, vectors.aead)?;
+    }
+
+    let plaintext = provider
+        .aead_open(&key, &ciphertext, Some(AEAD_AAD), &nonce)
+        .await
+        .map_err(provider_error)?;
+
+    let mut tampered = ciphertext;
+    tampered[0] ^= 1;
+
+    let tampered_opened = provider
+        .aead_open(&key, &tampered, Some(AEAD_AAD), &nonce)
+        .await
+        .is_ok();
+
+    if *plaintext != AEAD_PLAINTEXT || tampered_opened {
+        return Err(ProviderSelfTestError::Mismatch(SelfTestPrimitive::Aead));
+    }
+
+    let (secret_key, public_key) = provider.kem_generate().await.map_err(provider_error)?;
+
+    let hpke_ciphertext = provider
+        .hpke_seal(&public_key, HPKE_INFO, Some(AEAD_AAD), AEAD_PLAINTEXT)
+        .await
+        .map_err(provider_error)?;
+
+    let hpke_plaintext = provider
+        .hpke_open(
+            &hpke_ciphertext,
+            &secret_key,
+            &public_key,
+            HPKE_INFO,
+            Some(AEAD_AAD),
+        )
+        .await
+        .map_err(provider_error)?;
+
+    if hpke_plaintext != AEAD_PLAINTEXT {
+        return Err(ProviderSelfTestError::Mismatch(SelfTestPrimitive::Hpke));
+    }
+
+    let (secret_key, public_key) = provider
+        .signature_key_generate()
+        .await
+        .map_err(provider_error)?;
+
+    let signature = provider
+        .sign(&secret_key, SIGNATURE_INPUT)
+        .await
+        .map_err(provider_error)?;
+
+    provider
+        .verify(&public_key, &signature, SIGNATURE_INPUT)
+        .await
+        .map_err(|_| ProviderSelfTestError::Mismatch(SelfTestPrimitive::Signature))?;
+
+    if provider
+        .verify(&public_key, &signature, AEAD_PLAINTEXT)
+        .await
+        .is_ok()
+    {
+        return Err(ProviderSelfTestError::Mismatch(
+            SelfTestPrimitive::Signature,
+        ));
+    }
+
+    Ok(())
+}
+
+fn counting_bytes(len: usize) -> Vec<u8> {
+    (0..len).map(|i| i as u8).collect()
+}
+
+fn check(
+    primitive: SelfTestPrimitive,
+    computed: &[u8],
+    expected: &str,
+) -> Result<(), ProviderSelfTestError> {
+    (hex::encode(computed) == expected)
+        .then_some(())
+        .ok_or(ProviderSelfTestError::Mismatch(primitive))
+}
+
+fn provider_error<E: IntoAnyError>(error: E) -> ProviderSelfTestError {
+    ProviderSelfTestError::ProviderError(error.into_any_error())
+}
@@ -214,6 +214,41 @@ async fn verify_aead_tests<C: CipherSuiteProvider>(cs: &C, test_cases: Vec<AeadT
             .unwrap();
 
         assert_eq!(plaintext.to_vec(), case.pt);
+
+        let (detached_ciphertext, tag) = cs
+            .aead_seal_detached(&case.key, &case.pt, Some(&case.aad), &case.iv)
+            .await
+            .unwrap();
+
+        assert_eq!([detached_ciphertext.clone(), tag.clone()].concat(), case.ct);
+
+        let plaintext = cs
+            .aead_open_detached(
+                &case.key,
+                &detached_ciphertext,
+                &tag,
+                Some(&case.aad),
+                &case.iv,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(plaintext.to_vec(), case.pt);
+
+        let mut bad_tag = tag;
+        bad_tag[0] ^= 1;
+
+        let res = cs
+            .aead_open_detached(
+                &case.key,
+                &detached_ciphertext,
+                &bad_tag,
+                Some(&case.aad),
+                &case.iv,
+            )
+            .await;
+
+        assert!(res.is_err());
     }
 }
 
@@ -110,6 +110,16 @@ impl ExtensionList {
         self.0.iter().any(|e| e.extension_type == ext_id)
     }
 
+    /// Types of the extensions within the list that are not contained in
+    /// `known`.
+    pub fn unknown_extensions(&self, known: &[ExtensionType]) -> Vec<ExtensionType> {
+        self.0
+            .iter()
+            .map(|e| e.extension_type)
+            .filter(|ext_type| !known.contains(ext_type))
+            .collect()
+    }
+
     /// Set an extension in the list based on a provided type that implements
     /// the [MlsExtension](super::MlsExtension) trait.
     ///
@@ -280,6 +290,18 @@ mod tests {
         assert!(!list.has_extension(42.into()));
     }
 
+    #[test]
+    fn extension_list_reports_unknown_extensions() {
+        let mut list = ExtensionList::new();
+
+        list.set_from(TestExtensionA(0)).unwrap();
+        list.set(Extension::new(ExtensionType(254), vec![0, 1, 2]));
+
+        let known = [<TestExtensionA as MlsCodecExtension>::extension_type()];
+
+        assert_eq!(list.unknown_extensions(&known), vec![ExtensionType(254)]);
+    }
+
     #[derive(MlsEncode, MlsSize)]
     struct ExtensionsVec(Vec<Extension>);
 
@@ -56,6 +56,17 @@ impl ProposalType {
     pub const RE_INIT: ProposalType = ProposalType(5);
     pub const EXTERNAL_INIT: ProposalType = ProposalType(6);
     pub const GROUP_CONTEXT_EXTENSIONS: ProposalType = ProposalType(7);
+    /// Acknowledgement of received application messages. RFC 9420 does not
+    /// assign a value for this proposal, so a value from the private use
+    /// range is used. This type is not part of [`ProposalType::DEFAULT`]
+    /// and must be listed in the capabilities of every member of a group
+    /// using it.
+    ///
+    /// When the `app_ack` feature of `mls-rs` is enabled, applications can
+    /// not use this value for their own custom proposals. Encoding such a
+    /// custom proposal fails and received proposals of this type are decoded
+    /// as AppAck.
+    pub const APP_ACK: ProposalType = ProposalType(0xF000);
 
     /// Default proposal types defined
     /// in [RFC 9420](https://www.rfc-editor.org/rfc/rfc9420.html#name-leaf-node-contents)
@@ -184,6 +184,18 @@ impl Credential {
             _ => None,
         }
     }
+
+    /// MLS encoded representation of this credential, including its
+    /// credential type.
+    pub fn as_bytes(&self) -> Result<Vec<u8>, mls_rs_codec::Error> {
+        self.mls_encode_to_vec()
+    }
+
+    /// Decode a credential previously encoded with
+    /// [`as_bytes`](Self::as_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        Self::mls_decode(&mut &*bytes)
+    }
 }
 
 impl MlsSize for Credential {
@@ -240,3 +252,35 @@ pub trait MlsCredential: Sized {
     /// Function to convert this type into a [`Credential`] enum.
     fn into_credential(self) -> Result<Credential, Self::Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::identity::BasicCredential;
+
+    #[cfg(feature = "x509")]
+    use crate::identity::CertificateChain;
+
+    #[cfg(feature = "x509")]
+    use alloc::vec;
+
+    use super::Credential;
+
+    #[test]
+    fn basic_credential_bytes_round_trip() {
+        let credential = BasicCredential::new(b"alice".to_vec()).into_credential();
+        let bytes = credential.as_bytes().unwrap();
+
+        assert_eq!(Credential::from_bytes(&bytes).unwrap(), credential);
+    }
+
+    #[cfg(feature = "x509")]
+    #[test]
+    fn x509_credential_bytes_round_trip() {
+        let credential =
+            CertificateChain::from(vec![vec![1u8, 2, 3], vec![4u8, 5]]).into_credential();
+
+        let bytes = credential.as_bytes().unwrap();
+
+        assert_eq!(Credential::from_bytes(&bytes).unwrap(), credential);
+    }
+}
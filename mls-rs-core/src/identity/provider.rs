@@ -67,4 +67,23 @@ pub trait IdentityProvider: Send + Sync {
 
     /// Credential types that are supported by this provider.
     fn supported_types(&self) -> Vec<CredentialType>;
+
+    /// Maximum encoded size, in bytes, of a credential that this provider
+    /// is willing to validate.
+    ///
+    /// Group members with a larger credential are rejected before
+    /// [`validate_member`](IdentityProvider::validate_member) is called.
+    /// By default, no limit is enforced.
+    fn max_credential_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Time after which `signing_identity` is no longer valid, if it expires.
+    ///
+    /// Cached results of [`validate_member`](IdentityProvider::validate_member)
+    /// are not reused for timestamps after this time. By default, credentials
+    /// are assumed not to expire.
+    fn credential_expiration(&self, _signing_identity: &SigningIdentity) -> Option<MlsTime> {
+        None
+    }
 }
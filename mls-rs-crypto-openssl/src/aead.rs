@@ -15,6 +15,8 @@ pub enum AeadError {
     OpensslError(#[from] openssl::error::ErrorStack),
     #[error("AEAD ciphertext of length {0} is too short to fit the tag")]
     InvalidCipherLen(usize),
+    #[error("AEAD tag of invalid length {0}")]
+    InvalidTagLen(usize),
     #[error("encrypted message cannot be empty")]
     EmptyPlaintext,
     #[error("unsupported cipher suite")]
@@ -79,6 +81,36 @@ impl AeadType for Aead {
         aad: Option<&'a [u8]>,
         nonce: &[u8],
     ) -> Result<Vec<u8>, AeadError> {
+        let (ciphertext, tag) = self.seal_detached(key, data, aad, nonce).await?;
+
+        Ok([ciphertext, tag].concat())
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    async fn open<'a>(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, AeadError> {
+        (ciphertext.len() > AES_TAG_LEN)
+            .then_some(())
+            .ok_or(AeadError::InvalidCipherLen(ciphertext.len()))?;
+
+        let (data, tag) = ciphertext.split_at(ciphertext.len() - AES_TAG_LEN);
+
+        self.open_detached(key, data, tag, aad, nonce).await
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    async fn seal_detached<'a>(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), AeadError> {
         (!data.is_empty())
             .then_some(())
             .ok_or(AeadError::EmptyPlaintext)?;
@@ -88,26 +120,30 @@ impl AeadType for Aead {
 
         let ciphertext = encrypt_aead(self.cipher, key, Some(nonce), aad, data, &mut tag)?;
 
-        // Question Is this how this should be done? Or other encodings?
-        Ok([&ciphertext, &tag as &[u8]].concat())
+        Ok((ciphertext, tag.to_vec()))
     }
 
     #[allow(clippy::needless_lifetimes)]
-    async fn open<'a>(
+    async fn open_detached<'a>(
         &self,
         key: &[u8],
         ciphertext: &[u8],
+        tag: &[u8],
         aad: Option<&'a [u8]>,
         nonce: &[u8],
     ) -> Result<Vec<u8>, AeadError> {
-        (ciphertext.len() > AES_TAG_LEN)
+        (!ciphertext.is_empty())
             .then_some(())
             .ok_or(AeadError::InvalidCipherLen(ciphertext.len()))?;
 
-        let (data, tag) = ciphertext.split_at(ciphertext.len() - AES_TAG_LEN);
+        // OpenSSL would otherwise accept truncated GCM tags
+        (tag.len() == AES_TAG_LEN)
+            .then_some(())
+            .ok_or(AeadError::InvalidTagLen(tag.len()))?;
+
         let aad = aad.unwrap_or_default();
 
-        decrypt_aead(self.cipher, key, Some(nonce), aad, data, tag).map_err(Into::into)
+        decrypt_aead(self.cipher, key, Some(nonce), aad, ciphertext, tag).map_err(Into::into)
     }
 
     fn key_size(&self) -> usize {
@@ -181,6 +217,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn detached_tag() {
+        for aead in get_aeads() {
+            let key = vec![42u8; aead.key_size()];
+            let nonce = vec![42u8; aead.nonce_size()];
+
+            let (ciphertext, tag) = aead
+                .seal_detached(&key, b"message", Some(b"foo"), &nonce)
+                .unwrap();
+
+            assert_eq!(
+                aead.open_detached(&key, &ciphertext, &tag, Some(b"foo"), &nonce)
+                    .unwrap(),
+                b"message"
+            );
+
+            assert_matches!(
+                aead.open_detached(&key, &ciphertext, &tag[1..], Some(b"foo"), &nonce),
+                Err(AeadError::InvalidTagLen(_))
+            );
+        }
+    }
+
     #[test]
     fn aad_mismatch() {
         for aead in get_aeads() {
@@ -27,6 +27,9 @@ pub enum EcError {
     InvalidKeyBytes,
     #[error("unsupported cipher suite")]
     UnsupportedCipherSuite,
+    /// Point compression is only defined for NIST curves
+    #[error("point compression is not supported for this curve")]
+    CompressionUnsupported,
 }
 
 #[inline(always)]
@@ -75,6 +78,8 @@ fn pub_key_from_uncompressed_non_nist(bytes: &[u8], id: Id) -> Result<EcPublicKe
     PKey::public_key_from_raw_bytes(bytes, id)
 }
 
+/// Import a public key. For NIST curves, both compressed (leading byte 0x02 or 0x03) and
+/// uncompressed (leading byte 0x04) points are accepted.
 pub fn pub_key_from_uncompressed(bytes: &[u8], curve: Curve) -> Result<EcPublicKey, EcError> {
     let pubkey = if let Some(nist_id) = nist_curve_id(curve) {
         pub_key_from_uncompressed_nist(bytes, nist_id)
@@ -97,6 +102,22 @@ pub fn pub_key_to_uncompressed(key: &EcPublicKey) -> Result<Vec<u8>, ErrorStack>
     }
 }
 
+pub fn pub_key_from_compressed(bytes: &[u8], curve: Curve) -> Result<EcPublicKey, EcError> {
+    let nist_id = nist_curve_id(curve).ok_or(EcError::CompressionUnsupported)?;
+
+    // OpenSSL detects the point form from the leading byte
+    Ok(pub_key_from_uncompressed_nist(bytes, nist_id)?)
+}
+
+pub fn pub_key_to_compressed(key: &EcPublicKey) -> Result<Vec<u8>, EcError> {
+    let ec_key = key.ec_key().map_err(|_| EcError::CompressionUnsupported)?;
+    let mut ctx = BigNumContext::new()?;
+
+    Ok(ec_key
+        .public_key()
+        .to_bytes(ec_key.group(), PointConversionForm::COMPRESSED, &mut ctx)?)
+}
+
 pub fn curve_to_id(c: Curve) -> Result<Id, EcError> {
     match c {
         Curve::P256 | Curve::P384 | Curve::P521 => Ok(Id::EC),
@@ -146,9 +167,15 @@ fn private_key_from_bytes_nist(
     let mut sk_val = BigNum::from_slice(bytes)?;
     sk_val.set_const_time();
 
-    (sk_val < order && sk_val > BigNum::new()?)
-        .then_some(())
-        .ok_or(EcError::InvalidKeyBytes)?;
+    // Evaluate both bounds before branching so that the outcome does not reveal which bound
+    // was violated. Note that OpenSSL does not guarantee `BN_cmp` itself to be constant time.
+    let zero = BigNum::new()?;
+    let in_range = (sk_val < order) & (sk_val > zero);
+
+    if !in_range {
+        sk_val.clear();
+        return Err(EcError::InvalidKeyBytes);
+    }
 
     let mut pk_val = EcPoint::new(&group)?;
 
@@ -173,9 +200,11 @@ fn private_key_from_bytes_non_nist(bytes: &[u8], curve: Curve) -> Result<EcPriva
         _ => curve.secret_key_size(),
     };
 
-    (openssl_secret_len <= bytes.len())
-        .then_some(())
-        .ok_or(EcError::InvalidKeyBytes)?;
+    // Signature keys may be the seed alone or the seed followed by the public key. Any other
+    // length is rejected here since OpenSSL does not report a meaningful error for it.
+    if bytes.len() != openssl_secret_len && bytes.len() != curve.secret_key_size() {
+        return Err(EcError::InvalidKeyBytes);
+    }
 
     let bytes = &bytes[..openssl_secret_len];
 
@@ -356,11 +385,12 @@ pub(crate) mod test_utils {
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
+    use openssl::bn::BigNum;
 
     use super::{
         generate_keypair, generate_private_key, private_key_bytes_to_public,
-        private_key_from_bytes, private_key_to_bytes, pub_key_from_uncompressed,
-        pub_key_to_uncompressed,
+        private_key_from_bytes, private_key_to_bytes, pub_key_from_compressed,
+        pub_key_from_uncompressed, pub_key_to_compressed, pub_key_to_uncompressed,
         test_utils::{byte_equal, get_test_public_keys, get_test_secret_keys},
         Curve, EcError,
     };
@@ -502,5 +532,73 @@ mod tests {
                 Err(EcError::InvalidKeyBytes)
             );
         }
+
+        // Keys in the middle of the valid range are accepted
+        for (curve, order) in nist_curves
+            .into_iter()
+            .zip([p256_order, p384_order, p521_order])
+        {
+            let mut mid_range = BigNum::new().unwrap();
+            mid_range
+                .rshift1(&BigNum::from_slice(&order).unwrap())
+                .unwrap();
+
+            let mid_range = mid_range
+                .to_vec_padded(curve.secret_key_size() as i32)
+                .unwrap();
+
+            assert_matches!(private_key_from_bytes(&mid_range, curve, true), Ok(_));
+        }
+    }
+
+    #[test]
+    fn non_nist_keys_of_wrong_size_are_rejected() {
+        for curve in [Curve::X25519, Curve::Ed448] {
+            let size = curve.secret_key_size();
+
+            for len in [size - 1, size + 1] {
+                assert_matches!(
+                    private_key_from_bytes(&vec![1u8; len], curve, true),
+                    Err(EcError::InvalidKeyBytes),
+                    "Key of length {len} accepted for {curve:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nist_public_key_compression_round_trip() {
+        for curve in [Curve::P256, Curve::P384, Curve::P521] {
+            let key_bytes = get_test_public_keys().get_key_from_curve(curve);
+            let key = pub_key_from_uncompressed(&key_bytes, curve).unwrap();
+
+            let compressed = pub_key_to_compressed(&key).unwrap();
+
+            assert_eq!(compressed.len(), 1 + (key_bytes.len() - 1) / 2);
+            assert_matches!(compressed[0], 0x02 | 0x03);
+
+            let decompressed = pub_key_from_compressed(&compressed, curve).unwrap();
+            assert_eq!(pub_key_to_uncompressed(&decompressed).unwrap(), key_bytes);
+
+            // Existing callers accept the compressed form transparently
+            let imported = pub_key_from_uncompressed(&compressed, curve).unwrap();
+            assert_eq!(pub_key_to_uncompressed(&imported).unwrap(), key_bytes);
+        }
+    }
+
+    #[test]
+    fn non_nist_public_key_compression_fails() {
+        let key_bytes = get_test_public_keys().get_key_from_curve(Curve::X25519);
+        let key = pub_key_from_uncompressed(&key_bytes, Curve::X25519).unwrap();
+
+        assert_matches!(
+            pub_key_to_compressed(&key),
+            Err(EcError::CompressionUnsupported)
+        );
+
+        assert_matches!(
+            pub_key_from_compressed(&key_bytes, Curve::X25519),
+            Err(EcError::CompressionUnsupported)
+        );
     }
 }
@@ -15,8 +15,9 @@ use thiserror::Error;
 
 use crate::ec::{
     curve_from_private_key, curve_from_public_key, generate_keypair, private_key_bytes_to_public,
-    private_key_from_bytes, private_key_from_der, private_key_to_bytes, pub_key_from_uncompressed,
-    pub_key_to_uncompressed, public_key_from_der, EcError,
+    private_key_from_bytes, private_key_from_der, private_key_to_bytes, pub_key_from_compressed,
+    pub_key_from_uncompressed, pub_key_to_compressed, pub_key_to_uncompressed, public_key_from_der,
+    EcError,
 };
 
 #[derive(Debug, Error)]
@@ -85,6 +86,25 @@ impl EcSigner {
         Ok(private_key_bytes_to_public(secret_key, self.0)?.into())
     }
 
+    /// Convert a public key to the compressed point form. Only supported for NIST curves.
+    pub fn signature_key_compress(
+        &self,
+        public_key: &SignaturePublicKey,
+    ) -> Result<Vec<u8>, EcSignerError> {
+        let key = pub_key_from_uncompressed(public_key, self.0)?;
+        Ok(pub_key_to_compressed(&key)?)
+    }
+
+    /// Convert a compressed point, as sent by some peers, to the uncompressed form used by MLS.
+    /// Only supported for NIST curves.
+    pub fn signature_key_decompress(
+        &self,
+        compressed: &[u8],
+    ) -> Result<SignaturePublicKey, EcSignerError> {
+        let key = pub_key_from_compressed(compressed, self.0)?;
+        Ok(pub_key_to_uncompressed(&key)?.into())
+    }
+
     #[cfg(feature = "x509")]
     pub(crate) fn pkey_from_secret_key(
         &self,
@@ -10,7 +10,7 @@ use aes_gcm::{Aes128Gcm, Aes256Gcm, KeyInit};
 use chacha20poly1305::ChaCha20Poly1305;
 use mls_rs_core::{crypto::CipherSuite, error::IntoAnyError};
 use mls_rs_crypto_traits::{AeadId, AeadType, AES_TAG_LEN};
-use rc_aead::{generic_array::GenericArray, Payload};
+use rc_aead::{generic_array::GenericArray, AeadInPlace};
 
 use alloc::vec::Vec;
 
@@ -24,6 +24,8 @@ pub enum AeadError {
         error("AEAD ciphertext of length {0} is too short to fit the tag")
     )]
     InvalidCipherLen(usize),
+    #[cfg_attr(feature = "std", error("AEAD tag of invalid length {0}"))]
+    InvalidTagLen(usize),
     #[cfg_attr(feature = "std", error("encrypted message cannot be empty"))]
     EmptyPlaintext,
     #[cfg_attr(
@@ -73,6 +75,37 @@ impl AeadType for Aead {
         aad: Option<&'a [u8]>,
         nonce: &[u8],
     ) -> Result<Vec<u8>, AeadError> {
+        let (mut ciphertext, tag) = self.seal_detached(key, data, aad, nonce).await?;
+        ciphertext.extend_from_slice(&tag);
+
+        Ok(ciphertext)
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    async fn open<'a>(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, AeadError> {
+        (ciphertext.len() > AES_TAG_LEN)
+            .then_some(())
+            .ok_or(AeadError::InvalidCipherLen(ciphertext.len()))?;
+
+        let (data, tag) = ciphertext.split_at(ciphertext.len() - AES_TAG_LEN);
+
+        self.open_detached(key, data, tag, aad, nonce).await
+    }
+
+    #[allow(clippy::needless_lifetimes)]
+    async fn seal_detached<'a>(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), AeadError> {
         (!data.is_empty())
             .then_some(())
             .ok_or(AeadError::EmptyPlaintext)?;
@@ -99,17 +132,22 @@ impl AeadType for Aead {
     }
 
     #[allow(clippy::needless_lifetimes)]
-    async fn open<'a>(
+    async fn open_detached<'a>(
         &self,
         key: &[u8],
         ciphertext: &[u8],
+        tag: &[u8],
         aad: Option<&'a [u8]>,
         nonce: &[u8],
     ) -> Result<Vec<u8>, AeadError> {
-        (ciphertext.len() > AES_TAG_LEN)
+        (!ciphertext.is_empty())
             .then_some(())
             .ok_or(AeadError::InvalidCipherLen(ciphertext.len()))?;
 
+        (tag.len() == AES_TAG_LEN)
+            .then_some(())
+            .ok_or(AeadError::InvalidTagLen(tag.len()))?;
+
         (key.len() == self.key_size())
             .then_some(())
             .ok_or_else(|| AeadError::InvalidKeyLen(key.len(), self.key_size()))?;
@@ -117,15 +155,15 @@ impl AeadType for Aead {
         match self.0 {
             AeadId::Aes128Gcm => {
                 let cipher = Aes128Gcm::new(GenericArray::from_slice(key));
-                decrypt_aead_trait(cipher, ciphertext, aad, nonce)
+                decrypt_aead_trait(cipher, ciphertext, tag, aad, nonce)
             }
             AeadId::Aes256Gcm => {
                 let cipher = Aes256Gcm::new(GenericArray::from_slice(key));
-                decrypt_aead_trait(cipher, ciphertext, aad, nonce)
+                decrypt_aead_trait(cipher, ciphertext, tag, aad, nonce)
             }
             AeadId::Chacha20Poly1305 => {
                 let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(key));
-                decrypt_aead_trait(cipher, ciphertext, aad, nonce)
+                decrypt_aead_trait(cipher, ciphertext, tag, aad, nonce)
             }
             _ => Err(AeadError::UnsupportedCipherSuite),
         }
@@ -146,31 +184,39 @@ impl AeadType for Aead {
 }
 
 fn encrypt_aead_trait(
-    cipher: impl rc_aead::Aead,
+    cipher: impl AeadInPlace,
     data: &[u8],
     aad: Option<&[u8]>,
     nonce: &[u8],
-) -> Result<Vec<u8>, AeadError> {
-    let payload = Payload {
-        msg: data,
-        aad: aad.unwrap_or_default(),
-    };
+) -> Result<(Vec<u8>, Vec<u8>), AeadError> {
+    let mut ciphertext = data.to_vec();
+
+    let tag = cipher.encrypt_in_place_detached(
+        GenericArray::from_slice(nonce),
+        aad.unwrap_or_default(),
+        &mut ciphertext,
+    )?;
 
-    Ok(cipher.encrypt(GenericArray::from_slice(nonce), payload)?)
+    Ok((ciphertext, tag.to_vec()))
 }
 
 fn decrypt_aead_trait(
-    cipher: impl rc_aead::Aead,
+    cipher: impl AeadInPlace,
     ciphertext: &[u8],
+    tag: &[u8],
     aad: Option<&[u8]>,
     nonce: &[u8],
 ) -> Result<Vec<u8>, AeadError> {
-    let payload = Payload {
-        msg: ciphertext,
-        aad: aad.unwrap_or_default(),
-    };
+    let mut plaintext = ciphertext.to_vec();
+
+    cipher.decrypt_in_place_detached(
+        GenericArray::from_slice(nonce),
+        aad.unwrap_or_default(),
+        &mut plaintext,
+        GenericArray::from_slice(tag),
+    )?;
 
-    Ok(cipher.decrypt(GenericArray::from_slice(nonce), payload)?)
+    Ok(plaintext)
 }
 
 #[cfg(all(not(mls_build_async), test))]
@@ -233,6 +279,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn detached_tag() {
+        for aead in get_aeads() {
+            let key = vec![42u8; aead.key_size()];
+            let nonce = vec![42u8; aead.nonce_size()];
+
+            let (ciphertext, tag) = aead
+                .seal_detached(&key, b"message", Some(b"foo"), &nonce)
+                .unwrap();
+
+            assert_eq!(
+                aead.open_detached(&key, &ciphertext, &tag, Some(b"foo"), &nonce)
+                    .unwrap(),
+                b"message"
+            );
+
+            assert_matches!(
+                aead.open_detached(&key, &ciphertext, &tag[1..], Some(b"foo"), &nonce),
+                Err(AeadError::InvalidTagLen(_))
+            );
+        }
+    }
+
     #[test]
     fn aad_mismatch() {
         for aead in get_aeads() {
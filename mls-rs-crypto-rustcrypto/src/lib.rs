@@ -241,6 +241,34 @@ where
             .map(Zeroizing::new)
     }
 
+    async fn aead_seal_detached(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Self::Error> {
+        self.aead
+            .seal_detached(key, data, aad, nonce)
+            .await
+            .map_err(|e| RustCryptoError::AeadError(e.into_any_error()))
+    }
+
+    async fn aead_open_detached(
+        &self,
+        key: &[u8],
+        cipher_text: &[u8],
+        tag: &[u8],
+        aad: Option<&[u8]>,
+        nonce: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, Self::Error> {
+        self.aead
+            .open_detached(key, cipher_text, tag, aad, nonce)
+            .await
+            .map_err(|e| RustCryptoError::AeadError(e.into_any_error()))
+            .map(Zeroizing::new)
+    }
+
     fn aead_key_size(&self) -> usize {
         self.aead.key_size()
     }
@@ -387,3 +415,39 @@ fn mls_core_tests() {
         mls_rs_core::crypto::test_suite::verify_hpke_encap_tests(&mut hpke, cs);
     }
 }
+
+#[cfg(not(mls_build_async))]
+#[test]
+fn self_test_passes_for_all_cipher_suites() {
+    let provider = RustCryptoProvider::new();
+
+    for cs in RustCryptoProvider::all_supported_cipher_suites() {
+        provider
+            .cipher_suite_provider(cs)
+            .unwrap()
+            .self_test()
+            .unwrap();
+    }
+}
+
+#[cfg(not(mls_build_async))]
+#[test]
+fn self_test_detects_wrong_known_answer() {
+    use mls_rs_core::crypto::{
+        self_test_with_vectors, ProviderSelfTestError, SelfTestPrimitive, SelfTestVectors,
+    };
+
+    let cs = RustCryptoProvider::new()
+        .cipher_suite_provider(CipherSuite::CURVE25519_AES128)
+        .unwrap();
+
+    let mut vectors = SelfTestVectors::for_cipher_suite(CipherSuite::CURVE25519_AES128).unwrap();
+    vectors.mac = "00";
+
+    let res = self_test_with_vectors(&cs, Some(&vectors));
+
+    assert_matches::assert_matches!(
+        res,
+        Err(ProviderSelfTestError::Mismatch(SelfTestPrimitive::Mac))
+    );
+}
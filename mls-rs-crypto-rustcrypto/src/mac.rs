@@ -8,6 +8,7 @@ use hmac::{
 };
 use mls_rs_core::crypto::CipherSuite;
 use sha2::{Digest, Sha256, Sha384, Sha512};
+use sha3::Sha3_256;
 
 use alloc::vec::Vec;
 
@@ -26,6 +27,8 @@ pub enum Hash {
     Sha256,
     Sha384,
     Sha512,
+    /// Not used by any registered cipher suite, and therefore never returned by [`Hash::new`].
+    Sha3_256,
 }
 
 impl Hash {
@@ -47,6 +50,7 @@ impl Hash {
             Hash::Sha256 => Sha256::digest(data).to_vec(),
             Hash::Sha384 => Sha384::digest(data).to_vec(),
             Hash::Sha512 => Sha512::digest(data).to_vec(),
+            Hash::Sha3_256 => Sha3_256::digest(data).to_vec(),
         }
     }
 
@@ -67,6 +71,11 @@ impl Hash {
                     .map_err(|_| HashError::InvalidHmacLength)?,
                 data,
             ),
+            Hash::Sha3_256 => generic_generate_tag(
+                SimpleHmac::<Sha3_256>::new_from_slice(key)
+                    .map_err(|_| HashError::InvalidHmacLength)?,
+                data,
+            ),
         }
     }
 }
@@ -79,3 +88,30 @@ fn generic_generate_tag<D: Digest + BlockSizeUser + FixedOutputReset>(
     let res = hmac.finalize().into_bytes().to_vec();
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Hash;
+
+    #[test]
+    fn sha3_256_known_answer() {
+        let expected =
+            hex::decode("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")
+                .unwrap();
+
+        assert_eq!(Hash::Sha3_256.hash(b"abc"), expected);
+    }
+
+    #[test]
+    fn hmac_sha3_256_known_answer() {
+        let expected =
+            hex::decode("c7d4072e788877ae3596bbb0da73b887c9171f93095b294ae857fbe2645e1ba5")
+                .unwrap();
+
+        let tag = Hash::Sha3_256
+            .mac(b"Jefe", b"what do ya want for nothing?")
+            .unwrap();
+
+        assert_eq!(tag, expected);
+    }
+}
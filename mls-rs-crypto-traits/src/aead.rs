@@ -6,7 +6,10 @@
 use mockall::automock;
 
 use alloc::vec::Vec;
-use mls_rs_core::{crypto::CipherSuite, error::IntoAnyError};
+use mls_rs_core::{
+    crypto::{aead_tag_len, CipherSuite},
+    error::IntoAnyError,
+};
 
 pub const AEAD_ID_EXPORT_ONLY: u16 = 0xFFFF;
 pub const AES_TAG_LEN: usize = 16;
@@ -42,6 +45,41 @@ pub trait AeadType: Send + Sync {
         nonce: &[u8],
     ) -> Result<Vec<u8>, Self::Error>;
 
+    /// Same as [seal](AeadType::seal), except that the tag is returned separately
+    /// as `(ciphertext, tag)`.
+    ///
+    /// The default implementation splits the output of [seal](AeadType::seal) using the
+    /// [tag length](aead_tag_len) of [aead_id](AeadType::aead_id). If the AEAD is unknown,
+    /// the whole output is returned as ciphertext along with an empty tag.
+    #[allow(clippy::needless_lifetimes)]
+    async fn seal_detached<'a>(
+        &self,
+        key: &[u8],
+        data: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), Self::Error> {
+        let mut ciphertext = self.seal(key, data, aad, nonce).await?;
+        let tag_len = aead_tag_len(self.aead_id()).unwrap_or(0);
+        let tag = ciphertext.split_off(ciphertext.len().saturating_sub(tag_len));
+
+        Ok((ciphertext, tag))
+    }
+
+    /// Same as [open](AeadType::open), except that the tag is passed separately.
+    #[allow(clippy::needless_lifetimes)]
+    async fn open_detached<'a>(
+        &self,
+        key: &[u8],
+        ciphertext: &[u8],
+        tag: &[u8],
+        aad: Option<&'a [u8]>,
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.open(key, &[ciphertext, tag].concat(), aad, nonce)
+            .await
+    }
+
     fn key_size(&self) -> usize;
     fn nonce_size(&self) -> usize;
 }
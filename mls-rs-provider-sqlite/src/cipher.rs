@@ -82,25 +82,49 @@ where
     }
 }
 
-impl<I> ConnectionStrategy for CipheredConnectionStrategy<I>
+impl<CS> CipheredConnectionStrategy<CS>
 where
-    I: ConnectionStrategy,
+    CS: ConnectionStrategy,
 {
-    fn make_connection(&self) -> Result<Connection, SqLiteDataStorageError> {
+    /// Re-encrypt the database under `new_key`.
+    ///
+    /// The database is first unlocked with `old_key`, which fails if
+    /// `old_key` is incorrect. SQLCipher rewrites the database within a single
+    /// transaction, so a failed rekey leaves it readable with `old_key`.
+    ///
+    /// Connections made by this strategy keep using the key it was created
+    /// with. A new strategy using `new_key` must be created after rekeying.
+    pub fn rekey(
+        &self,
+        old_key: &SqlCipherKey,
+        new_key: &SqlCipherKey,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.check_key(new_key)?;
+
+        let connection = self.connect(old_key)?;
+
+        connection
+            .pragma_update(None, "rekey", new_key.to_key_pragma_value().as_str())
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    fn check_key(&self, key: &SqlCipherKey) -> Result<(), SqLiteDataStorageError> {
         if self.cipher_config.plaintext_header_size > 0
-            && !matches!(self.cipher_config.key, SqlCipherKey::RawKeyWithSalt(_))
+            && !matches!(key, SqlCipherKey::RawKeyWithSalt(_))
         {
             return Err(SqLiteDataStorageError::SqlCipherKeyInvalidWithHeader);
         }
 
+        Ok(())
+    }
+
+    fn connect(&self, key: &SqlCipherKey) -> Result<Connection, SqLiteDataStorageError> {
+        self.check_key(key)?;
+
         let connection = self.inner.make_connection()?;
 
         connection
-            .pragma_update(
-                None,
-                "key",
-                self.cipher_config.key.to_key_pragma_value().as_str(),
-            )
+            .pragma_update(None, "key", key.to_key_pragma_value().as_str())
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
         connection
@@ -120,6 +144,15 @@ where
     }
 }
 
+impl<I> ConnectionStrategy for CipheredConnectionStrategy<I>
+where
+    I: ConnectionStrategy,
+{
+    fn make_connection(&self) -> Result<Connection, SqLiteDataStorageError> {
+        self.connect(&self.cipher_config.key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -128,6 +161,7 @@ mod tests {
     use crate::cipher::SqlCipherConfig;
     use crate::connection_strategy::{ConnectionStrategy, MemoryStrategy};
     use crate::test_utils::gen_rand_bytes;
+    use crate::SqLiteDataStorageEngine;
     use crate::{connection_strategy::FileConnectionStrategy, SqLiteDataStorageError};
 
     use super::{CipheredConnectionStrategy, SqlCipherKey};
@@ -219,4 +253,67 @@ mod tests {
             Err(SqLiteDataStorageError::SqlCipherKeyInvalidWithHeader)
         );
     }
+
+    #[test]
+    fn sql_cipher_rekey() {
+        let temp_file = NamedTempFile::new().unwrap();
+
+        let old_key = SqlCipherKey::RawKey(gen_rand_bytes(32).try_into().unwrap());
+        let new_key = SqlCipherKey::Passphrase("new key".to_string());
+
+        let strategy = |key: &SqlCipherKey| {
+            CipheredConnectionStrategy::new(
+                FileConnectionStrategy::new(temp_file.path()),
+                SqlCipherConfig::new(key.clone()),
+            )
+        };
+
+        SqLiteDataStorageEngine::new(strategy(&old_key))
+            .unwrap()
+            .application_data_storage()
+            .unwrap()
+            .insert("key".to_string(), b"value".to_vec())
+            .unwrap();
+
+        strategy(&old_key).rekey(&old_key, &new_key).unwrap();
+
+        let value = SqLiteDataStorageEngine::new(strategy(&new_key))
+            .unwrap()
+            .application_data_storage()
+            .unwrap()
+            .get("key")
+            .unwrap();
+
+        assert_eq!(value, Some(b"value".to_vec()));
+
+        assert_matches!(
+            strategy(&old_key).make_connection(),
+            Err(SqLiteDataStorageError::SqlEngineError(_))
+        );
+    }
+
+    #[test]
+    fn sql_cipher_rekey_with_incorrect_key() {
+        let temp_file = NamedTempFile::new().unwrap();
+
+        let key = SqlCipherKey::Passphrase("correct".to_string());
+        let incorrect_key = SqlCipherKey::Passphrase("incorrect".to_string());
+        let new_key = SqlCipherKey::Passphrase("new key".to_string());
+
+        let strategy = CipheredConnectionStrategy::new(
+            FileConnectionStrategy::new(temp_file.path()),
+            SqlCipherConfig::new(key.clone()),
+        );
+
+        let connection = strategy.make_connection().unwrap();
+        connection.execute("CREATE TABLE test(item)", []).unwrap();
+
+        assert_matches!(
+            strategy.rekey(&incorrect_key, &new_key),
+            Err(SqLiteDataStorageError::SqlEngineError(_))
+        );
+
+        // The database is still readable with the original key
+        strategy.make_connection().unwrap();
+    }
 }
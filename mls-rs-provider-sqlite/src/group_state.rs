@@ -55,16 +55,105 @@ impl SqLiteGroupStateStorage {
         Ok(res)
     }
 
-    /// Delete a group from storage.
+    /// Delete a group and all of its stored epochs from storage.
     pub fn delete_group(&self, group_id: &[u8]) -> Result<(), SqLiteDataStorageError> {
+        let mut connection = self.connection.lock().unwrap();
+
+        let transaction = connection
+            .transaction()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        // The cascade on the epoch table only applies when foreign key
+        // enforcement is enabled, which depends on how SQLite was built
+        transaction
+            .execute("DELETE FROM epoch WHERE group_id = ?", params![group_id])
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        transaction
+            .execute(
+                "DELETE FROM mls_group WHERE group_id = ?",
+                params![group_id],
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        transaction
+            .commit()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// List the `(group_id, epoch_id)` pairs of stored epochs that belong
+    /// to a group that no longer exists.
+    pub fn find_orphaned_epochs(&self) -> Result<Vec<(Vec<u8>, u64)>, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        let mut statement = connection
+            .prepare(
+                "SELECT group_id, epoch_id FROM epoch WHERE group_id NOT IN (SELECT group_id FROM mls_group)",
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        let res = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?;
+
+        Ok(res)
+    }
+
+    /// Delete stored epochs of every group, keeping only the `keep_last_n`
+    /// most recent epochs of each group. Returns the number of deleted epochs.
+    ///
+    /// The deletion runs as a single statement and is therefore atomic.
+    pub fn prune_epochs(&self, keep_last_n: u64) -> Result<usize, SqLiteDataStorageError> {
         let connection = self.connection.lock().unwrap();
 
         connection
             .execute(
-                "DELETE FROM mls_group WHERE group_id = ?",
+                "DELETE FROM epoch WHERE epoch_id + ? <= \
+                (SELECT MAX(latest.epoch_id) FROM epoch latest WHERE latest.group_id = epoch.group_id)",
+                params![keep_last_n],
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Number of epochs stored for `group_id`.
+    pub fn epoch_count(&self, group_id: &[u8]) -> Result<u64, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        connection
+            .query_row(
+                "SELECT COUNT(*) FROM epoch WHERE group_id = ?",
+                params![group_id],
+                |row| row.get::<_, u64>(0),
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Total size in bytes of the epoch data stored for `group_id`.
+    pub fn total_epoch_bytes(&self, group_id: &[u8]) -> Result<u64, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        connection
+            .query_row(
+                "SELECT COALESCE(SUM(LENGTH(epoch_data)), 0) FROM epoch WHERE group_id = ?",
                 params![group_id],
+                |row| row.get::<_, u64>(0),
             )
-            .map(|_| ())
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Rebuild the database file to release the space left behind by
+    /// deleted groups and epochs.
+    ///
+    /// This runs `VACUUM`, which rewrites the whole database and blocks all
+    /// other use of this storage until it completes. It must not be called
+    /// while a transaction is open on the same database.
+    pub fn compact(&self) -> Result<(), SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        connection
+            .execute_batch("VACUUM")
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
 
@@ -137,17 +226,21 @@ impl SqLiteGroupStateStorage {
             params![group_id, group_snapshot],
         ).map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
-        // Insert new epochs as needed
+        // Insert new epochs as needed. Re-inserting an epoch with identical data is a no-op so
+        // that retrying the same write succeeds, while conflicting data is still rejected.
         for epoch in inserts {
             max_epoch_id = Some(epoch.id);
 
-            transaction
+            let inserted = transaction
                 .execute(
-                    "INSERT INTO epoch (group_id, epoch_id, epoch_data) VALUES (?, ?, ?)",
+                    "INSERT INTO epoch (group_id, epoch_id, epoch_data) VALUES (?, ?, ?) ON CONFLICT(group_id, epoch_id) DO UPDATE SET epoch_data=excluded.epoch_data WHERE epoch_data=excluded.epoch_data",
                     params![group_id, epoch.id, epoch.data],
                 )
-                .map(|_| ())
                 .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+            if inserted == 0 {
+                return Err(SqLiteDataStorageError::DuplicateEpoch(epoch.id));
+            }
         }
 
         // Update existing epochs as needed
@@ -216,8 +309,13 @@ impl GroupStateStorage for SqLiteGroupStateStorage {
 mod tests {
     use crate::{
         SqLiteDataStorageEngine,
-        {connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes},
+        {
+            connection_strategy::{FileConnectionStrategy, MemoryStrategy},
+            test_utils::gen_rand_bytes,
+        },
     };
+    use assert_matches::assert_matches;
+    use tempfile::NamedTempFile;
 
     use super::*;
 
@@ -490,4 +588,224 @@ mod tests {
 
         assert!(test_data.storage.group_ids().unwrap().is_empty());
     }
+
+    #[test]
+    fn delete_group_removes_epochs() {
+        let test_data = setup_group_storage_test();
+
+        test_data.storage.delete_group(&test_data.group_id).unwrap();
+
+        assert!(test_data
+            .storage
+            .get_epoch_data(&test_data.group_id, 0)
+            .unwrap()
+            .is_none());
+
+        assert!(test_data.storage.find_orphaned_epochs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn orphaned_epochs_are_found() {
+        let test_data = setup_group_storage_test();
+        let other_group = test_group_id();
+
+        test_data
+            .storage
+            .update_group_state(&other_group, test_snapshot(), vec![test_epoch(0)], vec![])
+            .unwrap();
+
+        assert!(test_data.storage.find_orphaned_epochs().unwrap().is_empty());
+
+        // Remove the group row without cascading to its epochs
+        {
+            let connection = test_data.storage.connection.lock().unwrap();
+
+            connection
+                .pragma_update(None, "foreign_keys", false)
+                .unwrap();
+
+            connection
+                .execute(
+                    "DELETE FROM mls_group WHERE group_id = ?",
+                    params![test_data.group_id],
+                )
+                .unwrap();
+        }
+
+        let orphaned = test_data.storage.find_orphaned_epochs().unwrap();
+
+        assert_eq!(orphaned, vec![(test_data.group_id, 0)]);
+    }
+
+    #[test]
+    fn epochs_can_be_pruned_across_groups() {
+        let storage = get_test_storage().with_max_epoch_retention(10);
+        let group_ids = [test_group_id(), test_group_id(), test_group_id()];
+
+        for group_id in group_ids.iter() {
+            storage
+                .update_group_state(
+                    group_id,
+                    test_snapshot(),
+                    (0..10).map(test_epoch).collect(),
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(storage.prune_epochs(3).unwrap(), 21);
+
+        for group_id in group_ids.iter() {
+            for epoch_id in 0..10 {
+                let stored = storage.get_epoch_data(group_id, epoch_id).unwrap();
+                assert_eq!(stored.is_some(), epoch_id >= 7);
+            }
+        }
+
+        // Pruning again is a no-op
+        assert_eq!(storage.prune_epochs(3).unwrap(), 0);
+    }
+
+    #[test]
+    fn epoch_count_and_size_are_reported() {
+        let test_data = setup_group_storage_test();
+
+        let epoch_1 = EpochRecord::new(1, vec![0u8; 100]);
+        let epoch_2 = EpochRecord::new(2, vec![0u8; 50]);
+
+        test_data
+            .storage
+            .update_group_state(
+                &test_data.group_id,
+                test_snapshot(),
+                vec![epoch_1, epoch_2],
+                vec![],
+            )
+            .unwrap();
+
+        let storage = &test_data.storage;
+
+        assert_eq!(storage.epoch_count(&test_data.group_id).unwrap(), 3);
+
+        assert_eq!(
+            storage.total_epoch_bytes(&test_data.group_id).unwrap(),
+            test_data.epoch_0.data.len() as u64 + 150
+        );
+
+        // Unknown groups have no epochs
+        assert_eq!(storage.epoch_count(&[0, 1, 2]).unwrap(), 0);
+        assert_eq!(storage.total_epoch_bytes(&[0, 1, 2]).unwrap(), 0);
+    }
+
+    #[test]
+    fn compact_releases_space_of_deleted_groups() {
+        let temp_file = NamedTempFile::new().unwrap();
+
+        let storage = SqLiteDataStorageEngine::new(FileConnectionStrategy::new(temp_file.path()))
+            .unwrap()
+            .group_state_storage()
+            .unwrap();
+
+        let page_count = |storage: &SqLiteGroupStateStorage| -> u64 {
+            storage
+                .connection
+                .lock()
+                .unwrap()
+                .pragma_query_value(None, "page_count", |row| row.get(0))
+                .unwrap()
+        };
+
+        let group_id = test_group_id();
+
+        let epochs = (0..3)
+            .map(|id| EpochRecord::new(id, gen_rand_bytes(100_000)))
+            .collect();
+
+        storage
+            .update_group_state(&group_id, gen_rand_bytes(1_000_000), epochs, vec![])
+            .unwrap();
+
+        storage.delete_group(&group_id).unwrap();
+
+        let before = page_count(&storage);
+
+        storage.compact().unwrap();
+
+        assert!(page_count(&storage) < before);
+    }
+
+    #[test]
+    fn identical_write_can_be_retried() {
+        let mut test_data = setup_group_storage_test();
+        let epoch_1 = test_epoch(1);
+        let snapshot = test_snapshot();
+
+        let state = GroupState {
+            id: test_data.group_id.clone(),
+            data: snapshot.clone(),
+        };
+
+        for _ in 0..2 {
+            test_data
+                .storage
+                .write(state.clone(), vec![epoch_1.clone()], vec![])
+                .unwrap();
+        }
+
+        assert_eq!(
+            test_data
+                .storage
+                .get_snapshot_data(&test_data.group_id)
+                .unwrap()
+                .unwrap(),
+            snapshot
+        );
+
+        assert_eq!(
+            test_data
+                .storage
+                .get_epoch_data(&test_data.group_id, 1)
+                .unwrap()
+                .unwrap(),
+            epoch_1.data
+        );
+
+        assert_eq!(
+            test_data.storage.max_epoch_id(&test_data.group_id).unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn duplicate_epoch_insert_is_rejected() {
+        let test_data = setup_group_storage_test();
+
+        let res = test_data.storage.update_group_state(
+            &test_data.group_id,
+            test_snapshot(),
+            vec![test_epoch(0)],
+            vec![],
+        );
+
+        assert_matches!(res, Err(SqLiteDataStorageError::DuplicateEpoch(0)));
+
+        // The failed transaction leaves the stored state untouched
+        assert_eq!(
+            test_data
+                .storage
+                .get_snapshot_data(&test_data.group_id)
+                .unwrap()
+                .unwrap(),
+            test_data.snapshot
+        );
+
+        assert_eq!(
+            test_data
+                .storage
+                .get_epoch_data(&test_data.group_id, 0)
+                .unwrap()
+                .unwrap(),
+            test_data.epoch_0.data
+        );
+    }
 }
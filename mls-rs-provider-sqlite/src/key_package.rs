@@ -29,18 +29,41 @@ impl SqLiteKeyPackageStorage {
         &mut self,
         id: &[u8],
         key_package: KeyPackageData,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.insert_with_kind(id, key_package, false)
+    }
+
+    /// Insert a last resort key package.
+    ///
+    /// Last resort key packages can be used to join any number of groups and
+    /// are kept in storage after a successful join. Use
+    /// [`usage_count`](Self::usage_count) to detect unexpected reuse.
+    pub fn insert_last_resort(
+        &mut self,
+        id: &[u8],
+        key_package: KeyPackageData,
+    ) -> Result<(), SqLiteDataStorageError> {
+        self.insert_with_kind(id, key_package, true)
+    }
+
+    fn insert_with_kind(
+        &mut self,
+        id: &[u8],
+        key_package: KeyPackageData,
+        last_resort: bool,
     ) -> Result<(), SqLiteDataStorageError> {
         let connection = self.connection.lock().unwrap();
 
         connection
             .execute(
-                "INSERT INTO key_package (id, expiration, data) VALUES (?,?,?)",
+                "INSERT INTO key_package (id, expiration, data, last_resort) VALUES (?,?,?,?)",
                 params![
                     id,
                     key_package.expiration,
                     key_package
                         .mls_encode_to_vec()
-                        .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?
+                        .map_err(|e| SqLiteDataStorageError::DataConversionError(e.into()))?,
+                    last_resort
                 ],
             )
             .map(|_| ())
@@ -65,6 +88,78 @@ impl SqLiteKeyPackageStorage {
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
     }
 
+    /// Record a successful join using the key package with the given id.
+    ///
+    /// The usage count is kept in a separate table that holds no key material.
+    /// One-time key packages are marked as consumed and deleted from storage.
+    fn record_use(&self, id: &[u8]) -> Result<(), SqLiteDataStorageError> {
+        let mut connection = self.connection.lock().unwrap();
+
+        let transaction = connection
+            .transaction()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        transaction
+            .execute(
+                "INSERT INTO key_package_usage (id, used_count, consumed)
+                SELECT id, 1, NOT last_resort FROM key_package WHERE id = ?
+                ON CONFLICT (id) DO UPDATE SET used_count = used_count + 1",
+                params![id],
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        transaction
+            .execute(
+                "DELETE FROM key_package WHERE id = ? AND last_resort = 0",
+                params![id],
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
+
+        transaction
+            .commit()
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Number of groups joined using the key package with the given id, or
+    /// `None` if it was neither stored nor used.
+    ///
+    /// Usage is still reported after a one-time key package was deleted by a
+    /// successful join.
+    pub fn usage_count(&self, id: &[u8]) -> Result<Option<u64>, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        connection
+            .query_row(
+                "SELECT COALESCE(
+                    (SELECT used_count FROM key_package_usage WHERE id = ?1),
+                    (SELECT 0 FROM key_package WHERE id = ?1)
+                )",
+                params![id],
+                |row| row.get(0),
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
+    /// Whether the one-time key package with the given id was already used to
+    /// join a group, or `None` if it was neither stored nor used.
+    ///
+    /// Consumed key packages are deleted and no longer returned by
+    /// [`KeyPackageStorage::get`].
+    pub fn is_consumed(&self, id: &[u8]) -> Result<Option<bool>, SqLiteDataStorageError> {
+        let connection = self.connection.lock().unwrap();
+
+        connection
+            .query_row(
+                "SELECT COALESCE(
+                    (SELECT consumed FROM key_package_usage WHERE id = ?1),
+                    (SELECT 0 FROM key_package WHERE id = ?1)
+                )",
+                params![id],
+                |row| row.get(0),
+            )
+            .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+    }
+
     /// Delete a specific key package from storage based on it's id.
     pub fn delete(&self, id: &[u8]) -> Result<(), SqLiteDataStorageError> {
         let connection = self.connection.lock().unwrap();
@@ -106,7 +201,8 @@ impl KeyPackageStorage for SqLiteKeyPackageStorage {
     }
 
     async fn delete(&mut self, id: &[u8]) -> Result<(), Self::Error> {
-        (*self).delete(id)
+        // Called after a successful join. Last resort key packages are kept
+        self.record_use(id)
     }
 }
 
@@ -118,7 +214,10 @@ mod tests {
         {connection_strategy::MemoryStrategy, test_utils::gen_rand_bytes},
     };
     use assert_matches::assert_matches;
-    use mls_rs_core::{crypto::HpkeSecretKey, key_package::KeyPackageData};
+    use mls_rs_core::{
+        crypto::HpkeSecretKey,
+        key_package::{KeyPackageData, KeyPackageStorage},
+    };
 
     fn test_storage() -> SqLiteKeyPackageStorage {
         SqLiteDataStorageEngine::new(MemoryStrategy)
@@ -215,4 +314,70 @@ mod tests {
         assert!(storage.get(&data[2].0).unwrap().is_none());
         assert!(storage.get(&data[3].0).unwrap().is_none());
     }
+
+    #[test]
+    fn key_package_usage_tracking() {
+        let mut storage = test_storage();
+        let (one_time_id, one_time) = test_key_package();
+        let (last_resort_id, last_resort) = test_key_package();
+
+        storage.insert(&one_time_id, one_time).unwrap();
+
+        storage
+            .insert_last_resort(&last_resort_id, last_resort)
+            .unwrap();
+
+        KeyPackageStorage::get(&storage, &one_time_id)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(storage.usage_count(&one_time_id).unwrap(), Some(0));
+        assert_eq!(storage.is_consumed(&one_time_id).unwrap(), Some(false));
+
+        KeyPackageStorage::delete(&mut storage, &one_time_id).unwrap();
+
+        assert_eq!(storage.usage_count(&one_time_id).unwrap(), Some(1));
+        assert_eq!(storage.is_consumed(&one_time_id).unwrap(), Some(true));
+
+        for _ in 0..2 {
+            KeyPackageStorage::get(&storage, &last_resort_id)
+                .unwrap()
+                .unwrap();
+
+            KeyPackageStorage::delete(&mut storage, &last_resort_id).unwrap();
+        }
+
+        assert_eq!(storage.usage_count(&last_resort_id).unwrap(), Some(2));
+        assert_eq!(storage.is_consumed(&last_resort_id).unwrap(), Some(false));
+
+        let (missing_id, _) = test_key_package();
+        assert_eq!(storage.usage_count(&missing_id).unwrap(), None);
+        assert_eq!(storage.is_consumed(&missing_id).unwrap(), None);
+    }
+
+    #[test]
+    fn key_package_data_is_erased_after_join() {
+        let mut storage = test_storage();
+        let (key_package_id, key_package) = test_key_package();
+
+        storage.insert(&key_package_id, key_package).unwrap();
+        KeyPackageStorage::delete(&mut storage, &key_package_id).unwrap();
+
+        assert!(KeyPackageStorage::get(&storage, &key_package_id)
+            .unwrap()
+            .is_none());
+
+        let stored_rows: u64 = storage
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM key_package WHERE id = ?",
+                [&key_package_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+
+        assert_eq!(stored_rows, 0);
+    }
 }
@@ -42,6 +42,9 @@ pub enum SqLiteDataStorageError {
     #[error(transparent)]
     /// Stored data is not compatible with the expected data type.
     DataConversionError(Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("epoch {0} is already stored for this group")]
+    /// Attempted to insert an epoch that already exists for the group.
+    DuplicateEpoch(u64),
     #[cfg(any(feature = "sqlcipher", feature = "sqlcipher-bundled"))]
     #[error("invalid key, must use SqlCipherKey::RawKeyWithSalt with plaintext_header_size > 0")]
     /// Invalid SQLCipher key header.
@@ -83,10 +86,14 @@ where
             .pragma_query_value(None, "user_version", |rows| rows.get::<_, u32>(0))
             .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))?;
 
-        if current_schema != 1 {
+        if current_schema < 1 {
             create_tables_v1(&connection)?;
         }
 
+        if current_schema < 2 {
+            migrate_tables_v2(&connection)?;
+        }
+
         Ok(connection)
     }
 
@@ -150,6 +157,22 @@ fn create_tables_v1(connection: &Connection) -> Result<(), SqLiteDataStorageErro
         .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
 }
 
+fn migrate_tables_v2(connection: &Connection) -> Result<(), SqLiteDataStorageError> {
+    connection
+        .execute_batch(
+            "BEGIN;
+            ALTER TABLE key_package ADD COLUMN last_resort INTEGER NOT NULL DEFAULT 0;
+            CREATE TABLE key_package_usage (
+                id BLOB PRIMARY KEY,
+                used_count INTEGER NOT NULL,
+                consumed INTEGER NOT NULL
+            ) WITHOUT ROWID;
+            PRAGMA user_version = 2;
+            COMMIT;",
+        )
+        .map_err(|e| SqLiteDataStorageError::SqlEngineError(e.into()))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{connection_strategy::MemoryStrategy, SqLiteDataStorageEngine};
@@ -168,6 +191,6 @@ mod tests {
             .pragma_query_value(None, "user_version", |rows| rows.get::<_, u32>(0))
             .unwrap();
 
-        assert_eq!(current_schema, 1);
+        assert_eq!(current_schema, 2);
     }
 }
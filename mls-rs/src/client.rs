@@ -17,6 +17,7 @@ use crate::group::{snapshot::Snapshot, ExportedTree, Group, NewMemberInfo};
 use crate::identity::SigningIdentity;
 use crate::key_package::{KeyPackageGeneration, KeyPackageGenerator};
 use crate::protocol_version::ProtocolVersion;
+use crate::psk::ResumptionPsk;
 use crate::tree_kem::node::NodeIndex;
 use alloc::vec::Vec;
 use mls_rs_codec::MlsDecode;
@@ -29,6 +30,9 @@ use mls_rs_core::key_package::KeyPackageStorage;
 
 use crate::group::external_commit::ExternalCommitBuilder;
 
+#[cfg(feature = "std")]
+use crate::group::group_info_cache::GroupInfoCache;
+
 #[cfg(feature = "by_ref_proposal")]
 use alloc::boxed::Box;
 
@@ -53,8 +57,14 @@ pub enum MlsError {
     SerializationError(AnyError),
     #[cfg_attr(feature = "std", error(transparent))]
     ExtensionError(AnyError),
-    #[cfg_attr(feature = "std", error("Cipher suite does not match"))]
-    CipherSuiteMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("Cipher suite does not match, expected {expected:?} but found {found:?}")
+    )]
+    CipherSuiteMismatch {
+        expected: CipherSuite,
+        found: CipherSuite,
+    },
     #[cfg_attr(feature = "std", error("Invalid commit, missing required path"))]
     CommitMissingPath,
     #[cfg_attr(feature = "std", error("plaintext message for incorrect epoch"))]
@@ -69,6 +79,16 @@ pub enum MlsError {
     InvalidTreeKemPrivateKey,
     #[cfg_attr(feature = "std", error("key package not found, unable to process"))]
     WelcomeKeyPackageNotFound,
+    #[cfg_attr(
+        feature = "std",
+        error("welcome contains multiple secrets for the same key package")
+    )]
+    AmbiguousWelcome,
+    #[cfg_attr(
+        feature = "std",
+        error("credential of {0} bytes exceeds the identity provider limit")
+    )]
+    CredentialTooLarge(usize),
     #[cfg_attr(feature = "std", error("leaf not found in tree for index {0}"))]
     LeafNotFound(u32),
     #[cfg_attr(feature = "std", error("message from self can't be processed"))]
@@ -91,6 +111,11 @@ pub enum MlsError {
     ProtocolVersionMismatch,
     #[cfg_attr(feature = "std", error("Unsupported cipher suite {0:?}"))]
     UnsupportedCipherSuite(CipherSuite),
+    #[cfg_attr(
+        feature = "std",
+        error("Cipher suite {0:?} is below the minimum accepted strength")
+    )]
+    CipherSuiteTooWeak(CipherSuite),
     #[cfg_attr(feature = "std", error("Signing key of external sender is unknown"))]
     UnknownSigningIdentityForExternalSender,
     #[cfg_attr(
@@ -131,6 +156,8 @@ pub enum MlsError {
         )
     )]
     GroupUsedAfterReInit,
+    #[cfg_attr(feature = "std", error("Group is not active, see Group::status"))]
+    GroupNotActive,
     #[cfg_attr(feature = "std", error("Pending ReIinit not found."))]
     PendingReInitNotFound,
     #[cfg_attr(
@@ -153,22 +180,68 @@ pub enum MlsError {
     MembershipTagForNonMember,
     #[cfg_attr(feature = "std", error("No member found for given identity id."))]
     MemberNotFound,
+    #[cfg_attr(feature = "std", error("Multiple members found for given identity."))]
+    AmbiguousIdentity,
+    #[cfg_attr(
+        feature = "std",
+        error("Application message chunk size must be nonzero")
+    )]
+    InvalidApplicationChunkSize,
+    #[cfg_attr(
+        feature = "std",
+        error("Expected application message chunk {expected}, found {found}")
+    )]
+    UnexpectedApplicationChunk { expected: u32, found: u32 },
+    #[cfg_attr(
+        feature = "std",
+        error("Application message chunk sent by unexpected member {0}")
+    )]
+    ApplicationChunkSenderMismatch(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("Application message chunk belongs to another message")
+    )]
+    ApplicationChunkMessageMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("Expected {expected} application message chunks, found {found}")
+    )]
+    ApplicationChunkCountMismatch { expected: u32, found: u32 },
+    #[cfg_attr(
+        feature = "std",
+        error("Application message consists of too many chunks: {0}")
+    )]
+    TooManyApplicationChunks(u32),
+    #[cfg_attr(
+        feature = "std",
+        error("Reassembled application message is too large: {0} bytes")
+    )]
+    ApplicationMessageTooLarge(usize),
     #[cfg_attr(feature = "std", error("group not found"))]
     GroupNotFound,
     #[cfg_attr(feature = "std", error("unexpected PSK ID"))]
     UnexpectedPskId,
     #[cfg_attr(feature = "std", error("invalid sender for content type"))]
     InvalidSender,
+    #[cfg_attr(
+        feature = "std",
+        error("fixed block padding requires a non-zero block size")
+    )]
+    InvalidPaddingBlockSize,
     #[cfg_attr(feature = "std", error("GroupID mismatch"))]
     GroupIdMismatch,
     #[cfg_attr(feature = "std", error("storage retention can not be zero"))]
     NonZeroRetentionRequired,
     #[cfg_attr(feature = "std", error("Too many PSK IDs to compute PSK secret"))]
     TooManyPskIds,
+    #[cfg_attr(feature = "std", error("Commit references more PSKs than allowed"))]
+    TooManyPsks,
     #[cfg_attr(feature = "std", error("Missing required Psk"))]
     MissingRequiredPsk,
     #[cfg_attr(feature = "std", error("Old group state not found"))]
     OldGroupStateNotFound,
+    #[cfg_attr(feature = "std", error("resumption secrets not found: {0:?}"))]
+    ResumptionPsksNotFound(Vec<ResumptionPsk>),
     #[cfg_attr(feature = "std", error("leaf secret already consumed"))]
     InvalidLeafConsumption,
     #[cfg_attr(feature = "std", error("key not available, invalid generation {0}"))]
@@ -196,6 +269,11 @@ pub enum MlsError {
     RequiredProposalNotFound(ProposalType),
     #[cfg_attr(feature = "std", error("required credential not found"))]
     RequiredCredentialNotFound(CredentialType),
+    #[cfg_attr(
+        feature = "std",
+        error("member at index {0} does not support the required capabilities")
+    )]
+    ExistingMemberViolatesCapabilities(u32),
     #[cfg_attr(feature = "std", error("capabilities must describe extensions used"))]
     ExtensionNotInCapabilities(ExtensionType),
     #[cfg_attr(feature = "std", error("expected non-blank node"))]
@@ -242,8 +320,23 @@ pub enum MlsError {
     PubKeyMismatch,
     #[cfg_attr(feature = "std", error("tree hash mismatch"))]
     TreeHashMismatch,
+    #[cfg_attr(
+        feature = "std",
+        error("group context is inconsistent with the ratchet tree")
+    )]
+    InconsistentGroupContext,
     #[cfg_attr(feature = "std", error("bad update: no suitable secret key"))]
     UpdateErrorNoSecretKey,
+    #[cfg_attr(
+        feature = "std",
+        error("update does not change the leaf's encryption key")
+    )]
+    NoKeyChangeInUpdate,
+    #[cfg_attr(
+        feature = "std",
+        error("update path secret was encrypted to a public key that is no longer current")
+    )]
+    StalePathEncryption,
     #[cfg_attr(feature = "std", error("invalid lca, not found on direct path"))]
     LcaNotFoundInDirectPath,
     #[cfg_attr(feature = "std", error("update path parent hash mismatch"))]
@@ -264,11 +357,21 @@ pub enum MlsError {
     InvalidTypeOrUsageInPreSharedKeyProposal,
     #[cfg_attr(feature = "std", error("psk nonce length does not match cipher suite"))]
     InvalidPskNonceLength,
+    #[cfg_attr(
+        feature = "std",
+        error("reuse guard in sender data has invalid length")
+    )]
+    InvalidReuseGuard,
     #[cfg_attr(
         feature = "std",
         error("ReInit proposal protocol version is less than the version of the original group")
     )]
     InvalidProtocolVersionInReInit,
+    #[cfg_attr(
+        feature = "std",
+        error("ReInit proposal downgrades the group to a weaker cipher suite")
+    )]
+    DowngradeDetected,
     #[cfg_attr(feature = "std", error("More than one proposal applying to leaf: {0}"))]
     MoreThanOneProposalForLeaf(u32),
     #[cfg_attr(
@@ -315,6 +418,13 @@ pub enum MlsError {
     UnsupportedGroupExtension(ExtensionType),
     #[cfg_attr(feature = "std", error("Unsupported custom proposal type {0:?}"))]
     UnsupportedCustomProposal(ProposalType),
+    #[cfg_attr(
+        feature = "std",
+        error("AppAck proposals are not supported by all members")
+    )]
+    UnsupportedAppAck,
+    #[cfg_attr(feature = "std", error("Invalid message range in AppAck proposal"))]
+    InvalidMessageRange,
     #[cfg_attr(feature = "std", error("by-ref proposal not found"))]
     ProposalNotFound,
     #[cfg_attr(
@@ -373,6 +483,8 @@ pub struct Client<C> {
     pub(crate) signing_identity: Option<(SigningIdentity, CipherSuite)>,
     pub(crate) signer: Option<SignatureSecretKey>,
     pub(crate) version: ProtocolVersion,
+    #[cfg(feature = "std")]
+    pub(crate) group_info_cache: GroupInfoCache,
 }
 
 impl Client<()> {
@@ -394,11 +506,16 @@ where
         signing_identity: Option<(SigningIdentity, CipherSuite)>,
         version: ProtocolVersion,
     ) -> Self {
+        #[cfg(feature = "std")]
+        let group_info_cache = GroupInfoCache::new(config.group_info_cache_ttl_in_s());
+
         Client {
             config,
             signer,
             signing_identity,
             version,
+            #[cfg(feature = "std")]
+            group_info_cache,
         }
     }
 
@@ -584,13 +701,7 @@ where
         &self,
         group_info_msg: MlsMessage,
     ) -> Result<(Group<C>, MlsMessage), MlsError> {
-        ExternalCommitBuilder::new(
-            self.signer()?.clone(),
-            self.signing_identity()?.0.clone(),
-            self.config.clone(),
-        )
-        .build(group_info_msg)
-        .await
+        self.external_commit_builder()?.build(group_info_msg).await
     }
 
     pub fn external_commit_builder(&self) -> Result<ExternalCommitBuilder<C>, MlsError> {
@@ -598,6 +709,8 @@ where
             self.signer()?.clone(),
             self.signing_identity()?.0.clone(),
             self.config.clone(),
+            #[cfg(feature = "std")]
+            self.group_info_cache.clone(),
         ))
     }
 
@@ -645,12 +758,26 @@ where
 
         let cipher_suite = group_info.group_context.cipher_suite;
 
+        crate::group::check_cipher_suite_strength(&self.config, cipher_suite)?;
+
         let cipher_suite_provider = self
             .config
             .crypto_provider()
             .cipher_suite_provider(cipher_suite)
             .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))?;
 
+        #[cfg(feature = "std")]
+        self.group_info_cache
+            .validate(
+                protocol_version,
+                group_info,
+                tree_data,
+                &self.config.identity_provider(),
+                &cipher_suite_provider,
+            )
+            .await?;
+
+        #[cfg(not(feature = "std"))]
         crate::group::validate_group_info_joiner(
             protocol_version,
             group_info,
@@ -881,6 +1008,23 @@ mod tests {
             .any(|member| member.signing_identity == bob_identity))
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn new_member_add_proposal_below_min_cipher_suite_strength_fails() {
+        let alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .min_cipher_suite_strength(256)
+            .build();
+
+        let group_info = alice_group.group.group_info_message(true).await.unwrap();
+        let res = bob.external_add_proposal(&group_info, None, vec![]).await;
+
+        assert_matches!(res, Err(MlsError::CipherSuiteTooWeak(cs)) if cs == TEST_CIPHER_SUITE);
+    }
+
     #[cfg(feature = "psk")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn join_via_external_commit(do_remove: bool, with_psk: bool) -> Result<(), MlsError> {
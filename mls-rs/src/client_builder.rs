@@ -354,6 +354,61 @@ impl<C: IntoConfig> ClientBuilder<C> {
         ClientBuilder(c)
     }
 
+    /// Set the maximum number of pre-shared keys a commit may reference.
+    ///
+    /// Commits exceeding this limit are rejected both when they are created and
+    /// when they are received. By default, the protocol maximum of `u16::MAX`
+    /// is used.
+    #[cfg(feature = "psk")]
+    pub fn max_psks_per_commit(
+        self,
+        max_psks_per_commit: u16,
+    ) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.max_psks_per_commit = max_psks_per_commit;
+        ClientBuilder(c)
+    }
+
+    /// Cache successful identity validations for `ttl_in_s` seconds.
+    ///
+    /// Within the TTL, a group does not ask the identity provider to validate
+    /// a signing identity it already validated. The entry for a member is
+    /// dropped as soon as the member's leaf is replaced by an update, and it is
+    /// not used for timestamps after the
+    /// [credential expiration](IdentityProvider::credential_expiration). By
+    /// default, every validation is forwarded to the identity provider.
+    #[cfg(feature = "std")]
+    pub fn identity_cache_ttl(self, ttl_in_s: u64) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.identity_cache_ttl_in_s = Some(ttl_in_s);
+        ClientBuilder(c)
+    }
+
+    /// Cache group infos validated before joining a group for `ttl_in_s`
+    /// seconds.
+    ///
+    /// Within the TTL, a client does not validate again a group info and
+    /// ratchet tree identical to ones it already validated when creating an
+    /// external commit or an external add proposal. Only the latest validated
+    /// epoch of each group is kept. By default, every group info is validated.
+    #[cfg(feature = "std")]
+    pub fn group_info_cache_ttl(self, ttl_in_s: u64) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.group_info_cache_ttl_in_s = Some(ttl_in_s);
+        ClientBuilder(c)
+    }
+
+    /// Refuse to join groups whose cipher suite has a security level below
+    /// `bits`, as given by [`CipherSuite::security_level`].
+    ///
+    /// When set, cipher suites without a known security level are refused as
+    /// well. By default, groups using any supported cipher suite can be joined.
+    pub fn min_cipher_suite_strength(self, bits: u16) -> ClientBuilder<IntoConfigOutput<C>> {
+        let mut c = self.0.into_config();
+        c.0.settings.min_cipher_suite_strength = Some(bits);
+        ClientBuilder(c)
+    }
+
     /// Set the key package repository to be used by the client.
     ///
     /// By default, an in-memory repository is used.
@@ -763,6 +818,25 @@ where
     fn supported_custom_proposals(&self) -> Vec<crate::group::proposal::ProposalType> {
         self.settings.custom_proposal_types.clone()
     }
+
+    #[cfg(feature = "psk")]
+    fn max_psks_per_commit(&self) -> u16 {
+        self.settings.max_psks_per_commit
+    }
+
+    #[cfg(feature = "std")]
+    fn identity_cache_ttl_in_s(&self) -> Option<u64> {
+        self.settings.identity_cache_ttl_in_s
+    }
+
+    #[cfg(feature = "std")]
+    fn group_info_cache_ttl_in_s(&self) -> Option<u64> {
+        self.settings.group_info_cache_ttl_in_s
+    }
+
+    fn min_cipher_suite_strength(&self) -> Option<u16> {
+        self.settings.min_cipher_suite_strength
+    }
 }
 
 impl<Kpr, Ps, Gss, Ip, Pr, Cp> Sealed for Config<Kpr, Ps, Gss, Ip, Pr, Cp> {}
@@ -863,6 +937,25 @@ impl<T: MlsConfig> ClientConfig for T {
     fn supported_credential_types(&self) -> Vec<CredentialType> {
         self.get().supported_credential_types()
     }
+
+    #[cfg(feature = "psk")]
+    fn max_psks_per_commit(&self) -> u16 {
+        self.get().max_psks_per_commit()
+    }
+
+    #[cfg(feature = "std")]
+    fn identity_cache_ttl_in_s(&self) -> Option<u64> {
+        self.get().identity_cache_ttl_in_s()
+    }
+
+    #[cfg(feature = "std")]
+    fn group_info_cache_ttl_in_s(&self) -> Option<u64> {
+        self.get().group_info_cache_ttl_in_s()
+    }
+
+    fn min_cipher_suite_strength(&self) -> Option<u16> {
+        self.get().min_cipher_suite_strength()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -873,6 +966,13 @@ pub(crate) struct Settings {
     pub(crate) key_package_extensions: ExtensionList,
     pub(crate) leaf_node_extensions: ExtensionList,
     pub(crate) lifetime_in_s: u64,
+    #[cfg(feature = "psk")]
+    pub(crate) max_psks_per_commit: u16,
+    #[cfg(feature = "std")]
+    pub(crate) identity_cache_ttl_in_s: Option<u64>,
+    #[cfg(feature = "std")]
+    pub(crate) group_info_cache_ttl_in_s: Option<u64>,
+    pub(crate) min_cipher_suite_strength: Option<u16>,
     #[cfg(any(test, feature = "test_util"))]
     pub(crate) key_package_not_before: Option<u64>,
 }
@@ -886,6 +986,13 @@ impl Default for Settings {
             leaf_node_extensions: Default::default(),
             lifetime_in_s: 365 * 24 * 3600,
             custom_proposal_types: Default::default(),
+            #[cfg(feature = "psk")]
+            max_psks_per_commit: u16::MAX,
+            #[cfg(feature = "std")]
+            identity_cache_ttl_in_s: None,
+            #[cfg(feature = "std")]
+            group_info_cache_ttl_in_s: None,
+            min_cipher_suite_strength: None,
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
         }
@@ -909,6 +1016,13 @@ pub(crate) fn recreate_config<T: ClientConfig>(
                 let l = c.lifetime();
                 l.not_after - l.not_before
             },
+            #[cfg(feature = "psk")]
+            max_psks_per_commit: c.max_psks_per_commit(),
+            #[cfg(feature = "std")]
+            identity_cache_ttl_in_s: c.identity_cache_ttl_in_s(),
+            #[cfg(feature = "std")]
+            group_info_cache_ttl_in_s: c.group_info_cache_ttl_in_s(),
+            min_cipher_suite_strength: c.min_cipher_suite_strength(),
             #[cfg(any(test, feature = "test_util"))]
             key_package_not_before: None,
         },
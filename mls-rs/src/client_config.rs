@@ -55,10 +55,29 @@ pub trait ClientConfig: Send + Sync + Clone {
         self.supported_protocol_versions().contains(&version)
     }
 
+    fn min_cipher_suite_strength(&self) -> Option<u16> {
+        None
+    }
+
     fn supported_credential_types(&self) -> Vec<CredentialType> {
         self.identity_provider().supported_types()
     }
 
+    #[cfg(feature = "psk")]
+    fn max_psks_per_commit(&self) -> u16 {
+        u16::MAX
+    }
+
+    #[cfg(feature = "std")]
+    fn identity_cache_ttl_in_s(&self) -> Option<u64> {
+        None
+    }
+
+    #[cfg(feature = "std")]
+    fn group_info_cache_ttl_in_s(&self) -> Option<u64> {
+        None
+    }
+
     fn leaf_properties(&self) -> ConfigProperties {
         ConfigProperties {
             capabilities: self.capabilities(),
@@ -11,11 +11,15 @@ use mls_rs_core::{group::ProposalType, identity::CredentialType};
 
 #[cfg(feature = "by_ref_proposal")]
 use mls_rs_core::{
+    error::IntoAnyError,
     extension::ExtensionList,
     identity::{IdentityProvider, SigningIdentity},
     time::MlsTime,
 };
 
+#[cfg(feature = "by_ref_proposal")]
+use crate::client::MlsError;
+
 use crate::group::ExportedTree;
 
 use mls_rs_core::crypto::HpkePublicKey;
@@ -217,11 +221,21 @@ impl ExternalSendersExt {
         provider: &I,
         timestamp: Option<MlsTime>,
         group_context_extensions: &ExtensionList,
-    ) -> Result<(), I::Error> {
+    ) -> Result<(), MlsError> {
         for id in self.allowed_senders.iter() {
+            // Bound the input handed to the identity provider
+            if let Some(max_size) = provider.max_credential_size() {
+                let size = id.credential.mls_encoded_len();
+
+                if size > max_size {
+                    return Err(MlsError::CredentialTooLarge(size));
+                }
+            }
+
             provider
                 .validate_external_sender(id, timestamp, Some(group_context_extensions))
-                .await?;
+                .await
+                .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
         }
 
         Ok(())
@@ -315,6 +329,35 @@ mod tests {
         assert_eq!(ext, restored)
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn oversized_external_sender_credential_is_rejected() {
+        use crate::identity::test_utils::BasicWithCustomProvider;
+        use assert_matches::assert_matches;
+
+        let small = get_test_signing_identity(TEST_CIPHER_SUITE, b"server")
+            .await
+            .0;
+        let large = get_test_signing_identity(TEST_CIPHER_SUITE, &[1; 512])
+            .await
+            .0;
+
+        let mut provider = BasicWithCustomProvider::default();
+        provider.max_credential_size = Some(256);
+
+        let res = ExternalSendersExt::new(vec![small.clone()])
+            .verify_all(&provider, None, &ExtensionList::new())
+            .await;
+
+        assert!(res.is_ok());
+
+        let res = ExternalSendersExt::new(vec![small, large])
+            .verify_all(&provider, None, &ExtensionList::new())
+            .await;
+
+        assert_matches!(res, Err(MlsError::CredentialTooLarge(size)) if size > 256);
+    }
+
     #[test]
     fn test_external_pub() {
         let ext = ExternalPubExt {
@@ -494,6 +494,12 @@ impl<C: ExternalClientConfig + Clone> ExternalGroup<C> {
             .map_err(Into::into)
     }
 
+    /// Length in bytes of the tree returned by [`ExternalGroup::export_tree`],
+    /// computed without exporting it.
+    pub fn tree_size(&self) -> usize {
+        self.group_state().public_tree.serialized_size()
+    }
+
     /// Get the current roster of the group.
     #[inline(always)]
     pub fn roster(&self) -> Roster {
@@ -1282,6 +1288,14 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_size_matches_exported_tree() {
+        let server =
+            make_external_group(&test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await).await;
+
+        assert_eq!(server.tree_size(), server.export_tree().unwrap().len());
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn external_group_can_be_serialized_to_tls_encoding() {
         let server =
@@ -0,0 +1,530 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
+use mls_rs_core::{crypto::CipherSuiteProvider, error::IntoAnyError};
+use zeroize::Zeroizing;
+
+use crate::{client::MlsError, client_config::ClientConfig, group::Group, MlsMessage};
+
+use super::ApplicationMessageDescription;
+
+/// Prefix of the authenticated data of each chunk of an application message
+/// encrypted with [`Group::encrypt_application_message_chunks`].
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+struct ApplicationChunkHeader {
+    /// Random identifier shared by all chunks of the same message.
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    message_id: Vec<u8>,
+    sequence: u32,
+    chunk_count: u32,
+    #[mls_codec(with = "mls_rs_codec::byte_vec")]
+    authenticated_data: Vec<u8>,
+}
+
+impl<C> Group<C>
+where
+    C: ClientConfig + Clone,
+{
+    /// Encrypt `message` as a sequence of application messages carrying at most
+    /// `chunk_size` bytes of it each.
+    ///
+    /// The chunks must be delivered in order and can be reassembled by an
+    /// [`ApplicationChunkReassembler`]. `authenticated_data` will be sent
+    /// unencrypted along with each chunk.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn encrypt_application_message_chunks(
+        &mut self,
+        message: &[u8],
+        chunk_size: usize,
+        authenticated_data: Vec<u8>,
+    ) -> Result<Vec<MlsMessage>, MlsError> {
+        if chunk_size == 0 {
+            return Err(MlsError::InvalidApplicationChunkSize);
+        }
+
+        let chunk_count = message.len() / chunk_size + usize::from(message.len() % chunk_size != 0);
+
+        let chunk_count =
+            u32::try_from(chunk_count.max(1)).map_err(|_| MlsError::InvalidApplicationChunkSize)?;
+
+        let message_id = self
+            .cipher_suite_provider
+            .random_bytes_vec(MESSAGE_ID_SIZE)
+            .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
+
+        let mut chunks = Vec::new();
+
+        for sequence in 0..chunk_count {
+            let start = sequence as usize * chunk_size;
+            let end = start.saturating_add(chunk_size).min(message.len());
+
+            let header = ApplicationChunkHeader {
+                message_id: message_id.clone(),
+                sequence,
+                chunk_count,
+                authenticated_data: authenticated_data.clone(),
+            };
+
+            let chunk = self
+                .encrypt_application_message(&message[start..end], header.mls_encode_to_vec()?)
+                .await?;
+
+            chunks.push(chunk);
+        }
+
+        Ok(chunks)
+    }
+}
+
+const MESSAGE_ID_SIZE: usize = 16;
+
+const DEFAULT_MAX_CHUNK_COUNT: u32 = 1024;
+const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Reassembles application messages encrypted with
+/// [`Group::encrypt_application_message_chunks`].
+///
+/// Chunks must be added in the order they were produced. A chunk that is out of
+/// order, missing, sent by another member or belonging to another message
+/// results in an error, after which the partially reassembled message is
+/// discarded.
+///
+/// Messages announcing more than [`Self::with_max_chunk_count`] chunks or
+/// growing beyond [`Self::with_max_message_size`] bytes are rejected. The
+/// defaults are 1024 chunks and 64 MiB.
+#[derive(Debug)]
+pub struct ApplicationChunkReassembler {
+    max_chunk_count: u32,
+    max_message_size: usize,
+    sender_index: Option<u32>,
+    message_id: Vec<u8>,
+    next_sequence: u32,
+    chunk_count: u32,
+    data: Zeroizing<Vec<u8>>,
+}
+
+impl Default for ApplicationChunkReassembler {
+    fn default() -> Self {
+        Self {
+            max_chunk_count: DEFAULT_MAX_CHUNK_COUNT,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            sender_index: None,
+            message_id: Vec::new(),
+            next_sequence: 0,
+            chunk_count: 0,
+            data: Default::default(),
+        }
+    }
+}
+
+impl ApplicationChunkReassembler {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the maximum number of chunks a reassembled message may consist of.
+    pub fn with_max_chunk_count(self, max_chunk_count: u32) -> Self {
+        Self {
+            max_chunk_count,
+            ..self
+        }
+    }
+
+    /// Set the maximum size in bytes of a reassembled message.
+    pub fn with_max_message_size(self, max_message_size: usize) -> Self {
+        Self {
+            max_message_size,
+            ..self
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self {
+            max_chunk_count: self.max_chunk_count,
+            max_message_size: self.max_message_size,
+            ..Self::default()
+        };
+    }
+
+    /// Add the next received chunk. Once the last chunk is added, this returns
+    /// the reassembled message and the reassembler is ready for a new one.
+    pub fn add_chunk(
+        &mut self,
+        chunk: ApplicationMessageDescription,
+    ) -> Result<Option<ApplicationMessageDescription>, MlsError> {
+        let res = self.try_add_chunk(chunk);
+
+        if res.is_err() {
+            self.reset();
+        }
+
+        res
+    }
+
+    fn try_add_chunk(
+        &mut self,
+        chunk: ApplicationMessageDescription,
+    ) -> Result<Option<ApplicationMessageDescription>, MlsError> {
+        let header = ApplicationChunkHeader::mls_decode(&mut &*chunk.authenticated_data)?;
+
+        if header.sequence != self.next_sequence {
+            return Err(MlsError::UnexpectedApplicationChunk {
+                expected: self.next_sequence,
+                found: header.sequence,
+            });
+        }
+
+        match self.sender_index {
+            None if header.chunk_count > self.max_chunk_count => {
+                return Err(MlsError::TooManyApplicationChunks(header.chunk_count))
+            }
+            None => {
+                self.sender_index = Some(chunk.sender_index);
+                self.message_id = header.message_id;
+                self.chunk_count = header.chunk_count;
+            }
+            Some(sender_index) if sender_index != chunk.sender_index => {
+                return Err(MlsError::ApplicationChunkSenderMismatch(chunk.sender_index))
+            }
+            Some(_) if header.message_id != self.message_id => {
+                return Err(MlsError::ApplicationChunkMessageMismatch)
+            }
+            Some(_) if header.chunk_count != self.chunk_count => {
+                return Err(MlsError::ApplicationChunkCountMismatch {
+                    expected: self.chunk_count,
+                    found: header.chunk_count,
+                })
+            }
+            Some(_) => {}
+        }
+
+        let size = self.data.len().saturating_add(chunk.data().len());
+
+        if size > self.max_message_size {
+            return Err(MlsError::ApplicationMessageTooLarge(size));
+        }
+
+        self.data.extend_from_slice(chunk.data());
+        self.next_sequence += 1;
+
+        if self.next_sequence < self.chunk_count {
+            return Ok(None);
+        }
+
+        let data = core::mem::take(&mut *self.data);
+        self.reset();
+
+        Ok(Some(ApplicationMessageDescription {
+            sender_index: chunk.sender_index,
+            data: data.into(),
+            authenticated_data: header.authenticated_data,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        group::{
+            test_utils::{test_group, TestGroup},
+            ReceivedMessage,
+        },
+    };
+
+    use super::*;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn receive_chunk(
+        group: &mut TestGroup,
+        chunk: MlsMessage,
+    ) -> ApplicationMessageDescription {
+        match group.process_message(chunk).await.unwrap() {
+            ReceivedMessage::ApplicationMessage(desc) => desc,
+            _ => panic!("expected an application message"),
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn large_message_round_trips_through_chunks() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let message = (0..5 * 1024 * 1024).map(|i| i as u8).collect::<Vec<_>>();
+
+        let chunks = alice
+            .group
+            .encrypt_application_message_chunks(&message, 64 * 1024, b"file".to_vec())
+            .await
+            .unwrap();
+
+        assert_eq!(chunks.len(), 80);
+
+        let mut reassembler = ApplicationChunkReassembler::new();
+        let mut received = None;
+
+        for chunk in chunks {
+            assert!(received.is_none());
+
+            let chunk = receive_chunk(&mut bob, chunk).await;
+            received = reassembler.add_chunk(chunk).unwrap();
+        }
+
+        let received = received.unwrap();
+
+        assert_eq!(received.data(), message.as_slice());
+        assert_eq!(received.authenticated_data, b"file".to_vec());
+        assert_eq!(received.sender_index, 0);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn missing_chunk_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 30], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut reassembler = ApplicationChunkReassembler::new();
+        let mut chunks = chunks.into_iter();
+
+        let first = receive_chunk(&mut bob, chunks.next().unwrap()).await;
+        assert_matches!(reassembler.add_chunk(first), Ok(None));
+
+        let third = receive_chunk(&mut bob, chunks.nth(1).unwrap()).await;
+
+        assert_matches!(
+            reassembler.add_chunk(third),
+            Err(MlsError::UnexpectedApplicationChunk {
+                expected: 1,
+                found: 2
+            })
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn chunks_of_another_message_are_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let first_message = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let second_message = alice
+            .group
+            .encrypt_application_message_chunks(&[2u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut reassembler = ApplicationChunkReassembler::new();
+
+        let chunk = receive_chunk(&mut bob, first_message[0].clone()).await;
+        assert_matches!(reassembler.add_chunk(chunk), Ok(None));
+
+        let chunk = receive_chunk(&mut bob, second_message[1].clone()).await;
+
+        assert_matches!(
+            reassembler.add_chunk(chunk),
+            Err(MlsError::ApplicationChunkMessageMismatch)
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn chunk_count_mismatch_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let mut reassembler = ApplicationChunkReassembler::new();
+
+        for (sequence, chunk_count) in [(0, 3), (1, 2)] {
+            let header = ApplicationChunkHeader {
+                message_id: vec![0; MESSAGE_ID_SIZE],
+                sequence,
+                chunk_count,
+                authenticated_data: vec![],
+            };
+
+            let chunk = alice
+                .group
+                .encrypt_application_message(&[1u8; 10], header.mls_encode_to_vec().unwrap())
+                .await
+                .unwrap();
+
+            let chunk = receive_chunk(&mut bob, chunk).await;
+            let res = reassembler.add_chunk(chunk);
+
+            if sequence == 0 {
+                assert_matches!(res, Ok(None));
+            } else {
+                assert_matches!(
+                    res,
+                    Err(MlsError::ApplicationChunkCountMismatch {
+                        expected: 3,
+                        found: 2
+                    })
+                );
+            }
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn reassembler_is_reset_after_error() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let other_chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut reassembler = ApplicationChunkReassembler::new();
+
+        let chunk = receive_chunk(&mut bob, chunks[0].clone()).await;
+        assert_matches!(reassembler.add_chunk(chunk), Ok(None));
+
+        let chunk = receive_chunk(&mut bob, other_chunks[1].clone()).await;
+        assert!(reassembler.add_chunk(chunk).is_err());
+
+        let message = alice
+            .group
+            .encrypt_application_message_chunks(&[2u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut received = None;
+
+        for chunk in message {
+            let chunk = receive_chunk(&mut bob, chunk).await;
+            received = reassembler.add_chunk(chunk).unwrap();
+        }
+
+        assert_eq!(received.unwrap().data(), &[2u8; 20]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn chunks_from_another_sender_are_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut carol, commit) = alice.join("carol").await;
+        bob.process_message(commit).await.unwrap();
+
+        let alice_chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let bob_chunks = bob
+            .group
+            .encrypt_application_message_chunks(&[2u8; 20], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut reassembler = ApplicationChunkReassembler::new();
+
+        let chunk = receive_chunk(&mut carol, alice_chunks[0].clone()).await;
+        assert_matches!(reassembler.add_chunk(chunk), Ok(None));
+
+        let chunk = receive_chunk(&mut carol, bob_chunks[1].clone()).await;
+
+        assert_matches!(
+            reassembler.add_chunk(chunk),
+            Err(MlsError::ApplicationChunkSenderMismatch(1))
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn zero_chunk_size_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 20], 0, vec![])
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidApplicationChunkSize));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn huge_chunk_size_does_not_overflow() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 20], usize::MAX, vec![])
+            .await
+            .unwrap();
+
+        assert_eq!(chunks.len(), 1);
+
+        let chunk = receive_chunk(&mut bob, chunks[0].clone()).await;
+        let received = ApplicationChunkReassembler::new().add_chunk(chunk).unwrap();
+
+        assert_eq!(received.unwrap().data(), &[1u8; 20]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn too_many_chunks_are_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 30], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut reassembler = ApplicationChunkReassembler::new().with_max_chunk_count(2);
+
+        let chunk = receive_chunk(&mut bob, chunks[0].clone()).await;
+
+        assert_matches!(
+            reassembler.add_chunk(chunk),
+            Err(MlsError::TooManyApplicationChunks(3))
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn oversized_message_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+
+        let chunks = alice
+            .group
+            .encrypt_application_message_chunks(&[1u8; 30], 10, vec![])
+            .await
+            .unwrap();
+
+        let mut reassembler = ApplicationChunkReassembler::new().with_max_message_size(25);
+        let mut results = Vec::new();
+
+        for chunk in chunks {
+            let chunk = receive_chunk(&mut bob, chunk).await;
+            results.push(reassembler.add_chunk(chunk));
+        }
+
+        assert_matches!(results[0], Ok(None));
+        assert_matches!(results[1], Ok(None));
+        assert_matches!(results[2], Err(MlsError::ApplicationMessageTooLarge(30)));
+    }
+}
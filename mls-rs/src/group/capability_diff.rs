@@ -0,0 +1,94 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use alloc::vec::Vec;
+use mls_rs_core::group::Capabilities;
+
+/// Capabilities that became common to all members, or stopped being common
+/// to all members, between two states of a group, as returned by
+/// [`Group::capability_changes_since`](crate::group::Group::capability_changes_since).
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CapabilityDiff {
+    /// Capabilities supported by all current members that were not supported
+    /// by all previous members.
+    pub gained: Capabilities,
+    /// Capabilities supported by all previous members that are not supported
+    /// by all current members.
+    pub lost: Capabilities,
+}
+
+#[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen)]
+impl CapabilityDiff {
+    pub(crate) fn new(previous: &Capabilities, current: &Capabilities) -> Self {
+        Self {
+            gained: filter_capabilities(current, previous, false),
+            lost: filter_capabilities(previous, current, false),
+        }
+    }
+
+    /// Newly common capabilities.
+    #[cfg(feature = "ffi")]
+    pub fn gained(&self) -> &Capabilities {
+        &self.gained
+    }
+
+    /// Capabilities that are no longer common.
+    #[cfg(feature = "ffi")]
+    pub fn lost(&self) -> &Capabilities {
+        &self.lost
+    }
+
+    /// Returns `true` if no capability was gained or lost.
+    pub fn is_empty(&self) -> bool {
+        self.gained == empty_capabilities() && self.lost == empty_capabilities()
+    }
+}
+
+/// Capabilities supported by every set in `capabilities`.
+pub(crate) fn common_capabilities<I>(capabilities: I) -> Capabilities
+where
+    I: IntoIterator<Item = Capabilities>,
+{
+    let mut capabilities = capabilities.into_iter();
+
+    let Some(first) = capabilities.next() else {
+        return empty_capabilities();
+    };
+
+    capabilities.fold(first, |common, other| {
+        filter_capabilities(&common, &other, true)
+    })
+}
+
+fn empty_capabilities() -> Capabilities {
+    Capabilities {
+        protocol_versions: Vec::new(),
+        cipher_suites: Vec::new(),
+        extensions: Vec::new(),
+        proposals: Vec::new(),
+        credentials: Vec::new(),
+    }
+}
+
+/// Capabilities in `a` that are (or are not, if `in_b` is `false`) in `b`.
+fn filter_capabilities(a: &Capabilities, b: &Capabilities, in_b: bool) -> Capabilities {
+    Capabilities {
+        protocol_versions: filter(&a.protocol_versions, &b.protocol_versions, in_b),
+        cipher_suites: filter(&a.cipher_suites, &b.cipher_suites, in_b),
+        extensions: filter(&a.extensions, &b.extensions, in_b),
+        proposals: filter(&a.proposals, &b.proposals, in_b),
+        credentials: filter(&a.credentials, &b.credentials, in_b),
+    }
+}
+
+fn filter<T: PartialEq + Clone>(a: &[T], b: &[T], in_b: bool) -> Vec<T> {
+    a.iter()
+        .filter(|x| b.contains(x) == in_b)
+        .cloned()
+        .collect()
+}
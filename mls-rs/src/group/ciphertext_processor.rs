@@ -100,11 +100,16 @@ where
         &mut self,
         auth_content: AuthenticatedContent,
         padding: PaddingMode,
+        padding_block_size: usize,
     ) -> Result<PrivateMessage, MlsError> {
         if Sender::Member(*self.group_state.self_index()) != auth_content.content.sender {
             return Err(MlsError::InvalidSender);
         }
 
+        if padding == PaddingMode::FixedBlock && padding_block_size == 0 {
+            return Err(MlsError::InvalidPaddingBlockSize);
+        }
+
         let content_type = ContentType::from(&auth_content.content.content);
         let authenticated_data = auth_content.content.authenticated_data;
 
@@ -114,12 +119,14 @@ where
             auth: auth_content.auth,
         };
 
-        // Build ciphertext aad using the plaintext message
+        // Build ciphertext aad using the plaintext message. The header of the resulting
+        // private message and the sender data aad are both derived from it, so that every
+        // field of the header is bound by the AEAD.
         let aad = PrivateContentAAD {
             group_id: auth_content.content.group_id,
             epoch: auth_content.content.epoch,
             content_type,
-            authenticated_data: authenticated_data.clone(),
+            authenticated_data,
         };
 
         // Generate a 4 byte reuse guard
@@ -135,7 +142,8 @@ where
         let mut serialized_private_content = private_content.mls_encode_to_vec()?;
 
         // Apply padding to private content based on the current padding mode.
-        serialized_private_content.resize(padding.padded_size(serialized_private_content.len()), 0);
+        let padded_size = padding.padded_size(serialized_private_content.len(), padding_block_size);
+        serialized_private_content.resize(padded_size, 0);
 
         let serialized_private_content = Zeroizing::new(serialized_private_content);
 
@@ -164,11 +172,7 @@ where
             reuse_guard,
         };
 
-        let sender_data_aad = SenderDataAAD {
-            group_id: self.group_state.group_context().group_id.clone(),
-            epoch: self.group_state.group_context().epoch,
-            content_type,
-        };
+        let sender_data_aad = SenderDataAAD::from(&aad);
 
         // Encrypt the sender data with the derived sender_key and sender_nonce from the current
         // epoch's key schedule
@@ -182,10 +186,10 @@ where
         let encrypted_sender_data = sender_data_key.seal(&sender_data, &sender_data_aad).await?;
 
         Ok(PrivateMessage {
-            group_id: self.group_state.group_context().group_id.clone(),
-            epoch: self.group_state.group_context().epoch,
-            content_type,
-            authenticated_data,
+            group_id: aad.group_id,
+            epoch: aad.epoch,
+            content_type: aad.content_type,
+            authenticated_data: aad.authenticated_data,
             encrypted_sender_data,
             ciphertext,
         })
@@ -196,13 +200,13 @@ where
         &mut self,
         ciphertext: &PrivateMessage,
     ) -> Result<AuthenticatedContent, MlsError> {
+        // Both aad structures are derived from the header of the message, so tampering with
+        // any header field causes decryption to fail
+        let aad = PrivateContentAAD::from(ciphertext);
+
         // Decrypt the sender data with the derived sender_key and sender_nonce from the message
         // epoch's key schedule
-        let sender_data_aad = SenderDataAAD {
-            group_id: self.group_state.group_context().group_id.clone(),
-            epoch: self.group_state.group_context().epoch,
-            content_type: ciphertext.content_type,
-        };
+        let sender_data_aad = SenderDataAAD::from(&aad);
 
         let sender_data_key = SenderDataKey::new(
             &self.group_state.epoch_secrets().sender_data_secret,
@@ -236,7 +240,7 @@ where
             .decrypt(
                 &self.cipher_suite_provider,
                 &ciphertext.ciphertext,
-                &PrivateContentAAD::from(ciphertext).mls_encode_to_vec()?,
+                &aad.mls_encode_to_vec()?,
                 &sender_data.reuse_guard,
             )
             .await
@@ -280,9 +284,12 @@ mod test {
         tree_kem::node::LeafIndex,
     };
 
-    use super::{CiphertextProcessor, GroupStateProvider, MlsError};
+    use super::{
+        reuse_guard::REUSE_GUARD_SIZE, CiphertextProcessor, GroupStateProvider, KeyType, MlsError,
+        ReuseGuard,
+    };
 
-    use alloc::vec;
+    use alloc::{collections::BTreeSet, vec};
     use assert_matches::assert_matches;
 
     struct TestData {
@@ -327,7 +334,7 @@ mod test {
             let mut ciphertext_processor = test_processor(&mut test_data.group, cipher_suite);
 
             let ciphertext = ciphertext_processor
-                .seal(test_data.content.clone(), PaddingMode::StepFunction)
+                .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
                 .await
                 .unwrap();
 
@@ -341,24 +348,89 @@ mod test {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn repeated_reuse_guard_does_not_repeat_nonce() {
+        let mut test_data = test_data(TEST_CIPHER_SUITE).await;
+        let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
+
+        // Every message is sealed with the next key of the ratchet, so even a reuse guard
+        // that repeats for every message never yields the same key and nonce twice.
+        let guard = ReuseGuard::from([7; REUSE_GUARD_SIZE]);
+        let mut used = BTreeSet::new();
+
+        for expected_generation in 0..100 {
+            let key_data = ciphertext_processor
+                .next_encryption_key(KeyType::Application)
+                .await
+                .unwrap();
+
+            assert_eq!(key_data.generation, expected_generation);
+
+            let nonce = guard.apply(&key_data.nonce);
+            assert!(used.insert((key_data.key.to_vec(), nonce)));
+        }
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_padding_use() {
         let mut test_data = test_data(TEST_CIPHER_SUITE).await;
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let ciphertext_step = ciphertext_processor
-            .seal(test_data.content.clone(), PaddingMode::StepFunction)
+            .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
             .await
             .unwrap();
 
         let ciphertext_no_pad = ciphertext_processor
-            .seal(test_data.content.clone(), PaddingMode::None)
+            .seal(test_data.content.clone(), PaddingMode::None, 0)
             .await
             .unwrap();
 
         assert!(ciphertext_step.ciphertext.len() > ciphertext_no_pad.ciphertext.len());
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn fixed_block_padding_hides_message_length() {
+        let mut test_data = test_data(TEST_CIPHER_SUITE).await;
+        let mut receiver_group = test_data.group.clone();
+        receiver_group.group.private_tree.self_index = LeafIndex::new(1);
+
+        let mut longer_content = test_data.content.clone();
+        longer_content.content.content =
+            Content::Application(ApplicationData::from(b"a longer test message".to_vec()));
+
+        let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
+
+        let short = ciphertext_processor
+            .seal(test_data.content.clone(), PaddingMode::FixedBlock, 256)
+            .await
+            .unwrap();
+
+        let long = ciphertext_processor
+            .seal(longer_content, PaddingMode::FixedBlock, 256)
+            .await
+            .unwrap();
+
+        assert_eq!(short.ciphertext.len(), long.ciphertext.len());
+
+        let mut receiver_processor = test_processor(&mut receiver_group, TEST_CIPHER_SUITE);
+        let decrypted = receiver_processor.open(&short).await.unwrap();
+
+        assert_eq!(decrypted.content.content, test_data.content.content.content);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn fixed_block_padding_requires_block_size() {
+        let mut test_data = test_data(TEST_CIPHER_SUITE).await;
+        let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
+
+        let res = ciphertext_processor
+            .seal(test_data.content, PaddingMode::FixedBlock, 0)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidPaddingBlockSize));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_invalid_sender() {
         let mut test_data = test_data(TEST_CIPHER_SUITE).await;
@@ -367,7 +439,7 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let res = ciphertext_processor
-            .seal(test_data.content, PaddingMode::None)
+            .seal(test_data.content, PaddingMode::None, 0)
             .await;
 
         assert_matches!(res, Err(MlsError::InvalidSender))
@@ -380,7 +452,7 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let ciphertext = ciphertext_processor
-            .seal(test_data.content, PaddingMode::None)
+            .seal(test_data.content, PaddingMode::None, 0)
             .await
             .unwrap();
 
@@ -396,7 +468,7 @@ mod test {
         let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
 
         let mut ciphertext = ciphertext_processor
-            .seal(test_data.content.clone(), PaddingMode::StepFunction)
+            .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
             .await
             .unwrap();
 
@@ -407,4 +479,24 @@ mod test {
 
         assert!(res.is_err());
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tampered_epoch_is_rejected() {
+        let mut test_data = test_data(TEST_CIPHER_SUITE).await;
+        let mut receiver_group = test_data.group.clone();
+        let mut ciphertext_processor = test_processor(&mut test_data.group, TEST_CIPHER_SUITE);
+
+        let mut ciphertext = ciphertext_processor
+            .seal(test_data.content.clone(), PaddingMode::StepFunction, 0)
+            .await
+            .unwrap();
+
+        ciphertext.epoch += 1;
+        receiver_group.group.private_tree.self_index = LeafIndex::new(1);
+
+        let mut receiver_processor = test_processor(&mut receiver_group, TEST_CIPHER_SUITE);
+        let res = receiver_processor.open(&ciphertext).await;
+
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
+    }
 }
@@ -7,7 +7,7 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
 use crate::CipherSuiteProvider;
 
-const REUSE_GUARD_SIZE: usize = 4;
+pub(super) const REUSE_GUARD_SIZE: usize = 4;
 
 #[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
 pub(crate) struct ReuseGuard([u8; REUSE_GUARD_SIZE]);
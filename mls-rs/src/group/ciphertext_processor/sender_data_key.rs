@@ -11,11 +11,15 @@ use zeroize::Zeroizing;
 use crate::{
     client::MlsError,
     crypto::CipherSuiteProvider,
-    group::{epoch::SenderDataSecret, framing::ContentType, key_schedule::kdf_expand_with_label},
+    group::{
+        epoch::SenderDataSecret,
+        framing::{ContentType, PrivateContentAAD},
+        key_schedule::kdf_expand_with_label,
+    },
     tree_kem::node::LeafIndex,
 };
 
-use super::ReuseGuard;
+use super::{reuse_guard::REUSE_GUARD_SIZE, ReuseGuard};
 
 #[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
 pub(crate) struct SenderData {
@@ -45,6 +49,16 @@ impl Debug for SenderDataAAD {
     }
 }
 
+impl From<&PrivateContentAAD> for SenderDataAAD {
+    fn from(content_aad: &PrivateContentAAD) -> Self {
+        Self {
+            group_id: content_aad.group_id.clone(),
+            epoch: content_aad.epoch,
+            content_type: content_aad.content_type,
+        }
+    }
+}
+
 pub(crate) struct SenderDataKey<'a, CP: CipherSuiteProvider> {
     pub(crate) key: Zeroizing<Vec<u8>>,
     pub(crate) nonce: Zeroizing<Vec<u8>>,
@@ -132,7 +146,23 @@ impl<'a, CP: CipherSuiteProvider> SenderDataKey<'a, CP> {
             )
             .await
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))
-            .and_then(|data| SenderData::mls_decode(&mut &**data).map_err(From::from))
+            .and_then(|data| {
+                let mut reader = &**data;
+                let sender = LeafIndex::mls_decode(&mut reader)?;
+                let generation = u32::mls_decode(&mut reader)?;
+
+                // The reuse guard is XORed into the nonce, so it must fill exactly
+                // the rest of the sender data
+                let reuse_guard = <[u8; REUSE_GUARD_SIZE]>::try_from(reader)
+                    .map_err(|_| MlsError::InvalidReuseGuard)?
+                    .into();
+
+                Ok(SenderData {
+                    sender,
+                    generation,
+                    reuse_guard,
+                })
+            })
     }
 }
 
@@ -201,12 +231,19 @@ mod tests {
 
     use super::{SenderData, SenderDataAAD, SenderDataKey};
 
-    #[cfg(not(mls_build_async))]
+    use alloc::vec;
+    use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
+
     use crate::{
-        cipher_suite::CipherSuite, crypto::test_utils::test_cipher_suite_provider,
-        group::test_utils::random_bytes, CipherSuiteProvider,
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        crypto::test_utils::test_cipher_suite_provider,
+        CipherSuiteProvider,
     };
 
+    #[cfg(not(mls_build_async))]
+    use crate::{cipher_suite::CipherSuite, group::test_utils::random_bytes};
+
     #[derive(serde::Deserialize, serde::Serialize)]
     struct TestCase {
         cipher_suite: u16,
@@ -357,4 +394,37 @@ mod tests {
             assert_eq!(plaintext, sender_data);
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sender_data_with_short_reuse_guard_is_rejected() {
+        let provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let secret = vec![0u8; provider.kdf_extract_size()].into();
+
+        let key = SenderDataKey::new(&secret, b"ciphertext", &provider)
+            .await
+            .unwrap();
+
+        let aad = SenderDataAAD {
+            group_id: b"group".to_vec(),
+            epoch: 0,
+            content_type: ContentType::Application,
+        };
+
+        // Sender and generation followed by a 3 byte reuse guard
+        let sender_data = [0u8; 11];
+
+        let ciphertext = provider
+            .aead_seal(
+                &key.key,
+                &sender_data,
+                Some(&aad.mls_encode_to_vec().unwrap()),
+                &key.nonce,
+            )
+            .await
+            .unwrap();
+
+        let res = key.open(&ciphertext, &aad).await;
+
+        assert_matches!(res, Err(MlsError::InvalidReuseGuard));
+    }
 }
@@ -16,7 +16,7 @@ use crate::{
     client::MlsError,
     client_config::ClientConfig,
     extension::RatchetTreeExt,
-    identity::SigningIdentity,
+    identity::{Credential, SigningIdentity},
     protocol_version::ProtocolVersion,
     signer::Signable,
     tree_kem::{
@@ -54,9 +54,22 @@ use super::{
 #[cfg(not(feature = "by_ref_proposal"))]
 use super::proposal_cache::prepare_commit;
 
+#[cfg(feature = "by_ref_proposal")]
+use crate::mls_rules::ProposalInfo;
+
+#[cfg(feature = "by_ref_proposal")]
+use super::proposal_filter::{FilterStrategy, ProposalApplier, ProposalBundle};
+
+/// Predicate deciding whether a received proposal may be included in a commit.
+#[cfg(feature = "by_ref_proposal")]
+type ProposalPolicy<'a> = dyn Fn(&ProposalInfo<Proposal>) -> bool + Send + Sync + 'a;
+
 #[cfg(feature = "custom_proposal")]
 use super::proposal::CustomProposal;
 
+#[cfg(feature = "app_ack")]
+use super::proposal::AppAckProposal;
+
 #[derive(Clone, Debug, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(mls_rs_core::arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -70,6 +83,8 @@ pub(crate) struct Commit {
 pub(super) struct CommitGeneration {
     pub content: AuthenticatedContent,
     pub pending_private_tree: TreeKemPrivate,
+    /// Needed to derive the next epoch when the commit is applied. The joiner secret is not
+    /// stored and is derived again from this value.
     pub pending_commit_secret: PathSecret,
     pub commit_message_hash: CommitHash,
 }
@@ -135,6 +150,31 @@ pub struct CommitOutput {
     pub unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
 }
 
+/// Result of [`Group::validate_proposals`](crate::group::Group::validate_proposals).
+#[cfg(feature = "by_ref_proposal")]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ProposalValidationReport {
+    /// Each proposal that the next commit would consider, along with the error that
+    /// makes it invalid, if any.
+    pub results: Vec<(ProposalInfo<Proposal>, Result<(), MlsError>)>,
+}
+
+#[cfg(feature = "by_ref_proposal")]
+impl ProposalValidationReport {
+    /// Returns `true` if every proposal passed validation.
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(|(_, res)| res.is_ok())
+    }
+
+    /// Proposals that failed validation, along with the reason.
+    pub fn invalid_proposals(&self) -> impl Iterator<Item = (&ProposalInfo<Proposal>, &MlsError)> {
+        self.results
+            .iter()
+            .filter_map(|(info, res)| res.as_ref().err().map(|e| (info, e)))
+    }
+}
+
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
 impl CommitOutput {
     /// Commit message to send to other group members.
@@ -228,6 +268,15 @@ where
         Ok(self)
     }
 
+    /// Insert a [`RemoveProposal`](crate::group::proposal::RemoveProposal) for
+    /// the member whose leaf holds `credential` into the current commit that is
+    /// being built.
+    pub fn remove_member_by_identity(mut self, credential: &Credential) -> Result<Self, MlsError> {
+        let proposal = self.group.remove_proposal_by_identity(credential)?;
+        self.proposals.push(proposal);
+        Ok(self)
+    }
+
     /// Insert a
     /// [`GroupContextExtensions`](crate::group::proposal::Proposal::GroupContextExtensions)
     /// into the current commit that is being built.
@@ -282,6 +331,14 @@ where
         Ok(self)
     }
 
+    /// Insert an [`AppAckProposal`](crate::group::proposal::AppAckProposal)
+    /// into the current commit that is being built.
+    #[cfg(feature = "app_ack")]
+    pub fn app_ack(mut self, proposal: AppAckProposal) -> Self {
+        self.proposals.push(Proposal::AppAck(proposal));
+        self
+    }
+
     /// Insert a [`CustomProposal`](crate::group::proposal::CustomProposal) into
     /// the current commit that is being built.
     #[cfg(feature = "custom_proposal")]
@@ -355,6 +412,9 @@ where
                 self.group_info_extensions,
                 self.new_signer,
                 self.new_signing_identity,
+                None,
+                #[cfg(feature = "by_ref_proposal")]
+                None,
             )
             .await
     }
@@ -413,6 +473,75 @@ where
             Default::default(),
             None,
             None,
+            None,
+            #[cfg(feature = "by_ref_proposal")]
+            None,
+        )
+        .await
+    }
+
+    /// Perform a commit of the received proposals accepted by `policy`.
+    ///
+    /// This function behaves like [`Group::commit`], except that each proposal
+    /// received since the last commit is first passed to `policy`. Proposals for
+    /// which `policy` returns `false` are left out of the commit and reported in
+    /// [`CommitOutput::unused_proposals`]. The remaining proposals are still
+    /// subject to the current
+    /// [proposal rules](crate::client_builder::ClientBuilder::mls_rules).
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn commit_with_policy<F>(
+        &mut self,
+        authenticated_data: Vec<u8>,
+        policy: F,
+    ) -> Result<CommitOutput, MlsError>
+    where
+        F: Fn(&ProposalInfo<Proposal>) -> bool + Send + Sync,
+    {
+        self.commit_internal(
+            vec![],
+            None,
+            authenticated_data,
+            Default::default(),
+            None,
+            None,
+            None,
+            Some(&policy),
+        )
+        .await
+    }
+
+    /// Rotate the external secret of the group without changing its membership.
+    ///
+    /// This creates an empty commit containing a path update. Once the commit
+    /// is applied, the `external_pub` found in any previously published
+    /// [`GroupInfo`] can no longer be used to produce a valid external commit.
+    ///
+    /// If `allow_external_commit` is true,
+    /// [`CommitOutput::external_commit_group_info`] will contain a group info
+    /// for the new epoch that can be published to allow external joins.
+    /// Otherwise it will be `None` regardless of the
+    /// [`MlsRules::commit_options`](crate::MlsRules::commit_options) in use,
+    /// effectively disabling external joins until a new group info is shared.
+    ///
+    /// The resulting commit must be applied using
+    /// [`Group::apply_pending_commit`] like any other commit.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn rotate_external_access(
+        &mut self,
+        allow_external_commit: bool,
+        authenticated_data: Vec<u8>,
+    ) -> Result<CommitOutput, MlsError> {
+        self.commit_internal(
+            vec![],
+            None,
+            authenticated_data,
+            Default::default(),
+            None,
+            None,
+            Some(allow_external_commit),
+            #[cfg(feature = "by_ref_proposal")]
+            None,
         )
         .await
     }
@@ -430,10 +559,80 @@ where
         }
     }
 
+    /// Validate the proposals received in the current epoch together with `proposals`,
+    /// without changing the group state.
+    ///
+    /// Each proposal is checked on its own, as if it were the only proposal committed
+    /// by this member, so the report pinpoints which proposal would fail and why.
+    /// Conflicts between proposals are still resolved when the commit is created.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn validate_proposals(
+        &self,
+        proposals: Vec<Proposal>,
+    ) -> Result<ProposalValidationReport, MlsError> {
+        let sender = Sender::Member(*self.private_tree.self_index);
+
+        #[cfg(feature = "std")]
+        let time = Some(crate::time::MlsTime::now());
+
+        #[cfg(not(feature = "std"))]
+        let time = None;
+
+        let identity_provider = self.identity_provider();
+        let psk_storage = self.config.secret_store();
+
+        let applier = ProposalApplier::new(
+            &self.state.public_tree,
+            self.protocol_version(),
+            &self.cipher_suite_provider,
+            &self.state.context.extensions,
+            None,
+            &identity_provider,
+            &psk_storage,
+            &self.state.context.group_id,
+        );
+
+        let mut results = Vec::new();
+
+        for info in self
+            .state
+            .proposals
+            .prepare_commit(sender, proposals)
+            .into_proposals()
+        {
+            let bundle: ProposalBundle =
+                [(info.proposal.clone(), info.sender, info.source.clone())]
+                    .into_iter()
+                    .collect();
+
+            let res = applier
+                .apply_proposals(FilterStrategy::IgnoreNone, &sender, bundle, time)
+                .await
+                .map(|_| ());
+
+            results.push((info, res));
+        }
+
+        Ok(ProposalValidationReport { results })
+    }
+
     /// Returns commit and optional [`MlsMessage`] containing a welcome message
     /// for newly added members.
     #[allow(clippy::too_many_arguments)]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                group_id = ?mls_rs_core::debug::pretty_group_id(&self.context().group_id),
+                epoch = self.context().epoch,
+                member_count = self.current_epoch_tree().occupied_leaf_count(),
+            )
+        )
+    )]
     pub(super) async fn commit_internal(
         &mut self,
         proposals: Vec<Proposal>,
@@ -442,14 +641,14 @@ where
         mut welcome_group_info_extensions: ExtensionList,
         new_signer: Option<SignatureSecretKey>,
         new_signing_identity: Option<SigningIdentity>,
+        allow_external_commit: Option<bool>,
+        #[cfg(feature = "by_ref_proposal")] proposal_policy: Option<&ProposalPolicy<'_>>,
     ) -> Result<CommitOutput, MlsError> {
         if self.pending_commit.is_some() {
             return Err(MlsError::ExistingPendingCommit);
         }
 
-        if self.state.pending_reinit.is_some() {
-            return Err(MlsError::GroupUsedAfterReInit);
-        }
+        self.ensure_active()?;
 
         let mls_rules = self.config.mls_rules();
 
@@ -474,7 +673,23 @@ where
         let time = None;
 
         #[cfg(feature = "by_ref_proposal")]
-        let proposals = self.state.proposals.prepare_commit(sender, proposals);
+        let mut proposals = self.state.proposals.prepare_commit(sender, proposals);
+
+        #[cfg(feature = "by_ref_proposal")]
+        let rejected_proposals = match proposal_policy {
+            Some(policy) => {
+                let (accepted, rejected): (Vec<_>, Vec<_>) =
+                    proposals.into_proposals().partition(|p| policy(p));
+
+                proposals = accepted
+                    .into_iter()
+                    .map(|p| (p.proposal, p.sender, p.source))
+                    .collect();
+
+                rejected
+            }
+            None => Vec::new(),
+        };
 
         #[cfg(not(feature = "by_ref_proposal"))]
         let proposals = prepare_commit(sender, proposals);
@@ -485,7 +700,7 @@ where
                 sender,
                 proposals,
                 external_leaf,
-                &self.config.identity_provider(),
+                &self.identity_provider(),
                 &self.cipher_suite_provider,
                 &self.config.secret_store(),
                 &mls_rules,
@@ -510,7 +725,7 @@ where
         // Decide whether to populate the path field: If the path field is required based on the
         // proposals that are in the commit (see above), then it MUST be populated. Otherwise, the
         // sender MAY omit the path field at its discretion.
-        let commit_options = mls_rules
+        let mut commit_options = mls_rules
             .commit_options(
                 &provisional_state.public_tree.roster(),
                 &provisional_group_context.extensions,
@@ -518,6 +733,10 @@ where
             )
             .map_err(|e| MlsError::MlsRulesError(e.into_any_error()))?;
 
+        if let Some(allow_external_commit) = allow_external_commit {
+            commit_options.allow_external_commit = allow_external_commit;
+        }
+
         let perform_path_update = commit_options.path_required
             || path_update_required(&provisional_state.applied_proposals);
 
@@ -703,7 +922,7 @@ where
         let path_secrets = path_secrets.as_ref();
 
         #[cfg(not(any(mls_build_async, not(feature = "rayon"))))]
-        let encrypted_path_secrets: Vec<_> = added_key_pkgs
+        let mut encrypted_path_secrets: Vec<_> = added_key_pkgs
             .into_par_iter()
             .zip(provisional_state.indexes_of_added_kpkgs)
             .map(|(key_package, leaf_index)| {
@@ -720,7 +939,7 @@ where
             .try_collect()?;
 
         #[cfg(any(mls_build_async, not(feature = "rayon")))]
-        let encrypted_path_secrets = {
+        let mut encrypted_path_secrets = {
             let mut secrets = Vec::new();
 
             for (key_package, leaf_index) in added_key_pkgs
@@ -744,6 +963,10 @@ where
             secrets
         };
 
+        // Order the secrets canonically so that the same set of added members always
+        // produces the same Welcome
+        encrypted_path_secrets.sort_by(|a, b| a.new_member.cmp(&b.new_member));
+
         let welcome_messages =
             if commit_options.single_welcome_message && !encrypted_path_secrets.is_empty() {
                 vec![self.make_welcome_message(encrypted_path_secrets, encrypted_group_info)]
@@ -754,6 +977,11 @@ where
                     .collect()
             };
 
+        // The joiner secret and the rest of the derived key schedule are only needed to build the
+        // welcome messages. They are zeroized here rather than kept alive until the end of this
+        // function, as applying the pending commit derives them again from `commit_secret`.
+        drop(key_schedule_result);
+
         let commit_message = self.format_for_wire(auth_content.clone()).await?;
 
         let pending_commit = CommitGeneration {
@@ -779,7 +1007,7 @@ where
             ratchet_tree,
             external_commit_group_info,
             #[cfg(feature = "by_ref_proposal")]
-            unused_proposals: provisional_state.unused_proposals,
+            unused_proposals: [provisional_state.unused_proposals, rejected_proposals].concat(),
         })
     }
 
@@ -841,6 +1069,7 @@ pub(crate) mod test_utils {
         pub modify_leaf: fn(&mut LeafNode, &SignatureSecretKey) -> Option<SignatureSecretKey>,
         pub modify_tree: fn(&mut TreeKemPublic),
         pub modify_path: fn(Vec<UpdatePathNode>) -> Vec<UpdatePathNode>,
+        pub modify_update_leaf: fn(&mut LeafNode, &LeafNode),
     }
 
     impl Default for CommitModifiers {
@@ -849,6 +1078,7 @@ pub(crate) mod test_utils {
                 modify_leaf: |_, _| None,
                 modify_tree: |_| (),
                 modify_path: |a| a,
+                modify_update_leaf: |_, _| (),
             }
         }
     }
@@ -894,6 +1124,8 @@ mod tests {
 
     use crate::extension::RequiredCapabilitiesExt;
 
+    use assert_matches::assert_matches;
+
     #[cfg(feature = "psk")]
     use crate::{
         group::proposal::PreSharedKeyProposal,
@@ -965,6 +1197,47 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_secrets_are_in_canonical_order() {
+        let mut group = test_commit_builder_group().await;
+
+        let bob = test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let carol =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let mut recipients = Vec::new();
+
+        for key_packages in [[bob.clone(), carol.clone()], [carol, bob]] {
+            let commit_output = key_packages
+                .into_iter()
+                .fold(group.commit_builder(), |builder, key_package| {
+                    builder.add_member(key_package).unwrap()
+                })
+                .build()
+                .await
+                .unwrap();
+
+            group.clear_pending_commit();
+
+            let welcome = commit_output.welcome_messages[0]
+                .clone()
+                .into_welcome()
+                .unwrap();
+
+            recipients.push(
+                welcome
+                    .secrets
+                    .into_iter()
+                    .map(|secrets| secrets.new_member)
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        assert_eq!(recipients[0], recipients[1]);
+        assert!(recipients[0][0] < recipients[0][1]);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_commit_builder_add() {
         let mut group = test_commit_builder_group().await;
@@ -1408,6 +1681,110 @@ mod tests {
         assert!(commit.external_commit_group_info.is_none());
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn rotating_external_access_invalidates_previous_group_info() {
+        use crate::{client::test_utils::TestClientBuilder, extension::ExternalPubExt};
+
+        let mut alice_group = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            Default::default(),
+            None,
+            Some(CommitOptions::new().with_allow_external_commit(true)),
+        )
+        .await;
+
+        let stale_info = alice_group
+            .group
+            .group_info_message_allowing_ext_commit(false)
+            .await
+            .unwrap();
+
+        let commit = alice_group
+            .group
+            .rotate_external_access(false, vec![])
+            .await
+            .unwrap();
+
+        assert!(commit.external_commit_group_info.is_none());
+
+        alice_group.group.apply_pending_commit().await.unwrap();
+
+        let commit = alice_group
+            .group
+            .rotate_external_access(true, vec![])
+            .await
+            .unwrap();
+
+        let fresh_info = commit
+            .external_commit_group_info
+            .unwrap()
+            .into_group_info()
+            .unwrap();
+
+        alice_group.group.apply_pending_commit().await.unwrap();
+
+        let stale_external_pub = stale_info
+            .clone()
+            .into_group_info()
+            .unwrap()
+            .extensions
+            .get_as::<ExternalPubExt>()
+            .unwrap()
+            .unwrap();
+
+        let fresh_external_pub = fresh_info
+            .extensions
+            .get_as::<ExternalPubExt>()
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(stale_external_pub, fresh_external_pub);
+
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        // Present each external_pub in an otherwise identical group info for the current
+        // epoch, so that the external commits only differ by the key they are encrypted to.
+        for (external_pub, is_fresh) in [(fresh_external_pub, true), (stale_external_pub, false)] {
+            let mut info = fresh_info.clone();
+            info.extensions.set_from(external_pub).unwrap();
+
+            info.sign(
+                &alice_group.group.cipher_suite_provider,
+                &alice_group.group.signer,
+                &(),
+            )
+            .await
+            .unwrap();
+
+            let info = MlsMessage::new(TEST_PROTOCOL_VERSION, MlsMessagePayload::GroupInfo(info));
+
+            let (_, external_commit) = bob
+                .external_commit_builder()
+                .unwrap()
+                .with_tree_data(alice_group.group.export_tree().into_owned())
+                .build(info)
+                .await
+                .unwrap();
+
+            let res = alice_group
+                .clone()
+                .process_message(external_commit)
+                .await
+                .map(|_| ());
+
+            if is_fresh {
+                res.unwrap();
+            } else {
+                assert_matches!(res, Err(MlsError::InvalidConfirmationTag));
+            }
+        }
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn member_identity_is_validated_against_new_extensions() {
         let alice = client_with_test_extension(b"alice").await;
@@ -1598,4 +1975,114 @@ mod tests {
             .signing_identity(identity, secret_key, TEST_CIPHER_SUITE)
             .build()
     }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_with_policy_excludes_rejected_proposals() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let remove = groups[1].group.propose_remove(2, vec![]).await.unwrap();
+        groups[0].process_message(remove).await.unwrap();
+
+        let admins = [Sender::Member(0)];
+
+        let commit_output = groups[0]
+            .group
+            .commit_with_policy(vec![], |p| {
+                !matches!(p.proposal, Proposal::Remove(_)) || admins.contains(&p.sender)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(commit_output.unused_proposals.len(), 1);
+
+        assert_matches!(
+            commit_output.unused_proposals[0].proposal,
+            Proposal::Remove(_)
+        );
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        groups[1]
+            .process_message(commit_output.commit_message)
+            .await
+            .unwrap();
+
+        assert_eq!(groups[0].group.roster().members().len(), 3);
+        assert_eq!(groups[1].group.roster().members().len(), 3);
+    }
+
+    /// Allocator recording whether a watched buffer was zeroed by the time it was freed.
+    #[cfg(feature = "std")]
+    mod zeroize_observer {
+        use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+        use std::alloc::{GlobalAlloc, Layout, System};
+
+        struct ZeroizeObserver;
+
+        #[global_allocator]
+        static ALLOCATOR: ZeroizeObserver = ZeroizeObserver;
+
+        static WATCHED: AtomicUsize = AtomicUsize::new(0);
+        static STATE: AtomicU8 = AtomicU8::new(NOT_FREED);
+
+        const NOT_FREED: u8 = 0;
+        const FREED_ZEROED: u8 = 1;
+        const FREED_NOT_ZEROED: u8 = 2;
+
+        unsafe impl GlobalAlloc for ZeroizeObserver {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                System.alloc(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                let watched = ptr as usize;
+
+                if WATCHED
+                    .compare_exchange(watched, 0, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+                {
+                    let bytes = core::slice::from_raw_parts(ptr, layout.size());
+
+                    let state = if bytes.iter().all(|b| *b == 0) {
+                        FREED_ZEROED
+                    } else {
+                        FREED_NOT_ZEROED
+                    };
+
+                    STATE.store(state, Ordering::SeqCst);
+                }
+
+                System.dealloc(ptr, layout)
+            }
+        }
+
+        pub fn watch(buffer: &[u8]) {
+            STATE.store(NOT_FREED, Ordering::SeqCst);
+            WATCHED.store(buffer.as_ptr() as usize, Ordering::SeqCst);
+        }
+
+        /// `None` if the watched buffer was not freed yet, otherwise whether it was zeroed.
+        pub fn freed_zeroed() -> Option<bool> {
+            match STATE.load(Ordering::SeqCst) {
+                NOT_FREED => None,
+                state => Some(state == FREED_ZEROED),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_secret_is_zeroized_once_commit_is_applied() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        groups[0].group.commit(vec![]).await.unwrap();
+
+        let pending_commit = groups[0].group.pending_commit.as_ref().unwrap();
+        zeroize_observer::watch(&pending_commit.pending_commit_secret);
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        assert_eq!(zeroize_observer::freed_zeroed(), Some(true));
+    }
 }
@@ -7,7 +7,10 @@ use alloc::vec::Vec;
 use core::fmt::{self, Debug};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
-use crate::{cipher_suite::CipherSuite, protocol_version::ProtocolVersion, ExtensionList};
+use crate::{
+    cipher_suite::CipherSuite, client::MlsError, protocol_version::ProtocolVersion,
+    tree_kem::TreeKemPublic, CipherSuiteProvider, ExtensionList,
+};
 
 use super::ConfirmedTranscriptHash;
 
@@ -52,6 +55,24 @@ impl Debug for GroupContext {
     }
 }
 
+impl GroupContext {
+    /// Check that this context, received in a Welcome message, is consistent with the
+    /// ratchet tree of the group being joined. A Welcome is always created by a commit,
+    /// so the epoch of the joined group can not be 0.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn validate_against<P: CipherSuiteProvider>(
+        &self,
+        tree: &mut TreeKemPublic,
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        if self.epoch == 0 || tree.tree_hash(cipher_suite_provider).await? != self.tree_hash {
+            return Err(MlsError::InconsistentGroupContext);
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
 impl GroupContext {
     pub(crate) fn new_group(
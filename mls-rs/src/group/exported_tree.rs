@@ -5,7 +5,11 @@
 use alloc::{borrow::Cow, vec::Vec};
 use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 
-use crate::{client::MlsError, tree_kem::node::NodeVec};
+use crate::{
+    client::MlsError,
+    tree_kem::{node::NodeVec, tree_hash_of},
+    CipherSuiteProvider,
+};
 
 #[cfg_attr(
     all(feature = "ffi", not(test)),
@@ -37,6 +41,21 @@ impl<'a> ExportedTree<'a> {
     }
 }
 
+impl ExportedTree<'_> {
+    /// Compute the tree hash of this tree, for instance to compare it with the
+    /// tree hash in a [`GroupInfo`](crate::group::GroupInfo).
+    ///
+    /// Unlike joining a group with this tree, this does not validate the
+    /// nodes or the identities of the members.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn tree_hash<P: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &P,
+    ) -> Result<Vec<u8>, MlsError> {
+        tree_hash_of(&self.0, cipher_suite_provider).await
+    }
+}
+
 #[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
 impl ExportedTree<'static> {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
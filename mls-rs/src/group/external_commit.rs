@@ -7,7 +7,7 @@ use mls_rs_core::{crypto::SignatureSecretKey, identity::SigningIdentity};
 use crate::{
     client_config::ClientConfig,
     group::{
-        cipher_suite_provider,
+        check_cipher_suite_strength, cipher_suite_provider,
         epoch::SenderDataSecret,
         key_schedule::{InitSecret, KeySchedule},
         proposal::{ExternalInit, Proposal, RemoveProposal},
@@ -39,7 +39,13 @@ use crate::group::{
     PreSharedKeyProposal, {JustPreSharedKeyID, PreSharedKeyID},
 };
 
-use super::{validate_group_info_joiner, ExportedTree};
+use super::ExportedTree;
+
+#[cfg(feature = "std")]
+use super::group_info_cache::GroupInfoCache;
+
+#[cfg(not(feature = "std"))]
+use super::validate_group_info_joiner;
 
 /// A builder that aids with the construction of an external commit.
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::ffi_type(opaque))]
@@ -47,6 +53,8 @@ pub struct ExternalCommitBuilder<C: ClientConfig> {
     signer: SignatureSecretKey,
     signing_identity: SigningIdentity,
     config: C,
+    #[cfg(feature = "std")]
+    group_info_cache: GroupInfoCache,
     tree_data: Option<ExportedTree<'static>>,
     to_remove: Option<u32>,
     #[cfg(feature = "psk")]
@@ -63,6 +71,7 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
         signer: SignatureSecretKey,
         signing_identity: SigningIdentity,
         config: C,
+        #[cfg(feature = "std")] group_info_cache: GroupInfoCache,
     ) -> Self {
         Self {
             tree_data: None,
@@ -71,6 +80,8 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
             signer,
             signing_identity,
             config,
+            #[cfg(feature = "std")]
+            group_info_cache,
             #[cfg(feature = "psk")]
             external_psks: Vec::new(),
             #[cfg(feature = "custom_proposal")]
@@ -153,6 +164,8 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
             .into_group_info()
             .ok_or(MlsError::UnexpectedMessageType)?;
 
+        check_cipher_suite_strength(&self.config, group_info.group_context.cipher_suite)?;
+
         let cipher_suite = cipher_suite_provider(
             self.config.crypto_provider(),
             group_info.group_context.cipher_suite,
@@ -163,6 +176,19 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
             .get_as::<ExternalPubExt>()?
             .ok_or(MlsError::MissingExternalPubExtension)?;
 
+        #[cfg(feature = "std")]
+        let public_tree = self
+            .group_info_cache
+            .validate(
+                protocol_version,
+                &group_info,
+                self.tree_data,
+                &self.config.identity_provider(),
+                &cipher_suite,
+            )
+            .await?;
+
+        #[cfg(not(feature = "std"))]
         let public_tree = validate_group_info_joiner(
             protocol_version,
             &group_info,
@@ -256,6 +282,9 @@ impl<C: ClientConfig> ExternalCommitBuilder<C> {
                 Default::default(),
                 None,
                 None,
+                None,
+                #[cfg(feature = "by_ref_proposal")]
+                None,
             )
             .await?;
 
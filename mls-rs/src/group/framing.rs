@@ -6,7 +6,10 @@ use core::ops::Deref;
 
 use crate::{client::MlsError, tree_kem::node::LeafIndex, KeyPackage, KeyPackageRef};
 
-use super::{Commit, FramedContentAuthData, GroupInfo, MembershipTag, Welcome};
+use super::{
+    transcript_hash::ConfirmedTranscriptHashInput, Commit, FramedContentAuthData, GroupInfo,
+    MembershipTag, Welcome,
+};
 
 #[cfg(feature = "by_ref_proposal")]
 use crate::{group::Proposal, mls_rules::ProposalRef};
@@ -481,6 +484,27 @@ impl MlsMessage {
         }
     }
 
+    /// If this is a plaintext commit message, return the encoded `ConfirmedTranscriptHashInput`
+    /// that is hashed, after the interim transcript hash, into the confirmed transcript hash
+    /// of the next epoch. If this is not a plaintext or not a commit, this returns `None`.
+    pub fn commit_transcript_hash_input(&self) -> Result<Option<Vec<u8>>, MlsError> {
+        let MlsMessagePayload::Plain(plaintext) = &self.payload else {
+            return Ok(None);
+        };
+
+        if !matches!(plaintext.content.content, Content::Commit(_)) {
+            return Ok(None);
+        }
+
+        let input = ConfirmedTranscriptHashInput {
+            wire_format: WireFormat::PublicMessage,
+            content: &plaintext.content,
+            signature: &plaintext.auth.signature,
+        };
+
+        input.mls_encode_to_vec().map(Some).map_err(Into::into)
+    }
+
     /// If this is a welcome message, return key package references of all members who can
     /// join using this message.
     pub fn welcome_key_package_references(&self) -> Vec<&KeyPackageRef> {
@@ -520,6 +544,25 @@ impl MlsMessage {
             .await
             .map(|r| Some(r.to_vec()))
     }
+
+    /// Check whether `reference` is the proposal reference of this plaintext proposal.
+    /// The comparison is performed in constant time. Returns `false` if this is not
+    /// a plaintext message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn matches_proposal_reference<C: CipherSuiteProvider>(
+        &self,
+        cipher_suite: &C,
+        reference: &ProposalRef,
+    ) -> Result<bool, MlsError> {
+        let MlsMessagePayload::Plain(public_message) = &self.payload else {
+            return Ok(false);
+        };
+
+        reference
+            .matches_content(cipher_suite, &public_message.clone().into())
+            .await
+    }
 }
 
 #[cfg(feature = "custom_proposal")]
@@ -675,8 +718,10 @@ mod tests {
         crypto::test_utils::test_cipher_suite_provider,
         group::{
             framing::test_utils::get_test_ciphertext_content,
-            proposal_ref::test_utils::auth_content_from_proposal, RemoveProposal,
+            proposal_ref::test_utils::auth_content_from_proposal, test_utils::test_group,
+            RemoveProposal,
         },
+        key_package::test_utils::test_key_package_message,
     };
 
     use super::*;
@@ -738,4 +783,30 @@ mod tests {
 
         assert_eq!(computed_ref, expected_ref.to_vec());
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_message_round_trips_through_bytes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let welcome = &commit.welcome_messages[0];
+        let bytes = welcome.to_bytes().unwrap();
+        let decoded = MlsMessage::from_bytes(&bytes).unwrap();
+
+        assert_eq!(&decoded, welcome);
+        assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        assert_eq!(decoded.wire_format(), WireFormat::Welcome);
+        assert_eq!(decoded.version, TEST_PROTOCOL_VERSION);
+        assert_eq!(decoded.cipher_suite(), Some(TEST_CIPHER_SUITE));
+    }
 }
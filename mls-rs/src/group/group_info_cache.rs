@@ -0,0 +1,239 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use alloc::vec::Vec;
+use mls_rs_core::{
+    extension::ExtensionType, identity::IdentityProvider, protocol_version::ProtocolVersion,
+    time::MlsTime,
+};
+
+use crate::{client::MlsError, tree_kem::TreeKemPublic, CipherSuiteProvider};
+
+use super::{validate_group_info_joiner, ExportedTree, GroupInfo};
+
+/// Maximum number of groups for which a validated group info is kept.
+const MAX_CACHED_GROUPS: usize = 64;
+
+#[derive(Clone, Debug)]
+struct CachedGroupInfo {
+    group_info: GroupInfo,
+    tree: TreeKemPublic,
+    validated_at: u64,
+}
+
+/// Cache of group infos that were successfully validated by a joiner, keyed
+/// by group id and holding at most the latest validated epoch of each group.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct GroupInfoCache {
+    ttl_in_s: Option<u64>,
+    validated: Arc<Mutex<HashMap<Vec<u8>, CachedGroupInfo>>>,
+}
+
+impl GroupInfoCache {
+    pub(crate) fn new(ttl_in_s: Option<u64>) -> Self {
+        Self {
+            ttl_in_s,
+            validated: Default::default(),
+        }
+    }
+
+    /// Validate `group_info` as [`validate_group_info_joiner`] does, skipping
+    /// the validation if the same group info and tree were validated within
+    /// the TTL.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn validate<C, I>(
+        &self,
+        msg_version: ProtocolVersion,
+        group_info: &GroupInfo,
+        tree: Option<ExportedTree<'_>>,
+        id_provider: &I,
+        cs: &C,
+    ) -> Result<TreeKemPublic, MlsError>
+    where
+        C: CipherSuiteProvider,
+        I: IdentityProvider,
+    {
+        let now = MlsTime::now().seconds_since_epoch();
+
+        if msg_version == group_info.group_context.protocol_version {
+            if let Some(tree) = self.get(group_info, tree.as_ref(), now) {
+                return Ok(tree);
+            }
+        }
+
+        let public_tree =
+            validate_group_info_joiner(msg_version, group_info, tree, id_provider, cs).await?;
+
+        self.insert(group_info, &public_tree, now);
+
+        Ok(public_tree)
+    }
+
+    fn get(
+        &self,
+        group_info: &GroupInfo,
+        tree: Option<&ExportedTree<'_>>,
+        now: u64,
+    ) -> Option<TreeKemPublic> {
+        let ttl_in_s = self.ttl_in_s?;
+        let validated = self.lock();
+        let cached = validated.get(&group_info.group_context.group_id)?;
+
+        let tree_matches = group_info
+            .extensions
+            .has_extension(ExtensionType::RATCHET_TREE)
+            || tree.map_or(false, |tree| {
+                *tree == ExportedTree::new_borrowed(&cached.tree.nodes)
+            });
+
+        (now.saturating_sub(cached.validated_at) < ttl_in_s
+            && tree_matches
+            && cached.group_info == *group_info)
+            .then(|| cached.tree.clone())
+    }
+
+    fn insert(&self, group_info: &GroupInfo, tree: &TreeKemPublic, now: u64) {
+        let Some(ttl_in_s) = self.ttl_in_s else {
+            return;
+        };
+
+        let mut validated = self.lock();
+        validated.retain(|_, cached| now.saturating_sub(cached.validated_at) < ttl_in_s);
+
+        let context = &group_info.group_context;
+
+        // Never replace a newer epoch of the same group by an older one
+        if let Some(cached) = validated.get(&context.group_id) {
+            if cached.group_info.group_context.epoch > context.epoch {
+                return;
+            }
+        }
+
+        if validated.len() >= MAX_CACHED_GROUPS && !validated.contains_key(&context.group_id) {
+            let oldest = validated
+                .iter()
+                .min_by_key(|(_, cached)| cached.validated_at)
+                .map(|(group_id, _)| group_id.clone());
+
+            if let Some(oldest) = oldest {
+                validated.remove(&oldest);
+            }
+        }
+
+        validated.insert(
+            context.group_id.clone(),
+            CachedGroupInfo {
+                group_info: group_info.clone(),
+                tree: tree.clone(),
+                validated_at: now,
+            },
+        );
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Vec<u8>, CachedGroupInfo>> {
+        self.validated.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::Ordering;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::test_cipher_suite_provider,
+        group::test_utils::test_group,
+        identity::{basic::BasicIdentityProvider, test_utils::BasicWithCustomProvider},
+    };
+
+    use super::*;
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn validate(
+        cache: &GroupInfoCache,
+        provider: &BasicWithCustomProvider,
+        group_info: &GroupInfo,
+    ) {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        cache
+            .validate(TEST_PROTOCOL_VERSION, group_info, None, provider, &cs)
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn validated_group_info_is_cached_until_newer_epoch() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let provider = BasicWithCustomProvider::new(BasicIdentityProvider::new());
+        let calls = provider.validate_member_calls.clone();
+        let cache = GroupInfoCache::new(Some(3600));
+
+        let old_info = alice
+            .group
+            .group_info_message(true)
+            .await
+            .unwrap()
+            .into_group_info()
+            .unwrap();
+
+        validate(&cache, &provider, &old_info).await;
+        let validations = calls.load(Ordering::Relaxed);
+        assert!(validations > 0);
+
+        // Within the TTL, the same group info is not validated again
+        validate(&cache, &provider, &old_info).await;
+        assert_eq!(calls.load(Ordering::Relaxed), validations);
+
+        // A group info for a newer epoch replaces the cached one
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let new_info = alice
+            .group
+            .group_info_message(true)
+            .await
+            .unwrap()
+            .into_group_info()
+            .unwrap();
+
+        validate(&cache, &provider, &new_info).await;
+        assert_eq!(calls.load(Ordering::Relaxed), 2 * validations);
+
+        validate(&cache, &provider, &old_info).await;
+        assert_eq!(calls.load(Ordering::Relaxed), 3 * validations);
+
+        validate(&cache, &provider, &new_info).await;
+        assert_eq!(calls.load(Ordering::Relaxed), 3 * validations);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_infos_are_not_cached_without_ttl() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let provider = BasicWithCustomProvider::new(BasicIdentityProvider::new());
+        let calls = provider.validate_member_calls.clone();
+        let cache = GroupInfoCache::new(None);
+
+        let info = alice
+            .group
+            .group_info_message(true)
+            .await
+            .unwrap()
+            .into_group_info()
+            .unwrap();
+
+        validate(&cache, &provider, &info).await;
+
+        let validations = calls.load(Ordering::Relaxed);
+
+        validate(&cache, &provider, &info).await;
+
+        assert_eq!(calls.load(Ordering::Relaxed), 2 * validations);
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+#[cfg(mls_build_async)]
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use mls_rs_codec::MlsEncode;
+use mls_rs_core::{
+    extension::ExtensionList,
+    identity::{CredentialType, IdentityProvider, SigningIdentity},
+    time::MlsTime,
+};
+
+/// Inputs of a successful [`IdentityProvider::validate_member`] call. A
+/// result is only reused for the same group context extensions, and results
+/// of validations without a timestamp are not reused for validations with one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ValidationKey {
+    identity: SigningIdentity,
+    with_timestamp: bool,
+    extensions: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Validation {
+    validated_at: u64,
+    /// Expiration of the credential, after which the result is not reused.
+    expires_at: Option<u64>,
+}
+
+/// Cache of signing identities that were successfully validated by the
+/// identity provider, shared by all providers handed out by a group.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IdentityCache {
+    ttl_in_s: Option<u64>,
+    validated: Arc<Mutex<HashMap<ValidationKey, Validation>>>,
+}
+
+impl IdentityCache {
+    pub(crate) fn new(ttl_in_s: Option<u64>) -> Self {
+        Self {
+            ttl_in_s,
+            validated: Default::default(),
+        }
+    }
+
+    fn key(
+        &self,
+        identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Option<ValidationKey> {
+        self.ttl_in_s?;
+
+        let extensions = match extensions {
+            Some(extensions) => Some(extensions.mls_encode_to_vec().ok()?),
+            None => None,
+        };
+
+        Some(ValidationKey {
+            identity: identity.clone(),
+            with_timestamp: timestamp.is_some(),
+            extensions,
+        })
+    }
+
+    fn is_validated(&self, key: &ValidationKey, timestamp: Option<MlsTime>, now: u64) -> bool {
+        let Some(ttl_in_s) = self.ttl_in_s else {
+            return false;
+        };
+
+        self.lock().get(key).map_or(false, |validation| {
+            let expired = timestamp
+                .zip(validation.expires_at)
+                .map_or(false, |(t, expires_at)| {
+                    t.seconds_since_epoch() > expires_at
+                });
+
+            !expired && now.saturating_sub(validation.validated_at) < ttl_in_s
+        })
+    }
+
+    fn insert(&self, key: ValidationKey, expiration: Option<MlsTime>, now: u64) {
+        let Some(ttl_in_s) = self.ttl_in_s else {
+            return;
+        };
+
+        let mut validated = self.lock();
+
+        validated.retain(|_, validation| now.saturating_sub(validation.validated_at) < ttl_in_s);
+
+        validated.insert(
+            key,
+            Validation {
+                validated_at: now,
+                expires_at: expiration.map(|t| t.seconds_since_epoch()),
+            },
+        );
+    }
+
+    /// Forget the validation results for `identities`.
+    pub(crate) fn invalidate<'a, I>(&self, identities: I)
+    where
+        I: IntoIterator<Item = &'a SigningIdentity>,
+    {
+        if self.ttl_in_s.is_none() {
+            return;
+        }
+
+        let mut validated = self.lock();
+
+        identities.into_iter().for_each(|identity| {
+            validated.retain(|key, _| &key.identity != identity);
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<ValidationKey, Validation>> {
+        self.validated.lock().unwrap()
+    }
+}
+
+/// Identity provider that skips [`IdentityProvider::validate_member`] for
+/// signing identities found in an [`IdentityCache`].
+#[derive(Clone, Debug)]
+pub(crate) struct CachedIdentityProvider<I> {
+    inner: I,
+    cache: IdentityCache,
+}
+
+impl<I> CachedIdentityProvider<I> {
+    pub(crate) fn new(inner: I, cache: IdentityCache) -> Self {
+        Self { inner, cache }
+    }
+}
+
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+#[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+impl<I: IdentityProvider> IdentityProvider for CachedIdentityProvider<I> {
+    type Error = I::Error;
+
+    async fn validate_member(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        let now = MlsTime::now().seconds_since_epoch();
+        let key = self.cache.key(signing_identity, timestamp, extensions);
+
+        if let Some(key) = &key {
+            if self.cache.is_validated(key, timestamp, now) {
+                return Ok(());
+            }
+        }
+
+        self.inner
+            .validate_member(signing_identity, timestamp, extensions)
+            .await?;
+
+        if let Some(key) = key {
+            let expiration = self.inner.credential_expiration(signing_identity);
+            self.cache.insert(key, expiration, now);
+        }
+
+        Ok(())
+    }
+
+    async fn validate_external_sender(
+        &self,
+        signing_identity: &SigningIdentity,
+        timestamp: Option<MlsTime>,
+        extensions: Option<&ExtensionList>,
+    ) -> Result<(), Self::Error> {
+        self.inner
+            .validate_external_sender(signing_identity, timestamp, extensions)
+            .await
+    }
+
+    async fn identity(
+        &self,
+        signing_identity: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<Vec<u8>, Self::Error> {
+        self.inner.identity(signing_identity, extensions).await
+    }
+
+    async fn valid_successor(
+        &self,
+        predecessor: &SigningIdentity,
+        successor: &SigningIdentity,
+        extensions: &ExtensionList,
+    ) -> Result<bool, Self::Error> {
+        self.inner
+            .valid_successor(predecessor, successor, extensions)
+            .await
+    }
+
+    fn supported_types(&self) -> Vec<CredentialType> {
+        self.inner.supported_types()
+    }
+
+    fn max_credential_size(&self) -> Option<usize> {
+        self.inner.max_credential_size()
+    }
+
+    fn credential_expiration(&self, signing_identity: &SigningIdentity) -> Option<MlsTime> {
+        self.inner.credential_expiration(signing_identity)
+    }
+}
@@ -100,6 +100,17 @@ impl KeySchedule {
     /// Returns the derived epoch as well as the joiner secret required for building welcome
     /// messages
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                group_id = ?mls_rs_core::debug::pretty_group_id(&context.group_id),
+                epoch = context.epoch,
+            )
+        )
+    )]
     pub(crate) async fn from_key_schedule<P: CipherSuiteProvider>(
         last_key_schedule: &KeySchedule,
         commit_secret: &PathSecret,
@@ -374,6 +385,14 @@ impl<'a, P: CipherSuiteProvider> SecretsProducer<'a, P> {
     // KDF extract size but then inputs secrets as MAC keys etc, therefore, we require that these
     // lengths match in the crypto provider
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(label = core::str::from_utf8(label).unwrap_or_default())
+        )
+    )]
     async fn derive(&self, label: &[u8]) -> Result<Zeroizing<Vec<u8>>, MlsError> {
         kdf_derive_secret(self.cipher_suite_provider, self.epoch_secret, label).await
     }
@@ -438,6 +457,23 @@ impl InitSecret {
     }
 }
 
+/// Derive the init secret used by an external joiner from the `external_pub`
+/// advertised in a group info.
+///
+/// Returns the KEM output, to be sent in the `ExternalInit` proposal, along
+/// with the init secret that the group members derive from it when processing
+/// the external commit.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn derive_external_init_secret<P: CipherSuiteProvider>(
+    cipher_suite_provider: &P,
+    external_pub: &HpkePublicKey,
+) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), MlsError> {
+    let (init_secret, kem_output) =
+        InitSecret::encode_for_external(cipher_suite_provider, external_pub).await?;
+
+    Ok((kem_output, init_secret.0))
+}
+
 pub(crate) struct WelcomeSecret<'a, P: CipherSuiteProvider> {
     cipher_suite: &'a P,
     key: Zeroizing<Vec<u8>>,
@@ -564,7 +600,7 @@ pub(crate) mod test_utils {
 
 #[cfg(test)]
 mod tests {
-    use crate::client::test_utils::TEST_PROTOCOL_VERSION;
+    use crate::client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION};
     use crate::crypto::test_utils::try_test_cipher_suite_provider;
     use crate::group::key_schedule::{
         get_welcome_secret, kdf_derive_secret, kdf_expand_with_label,
@@ -593,7 +629,7 @@ mod tests {
     use zeroize::Zeroizing;
 
     use super::test_utils::get_test_key_schedule;
-    use super::KeySchedule;
+    use super::{derive_external_init_secret, KeySchedule};
 
     #[derive(serde::Deserialize, serde::Serialize)]
     struct TestCase {
@@ -985,4 +1021,23 @@ mod tests {
             }
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_init_secret_matches_member_derivation() {
+        let cs = crate::crypto::test_utils::test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let key_schedule = get_test_key_schedule(TEST_CIPHER_SUITE);
+
+        let (_, external_pub) = key_schedule.get_external_key_pair(&cs).await.unwrap();
+
+        let (kem_output, init_secret) = derive_external_init_secret(&cs, &external_pub)
+            .await
+            .unwrap();
+
+        let member_key_schedule = key_schedule
+            .derive_for_external(&kem_output, &cs)
+            .await
+            .unwrap();
+
+        assert_eq!(member_key_schedule.init_secret.0, init_secret);
+    }
 }
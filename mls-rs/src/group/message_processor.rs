@@ -65,6 +65,9 @@ use super::{member_from_key_package, member_from_leaf_node};
 #[cfg(all(feature = "state_update", feature = "custom_proposal"))]
 use super::proposal::CustomProposal;
 
+#[cfg(all(feature = "state_update", feature = "app_ack"))]
+use super::proposal::AppAckProposal;
+
 #[cfg(feature = "private_message")]
 use crate::group::framing::PrivateMessage;
 
@@ -115,6 +118,8 @@ pub struct StateUpdate {
     pub(crate) epoch: u64,
     #[cfg(feature = "custom_proposal")]
     pub(crate) custom_proposals: Vec<ProposalInfo<CustomProposal>>,
+    #[cfg(feature = "app_ack")]
+    pub(crate) app_acks: Vec<crate::mls_rules::ProposalInfo<AppAckProposal>>,
     #[cfg(feature = "by_ref_proposal")]
     pub(crate) unused_proposals: Vec<crate::mls_rules::ProposalInfo<Proposal>>,
 }
@@ -161,6 +166,13 @@ impl StateUpdate {
         &self.custom_proposals
     }
 
+    /// AppAck proposals that were committed to, reporting the application
+    /// messages received by their senders.
+    #[cfg(feature = "app_ack")]
+    pub fn app_acks(&self) -> &[crate::mls_rules::ProposalInfo<AppAckProposal>] {
+        &self.app_acks
+    }
+
     /// Proposals that were received in the prior epoch but not committed to.
     #[cfg(feature = "by_ref_proposal")]
     pub fn unused_proposals(&self) -> &[crate::mls_rules::ProposalInfo<Proposal>] {
@@ -243,7 +255,7 @@ pub struct ApplicationMessageDescription {
     /// Index of this user in the group state.
     pub sender_index: u32,
     /// Received application data.
-    data: ApplicationData,
+    pub(crate) data: ApplicationData,
     /// Plaintext authenticated data in the received MLS packet.
     pub authenticated_data: Vec<u8>,
 }
@@ -590,6 +602,11 @@ pub(crate) trait MessageProcessor: Send + Sync {
         let proposal_ref =
             ProposalRef::from_content(self.cipher_suite_provider(), auth_content).await?;
 
+        #[cfg(feature = "app_ack")]
+        if let Proposal::AppAck(app_ack) = proposal {
+            app_ack.validate(&self.group_state().public_tree)?;
+        }
+
         let group_state = self.group_state_mut();
 
         if cache_proposal {
@@ -703,6 +720,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
             epoch: provisional.group_context.epoch,
             #[cfg(feature = "custom_proposal")]
             custom_proposals: provisional.applied_proposals.custom_proposals.clone(),
+            #[cfg(feature = "app_ack")]
+            app_acks: provisional.applied_proposals.app_acks.clone(),
             #[cfg(feature = "by_ref_proposal")]
             unused_proposals: provisional.unused_proposals.clone(),
         };
@@ -710,6 +729,18 @@ pub(crate) trait MessageProcessor: Send + Sync {
         Ok(update)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                group_id = ?mls_rs_core::debug::pretty_group_id(&self.group_state().context.group_id),
+                epoch = self.group_state().context.epoch,
+                member_count = self.group_state().public_tree.occupied_leaf_count(),
+            )
+        )
+    )]
     async fn process_commit(
         &mut self,
         auth_content: AuthenticatedContent,
@@ -783,6 +814,8 @@ pub(crate) trait MessageProcessor: Send + Sync {
                 state_update.active = false;
             }
 
+            self.mark_self_removed();
+
             return Ok(CommitMessageDescription {
                 is_external: matches!(auth_content.content.sender, Sender::NewMemberCommit),
                 authenticated_data: auth_content.content.authenticated_data,
@@ -829,17 +862,16 @@ pub(crate) trait MessageProcessor: Send + Sync {
             .tree_hash(self.cipher_suite_provider())
             .await?;
 
-        if let Some(reinit) = provisional_state.applied_proposals.reinitializations.pop() {
-            self.group_state_mut().pending_reinit = Some(reinit.proposal);
+        let pending_reinit = provisional_state.applied_proposals.reinitializations.pop();
 
-            #[cfg(feature = "state_update")]
-            {
-                state_update.active = false;
-            }
+        #[cfg(feature = "state_update")]
+        if pending_reinit.is_some() {
+            state_update.active = false;
         }
 
         if let Some(confirmation_tag) = &auth_content.auth.confirmation_tag {
-            // Update the key schedule to calculate new private keys
+            // Update the key schedule to calculate new private keys. The group state is
+            // only modified once the new epoch has been fully computed and verified.
             self.update_key_schedule(
                 new_secrets,
                 interim_transcript_hash,
@@ -848,6 +880,10 @@ pub(crate) trait MessageProcessor: Send + Sync {
             )
             .await?;
 
+            if let Some(reinit) = pending_reinit {
+                self.group_state_mut().pending_reinit = Some(reinit.proposal);
+            }
+
             Ok(CommitMessageDescription {
                 is_external: matches!(auth_content.content.sender, Sender::NewMemberCommit),
                 authenticated_data: auth_content.content.authenticated_data,
@@ -869,6 +905,9 @@ pub(crate) trait MessageProcessor: Send + Sync {
     fn psk_storage(&self) -> Self::PreSharedKeyStorage;
     fn can_continue_processing(&self, provisional_state: &ProvisionalState) -> bool;
 
+    /// Called when a commit removing the local member is processed.
+    fn mark_self_removed(&mut self) {}
+
     #[cfg(feature = "private_message")]
     fn min_epoch_available(&self) -> Option<u64>;
 
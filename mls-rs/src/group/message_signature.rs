@@ -151,6 +151,11 @@ impl MlsDecode for AuthenticatedContent {
     }
 }
 
+/// `FramedContentTBS` as defined by RFC 9420.
+///
+/// The encoded `content` starts with its [`ContentType`], so a signature
+/// produced over a proposal can never be verified as a signature over a
+/// commit (or application message) carrying the same body bytes.
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct AuthenticatedContentTBS<'a> {
     pub(crate) protocol_version: ProtocolVersion,
@@ -244,8 +244,8 @@ mod tests {
         crypto::SignatureSecretKey,
         group::{
             message_signature::MessageSigningContext,
-            proposal::{AddProposal, Proposal, RemoveProposal},
-            Content,
+            proposal::{AddProposal, Proposal, ProposalOrRef, RemoveProposal},
+            Commit, Content,
         },
         key_package::KeyPackageGeneration,
         signer::Signable,
@@ -660,6 +660,55 @@ mod tests {
         assert_matches!(res, Err(MlsError::MembershipTagForNonMember));
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposal_signature_does_not_verify_as_commit() {
+        let env = TestEnv::new().await;
+
+        let proposal = Proposal::Remove(RemoveProposal {
+            to_remove: LeafIndex(1),
+        });
+
+        let mut content = AuthenticatedContent::new_signed(
+            &env.alice.group.cipher_suite_provider,
+            env.alice.group.context(),
+            Sender::Member(0),
+            Content::Proposal(Box::new(proposal.clone())),
+            &env.alice.group.signer,
+            WireFormat::PublicMessage,
+            vec![],
+        )
+        .await
+        .unwrap();
+
+        verify_auth_content_signature(
+            &env.bob.group.cipher_suite_provider,
+            super::SignaturePublicKeysContainer::RatchetTree(&env.bob.group.state.public_tree),
+            env.bob.group.context(),
+            &content,
+            &[],
+        )
+        .await
+        .unwrap();
+
+        // Reinterpret the signed proposal as a commit while keeping the signature
+        content.content.content = Content::Commit(Box::new(Commit {
+            proposals: vec![ProposalOrRef::Proposal(Box::new(proposal))],
+            path: None,
+        }));
+
+        let res = verify_auth_content_signature(
+            &env.bob.group.cipher_suite_provider,
+            super::SignaturePublicKeysContainer::RatchetTree(&env.bob.group.state.public_tree),
+            env.bob.group.context(),
+            &content,
+            &[],
+        )
+        .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn plaintext_from_self_fails_verification() {
         let mut env = TestEnv::new().await;
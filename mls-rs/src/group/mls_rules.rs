@@ -93,6 +93,10 @@ pub struct EncryptionOptions {
     pub encrypt_control_messages: bool,
     #[cfg(feature = "private_message")]
     pub padding_mode: PaddingMode,
+    /// Block size used by [`PaddingMode::FixedBlock`]. It must be set to a
+    /// non-zero value when using that mode.
+    #[cfg(feature = "private_message")]
+    pub padding_block_size: usize,
 }
 
 #[cfg(feature = "private_message")]
@@ -101,6 +105,15 @@ impl EncryptionOptions {
         Self {
             encrypt_control_messages,
             padding_mode,
+            padding_block_size: 0,
+        }
+    }
+
+    /// Set the block size used by [`PaddingMode::FixedBlock`].
+    pub fn with_padding_block_size(self, padding_block_size: usize) -> Self {
+        Self {
+            padding_block_size,
+            ..self
         }
     }
 
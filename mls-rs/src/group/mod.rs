@@ -2,6 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::fmt::{self, Debug};
@@ -13,9 +14,9 @@ use mls_rs_core::time::MlsTime;
 use crate::cipher_suite::CipherSuite;
 use crate::client::MlsError;
 use crate::client_config::ClientConfig;
-use crate::crypto::{HpkeCiphertext, SignatureSecretKey};
+use crate::crypto::{HpkeCiphertext, SignaturePublicKey, SignatureSecretKey};
 use crate::extension::RatchetTreeExt;
-use crate::identity::SigningIdentity;
+use crate::identity::{Credential, SigningIdentity};
 use crate::key_package::{KeyPackage, KeyPackageRef};
 use crate::protocol_version::ProtocolVersion;
 use crate::psk::secret::PskSecret;
@@ -39,6 +40,9 @@ use crate::crypto::{HpkePublicKey, HpkeSecretKey};
 
 use crate::extension::ExternalPubExt;
 
+#[cfg(feature = "by_ref_proposal")]
+use crate::extension::ExternalSendersExt;
+
 #[cfg(feature = "private_message")]
 use self::mls_rules::{EncryptionOptions, MlsRules};
 
@@ -84,6 +88,9 @@ use secret_tree::*;
 #[cfg(feature = "prior_epoch")]
 use self::epoch::PriorEpoch;
 
+#[cfg(feature = "std")]
+use self::identity_cache::{CachedIdentityProvider, IdentityCache};
+
 use self::epoch::EpochSecrets;
 pub use self::message_processor::{
     ApplicationMessageDescription, CommitMessageDescription, ProposalMessageDescription,
@@ -94,18 +101,27 @@ use self::message_processor::{EventOrContent, MessageProcessor, ProvisionalState
 use self::proposal_ref::ProposalRef;
 use self::state_repo::GroupStateRepository;
 pub use group_info::GroupInfo;
+pub use key_schedule::derive_external_init_secret;
 
 pub use self::framing::{ContentType, Sender};
 pub use commit::*;
 pub use context::GroupContext;
 pub use roster::*;
+pub use snapshot::GroupSnapshot;
+pub use status::{GroupStatus, SuspensionReason};
+pub use util::verify_group_infos;
 
 pub(crate) use transcript_hash::ConfirmedTranscriptHash;
 pub(crate) use util::*;
 
+#[cfg(feature = "private_message")]
+pub use application_chunks::ApplicationChunkReassembler;
+
 #[cfg(all(feature = "by_ref_proposal", feature = "external_client"))]
 pub use self::message_processor::CachedProposal;
 
+#[cfg(feature = "private_message")]
+mod application_chunks;
 #[cfg(feature = "private_message")]
 mod ciphertext_processor;
 
@@ -115,6 +131,10 @@ mod context;
 pub(crate) mod epoch;
 pub(crate) mod framing;
 mod group_info;
+#[cfg(feature = "std")]
+pub(crate) mod group_info_cache;
+#[cfg(feature = "std")]
+mod identity_cache;
 pub(crate) mod key_schedule;
 mod membership_tag;
 pub(crate) mod message_processor;
@@ -134,6 +154,11 @@ mod resumption;
 mod roster;
 pub(crate) mod snapshot;
 pub(crate) mod state;
+mod status;
+
+mod capability_diff;
+
+pub use capability_diff::CapabilityDiff;
 
 #[cfg(feature = "prior_epoch")]
 pub(crate) mod state_repo;
@@ -270,11 +295,16 @@ where
     #[cfg(all(not(feature = "std"), feature = "by_ref_proposal"))]
     pending_updates: Vec<(HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>))>,
     pending_commit: Option<CommitGeneration>,
+    last_committer: Option<u32>,
+    removed: bool,
     #[cfg(feature = "psk")]
     previous_psk: Option<PskSecretInput>,
+    #[cfg(feature = "std")]
+    identity_cache: IdentityCache,
     #[cfg(test)]
     pub(crate) commit_modifiers: CommitModifiers,
     pub(crate) signer: SignatureSecretKey,
+    leaf_rotation_epochs: BTreeMap<u32, u64>,
 }
 
 #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen)]
@@ -311,8 +341,14 @@ where
             Some(&group_context_extensions),
         );
 
+        #[cfg(feature = "std")]
+        let time = Some(MlsTime::now());
+
+        #[cfg(not(feature = "std"))]
+        let time = None;
+
         leaf_node_validator
-            .check_if_valid(&leaf_node, ValidationContext::Add(None))
+            .check_if_valid(&leaf_node, ValidationContext::Add(time))
             .await?;
 
         let (mut public_tree, private_tree) = TreeKemPublic::derive(
@@ -368,6 +404,9 @@ where
         )
         .await?;
 
+        #[cfg(feature = "std")]
+        let identity_cache = IdentityCache::new(config.identity_cache_ttl_in_s());
+
         Ok(Self {
             config,
             state: GroupState::new(context, public_tree, interim_hash, confirmation_tag),
@@ -376,6 +415,8 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: None,
+            last_committer: None,
+            removed: false,
             #[cfg(test)]
             commit_modifiers: Default::default(),
             epoch_secrets: key_schedule_result.epoch_secrets,
@@ -383,7 +424,10 @@ where
             cipher_suite_provider,
             #[cfg(feature = "psk")]
             previous_psk: None,
+            #[cfg(feature = "std")]
+            identity_cache,
             signer,
+            leaf_rotation_epochs: BTreeMap::from([(0, 0)]),
         })
     }
 
@@ -423,6 +467,8 @@ where
             return Err(MlsError::UnexpectedMessageType);
         };
 
+        check_cipher_suite_strength(&config, welcome.cipher_suite)?;
+
         let cipher_suite_provider =
             cipher_suite_provider(config.crypto_provider(), welcome.cipher_suite)?;
 
@@ -500,7 +546,7 @@ where
 
         let group_info = GroupInfo::mls_decode(&mut &**decrypted_group_info)?;
 
-        let public_tree = validate_group_info_joiner(
+        let mut public_tree = validate_group_info_joiner(
             protocol_version,
             &group_info,
             tree_data,
@@ -509,6 +555,11 @@ where
         )
         .await?;
 
+        group_info
+            .group_context
+            .validate_against(&mut public_tree, &cipher_suite_provider)
+            .await?;
+
         // Identify a leaf in the tree array (any even-numbered node) whose leaf_node is identical
         // to the leaf_node field of the KeyPackage. If no such field exists, return an error. Let
         // index represent the index of this node among the leaves in the tree, namely the index of
@@ -575,6 +626,18 @@ where
 
     #[allow(clippy::too_many_arguments)]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                group_id = ?mls_rs_core::debug::pretty_group_id(&group_info.group_context.group_id),
+                epoch = group_info.group_context.epoch,
+                member_count = public_tree.occupied_leaf_count(),
+            )
+        )
+    )]
     async fn join_with(
         config: C,
         group_info: GroupInfo,
@@ -609,6 +672,13 @@ where
             used_key_package_ref,
         )?;
 
+        #[cfg(feature = "std")]
+        let identity_cache = IdentityCache::new(config.identity_cache_ttl_in_s());
+
+        // The epochs at which existing members last rotated their keys are unknown to a new member
+        let leaf_rotation_epochs =
+            BTreeMap::from([(*private_tree.self_index, group_info.group_context.epoch)]);
+
         let group = Group {
             config,
             state: GroupState::new(
@@ -622,6 +692,8 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: None,
+            last_committer: None,
+            removed: false,
             #[cfg(test)]
             commit_modifiers: Default::default(),
             epoch_secrets,
@@ -629,7 +701,10 @@ where
             cipher_suite_provider: cs,
             #[cfg(feature = "psk")]
             previous_psk: None,
+            #[cfg(feature = "std")]
+            identity_cache,
             signer,
+            leaf_rotation_epochs,
         };
 
         Ok((group, NewMemberInfo::new(group_info.extensions)))
@@ -647,6 +722,33 @@ where
         self.context().epoch
     }
 
+    /// Whether the group can still be used to encrypt messages and create
+    /// commits.
+    pub fn status(&self) -> GroupStatus {
+        if self.removed {
+            return GroupStatus::Suspended(SuspensionReason::Removed);
+        }
+
+        if self.state.pending_reinit.is_some() {
+            return GroupStatus::Suspended(SuspensionReason::ReInit);
+        }
+
+        #[cfg(feature = "by_ref_proposal")]
+        if self.state.proposals.has_reinit() {
+            return GroupStatus::PendingReinit;
+        }
+
+        GroupStatus::Active
+    }
+
+    fn ensure_active(&self) -> Result<(), MlsError> {
+        match self.status() {
+            GroupStatus::Suspended(SuspensionReason::ReInit) => Err(MlsError::GroupUsedAfterReInit),
+            status if !status.is_active() => Err(MlsError::GroupNotActive),
+            _ => Ok(()),
+        }
+    }
+
     /// Index within the group's state for the local group instance.
     ///
     /// This index corresponds to indexes in content descriptions within
@@ -680,6 +782,32 @@ where
             .map(|ln| member_from_leaf_node(ln, leaf_index))
     }
 
+    /// Signing identity of the sender of a message in the current epoch.
+    ///
+    /// Member senders resolve to the credential of their leaf and external
+    /// senders to the matching entry of the
+    /// [`ExternalSendersExt`](crate::extension::ExternalSendersExt) in the
+    /// group context. New member senders are not part of the group state,
+    /// and their identity is found in the message itself.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn sender_identity(&self, sender: &Sender) -> Result<SigningIdentity, MlsError> {
+        match sender {
+            Sender::Member(index) => self
+                .current_epoch_tree()
+                .get_leaf_node(LeafIndex(*index))
+                .map(|leaf| leaf.signing_identity.clone())
+                .map_err(|_| MlsError::LeafNotFound(*index)),
+            #[cfg(feature = "by_ref_proposal")]
+            Sender::External(index) => self
+                .context()
+                .extensions
+                .get_as::<ExternalSendersExt>()?
+                .and_then(|ext| ext.allowed_senders.get(*index as usize).cloned())
+                .ok_or(MlsError::UnknownSigningIdentityForExternalSender),
+            _ => Err(MlsError::InvalidSender),
+        }
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn proposal_message(
@@ -897,6 +1025,70 @@ where
         self.proposal_message(proposal, authenticated_data).await
     }
 
+    /// Create a proposal message that rotates your signature key and
+    /// credential while keeping your current HPKE key.
+    ///
+    /// This is useful when signing keys are renewed more often than
+    /// encryption keys, for instance on certificate renewal. Unlike
+    /// [`Group::propose_update_with_identity`], this proposal does not
+    /// contribute forward secrecy for your leaf.
+    ///
+    /// The new identity must be an accepted
+    /// [successor](crate::IdentityProvider::valid_successor) of the current
+    /// one and must use a different signature key.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_signature_key_rotation(
+        &mut self,
+        signer: SignatureSecretKey,
+        signing_identity: SigningIdentity,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let current_leaf = self.current_user_leaf_node()?;
+
+        if signing_identity.signature_key == current_leaf.signing_identity.signature_key {
+            return Err(MlsError::NoKeyChangeInUpdate);
+        }
+
+        let secret_key = self
+            .private_tree
+            .secret_keys
+            .first()
+            .cloned()
+            .flatten()
+            .ok_or(MlsError::InvalidTreeKemPrivateKey)?;
+
+        let mut new_leaf_node = current_leaf.clone();
+
+        new_leaf_node
+            .update_signing_identity(
+                &self.cipher_suite_provider,
+                self.group_id(),
+                self.current_member_index(),
+                self.config.leaf_properties(),
+                Some(signing_identity),
+                &signer,
+            )
+            .await?;
+
+        #[cfg(feature = "std")]
+        self.pending_updates
+            .insert(new_leaf_node.public_key.clone(), (secret_key, Some(signer)));
+
+        #[cfg(not(feature = "std"))]
+        self.pending_updates
+            .push((new_leaf_node.public_key.clone(), (secret_key, Some(signer))));
+
+        let proposal = Proposal::Update(UpdateProposal {
+            leaf_node: new_leaf_node,
+        });
+
+        self.proposal_message(proposal, authenticated_data).await
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn update_proposal(
@@ -918,6 +1110,17 @@ where
             )
             .await?;
 
+        #[cfg(test)]
+        (self.commit_modifiers.modify_update_leaf)(
+            &mut new_leaf_node,
+            self.current_user_leaf_node()?,
+        );
+
+        // Reusing the current key would make the update useless for forward secrecy
+        if new_leaf_node.public_key == self.current_user_leaf_node()?.public_key {
+            return Err(MlsError::NoKeyChangeInUpdate);
+        }
+
         // Store the secret key in the pending updates storage for later
         #[cfg(feature = "std")]
         self.pending_updates
@@ -948,6 +1151,41 @@ where
         self.proposal_message(proposal, authenticated_data).await
     }
 
+    /// Create a proposal message that removes the member whose leaf holds `credential`.
+    ///
+    /// Returns [`MlsError::MemberNotFound`] if no member has this credential and
+    /// [`MlsError::AmbiguousIdentity`] if several members have it.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_remove_by_identity(
+        &mut self,
+        credential: &Credential,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        let proposal = self.remove_proposal_by_identity(credential)?;
+        self.proposal_message(proposal, authenticated_data).await
+    }
+
+    fn remove_proposal_by_identity(&self, credential: &Credential) -> Result<Proposal, MlsError> {
+        let mut matching = self
+            .current_epoch_tree()
+            .non_empty_leaves()
+            .filter(|(_, leaf)| leaf.signing_identity.credential == *credential)
+            .map(|(index, _)| index);
+
+        let to_remove = matching.next().ok_or(MlsError::MemberNotFound)?;
+
+        if matching.next().is_some() {
+            return Err(MlsError::AmbiguousIdentity);
+        }
+
+        Ok(Proposal::Remove(RemoveProposal { to_remove }))
+    }
+
     fn remove_proposal(&self, index: u32) -> Result<Proposal, MlsError> {
         let leaf_index = LeafIndex(index);
 
@@ -1003,14 +1241,47 @@ where
         psk_epoch: u64,
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
+        let psk = self
+            .resumption_psk_id(ResumptionPSKUsage::Application, psk_epoch)
+            .await?;
+
+        let proposal = Proposal::Psk(PreSharedKeyProposal { psk });
+        self.proposal_message(proposal, authenticated_data).await
+    }
+
+    /// Identifier of the resumption PSK of this group at `psk_epoch`.
+    ///
+    /// Fails with [`MlsError::ResumptionPsksNotFound`] if the resumption secret of
+    /// `psk_epoch` is no longer retained.
+    #[cfg(feature = "psk")]
+    #[cfg_attr(not(feature = "by_ref_proposal"), allow(dead_code))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn resumption_psk_id(
+        &self,
+        usage: ResumptionPSKUsage,
+        psk_epoch: u64,
+    ) -> Result<PreSharedKeyID, MlsError> {
         let key_id = ResumptionPsk {
-            psk_epoch,
-            usage: ResumptionPSKUsage::Application,
+            usage,
             psk_group_id: PskGroupId(self.group_id().to_vec()),
+            psk_epoch,
         };
 
-        let proposal = self.psk_proposal(JustPreSharedKeyID::Resumption(key_id))?;
-        self.proposal_message(proposal, authenticated_data).await
+        #[cfg(feature = "prior_epoch")]
+        let retained = psk_epoch == self.current_epoch()
+            || self.state_repo.resumption_secret(&key_id).await?.is_some();
+
+        #[cfg(not(feature = "prior_epoch"))]
+        let retained = psk_epoch == self.current_epoch();
+
+        if !retained {
+            return Err(MlsError::ResumptionPsksNotFound(vec![key_id]));
+        }
+
+        PreSharedKeyID::new(
+            JustPreSharedKeyID::Resumption(key_id),
+            &self.cipher_suite_provider,
+        )
     }
 
     /// Create a proposal message that requests for this group to be
@@ -1085,6 +1356,22 @@ where
         Proposal::GroupContextExtensions(extensions)
     }
 
+    /// Create a proposal message acknowledging the receipt of application
+    /// messages.
+    ///
+    /// `authenticated_data` will be sent unencrypted along with the contents
+    /// of the proposal message.
+    #[cfg(all(feature = "app_ack", feature = "by_ref_proposal"))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn propose_app_ack(
+        &mut self,
+        proposal: AppAckProposal,
+        authenticated_data: Vec<u8>,
+    ) -> Result<MlsMessage, MlsError> {
+        self.proposal_message(Proposal::AppAck(proposal), authenticated_data)
+            .await
+    }
+
     /// Create a custom proposal message.
     ///
     /// `authenticated_data` will be sent unencrypted along with the contents
@@ -1152,11 +1439,17 @@ where
         &mut self,
         auth_content: AuthenticatedContent,
     ) -> Result<PrivateMessage, MlsError> {
-        let padding_mode = self.encryption_options()?.padding_mode;
+        let options = self.encryption_options()?;
 
         let mut encryptor = CiphertextProcessor::new(self, self.cipher_suite_provider.clone());
 
-        encryptor.seal(auth_content, padding_mode).await
+        encryptor
+            .seal(
+                auth_content,
+                options.padding_mode,
+                options.padding_block_size,
+            )
+            .await
     }
 
     /// Encrypt an application message using the current group state.
@@ -1170,6 +1463,8 @@ where
         message: &[u8],
         authenticated_data: Vec<u8>,
     ) -> Result<MlsMessage, MlsError> {
+        self.ensure_active()?;
+
         // A group member that has observed one or more proposals within an epoch MUST send a Commit message
         // before sending application data
         #[cfg(feature = "by_ref_proposal")]
@@ -1248,6 +1543,54 @@ where
         Ok(auth_content)
     }
 
+    /// Returns `true` if the holder of this group state and of `private` is able to decrypt
+    /// `ciphertext`.
+    ///
+    /// This requires `private` to hold the secret key of a leaf in the tree of this group, and
+    /// `ciphertext` to open with the epoch secrets held by this group. A removed member whose
+    /// state is retained therefore can't decrypt messages of epochs after its removal.
+    #[cfg(all(test, feature = "private_message"))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn can_decrypt_with(
+        &self,
+        private: &TreeKemPrivate,
+        ciphertext: &PrivateMessage,
+    ) -> bool {
+        let (Some(Some(secret_key)), Ok(leaf)) = (
+            private.secret_keys.first(),
+            self.current_epoch_tree().get_leaf_node(private.self_index),
+        ) else {
+            return false;
+        };
+
+        // Check that the leaf secret key belongs to the leaf currently in the tree.
+        let Ok(probe) = self
+            .cipher_suite_provider
+            .hpke_seal(&leaf.public_key, &[], None, b"probe")
+            .await
+        else {
+            return false;
+        };
+
+        let key_matches = self
+            .cipher_suite_provider
+            .hpke_open(&probe, secret_key, &leaf.public_key, &[], None)
+            .await
+            .is_ok();
+
+        if !key_matches {
+            return false;
+        }
+
+        let mut group = self.clone();
+        group.private_tree = private.clone();
+
+        CiphertextProcessor::new(&mut group, self.cipher_suite_provider.clone())
+            .open(ciphertext)
+            .await
+            .is_ok()
+    }
+
     /// Apply a pending commit that was created by [`Group::commit`] or
     /// [`CommitBuilder::build`].
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -1257,7 +1600,47 @@ where
             .clone()
             .ok_or(MlsError::PendingCommitNotFound)?;
 
-        self.process_commit(pending_commit.content, None).await
+        let description = self.process_commit(pending_commit.content, None).await?;
+        self.last_committer = Some(description.committer);
+
+        Ok(description)
+    }
+
+    /// Index of the member that created the most recently processed commit,
+    /// or `None` if no commit has been processed since the group was created
+    /// or joined.
+    pub fn last_committer(&self) -> Option<u32> {
+        self.last_committer
+    }
+
+    /// Number of epochs since the member at `index` last changed the HPKE key
+    /// of its leaf, either by joining, by sending an update or by committing
+    /// with a path.
+    ///
+    /// Returns `None` if there is no member at `index` or if the member has
+    /// not rotated its key since this client joined the group.
+    pub fn epochs_since_rotation(&self, index: u32) -> Option<u64> {
+        self.leaf_rotation_epochs
+            .get(&index)
+            .map(|epoch| self.context().epoch - epoch)
+    }
+
+    fn update_leaf_rotation_epochs(&mut self, new_tree: &TreeKemPublic) {
+        let epoch = self.context().epoch;
+        let old_tree = &self.state.public_tree;
+
+        self.leaf_rotation_epochs
+            .retain(|index, _| new_tree.get_leaf_node(LeafIndex(*index)).is_ok());
+
+        for (index, leaf) in new_tree.non_empty_leaves() {
+            let rotated = old_tree
+                .get_leaf_node(index)
+                .map_or(true, |old| old.public_key != leaf.public_key);
+
+            if rotated {
+                self.leaf_rotation_epochs.insert(*index, epoch);
+            }
+        }
     }
 
     /// Returns true if a commit has been created but not yet applied
@@ -1299,13 +1682,17 @@ where
             }
         }
 
-        MessageProcessor::process_incoming_message(
+        let received = MessageProcessor::process_incoming_message(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
         )
-        .await
+        .await?;
+
+        self.record_committer(&received);
+
+        Ok(received)
     }
 
     /// Process an inbound message for this group, providing additional context
@@ -1330,14 +1717,24 @@ where
         message: MlsMessage,
         time: MlsTime,
     ) -> Result<ReceivedMessage, MlsError> {
-        MessageProcessor::process_incoming_message_with_time(
+        let received = MessageProcessor::process_incoming_message_with_time(
             self,
             message,
             #[cfg(feature = "by_ref_proposal")]
             true,
             Some(time),
         )
-        .await
+        .await?;
+
+        self.record_committer(&received);
+
+        Ok(received)
+    }
+
+    fn record_committer(&mut self, received: &ReceivedMessage) {
+        if let ReceivedMessage::Commit(description) = received {
+            self.last_committer = Some(description.committer);
+        }
     }
 
     /// Find a group member by
@@ -1446,6 +1843,9 @@ where
         Ok(self.key_schedule.authentication_secret.clone().into())
     }
 
+    /// Derive `len` bytes of secret key material bound to the current epoch, using the
+    /// MLS exporter defined in
+    /// [RFC 9420 Section 8.5](https://www.rfc-editor.org/rfc/rfc9420.html#section-8.5).
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn export_secret(
         &self,
@@ -1467,6 +1867,23 @@ where
         ExportedTree::new_borrowed(&self.current_epoch_tree().nodes)
     }
 
+    /// Validate the parent hash chain from the leaf of the member at
+    /// `member_index` up to the root of the current ratchet tree.
+    ///
+    /// Processing a commit already checks the parent hashes of the
+    /// committer's new path. This is an optional, cheap integrity check of a
+    /// single path that can be run on the committer of the latest commit,
+    /// as opposed to validating every parent hash of the tree.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn validate_parent_hashes_for_member(
+        &self,
+        member_index: u32,
+    ) -> Result<(), MlsError> {
+        self.current_epoch_tree()
+            .validate_parent_hashes_for_path(LeafIndex(member_index), &self.cipher_suite_provider)
+            .await
+    }
+
     /// Current version of the MLS protocol in use by this group.
     pub fn protocol_version(&self) -> ProtocolVersion {
         self.context().protocol_version
@@ -1482,6 +1899,30 @@ where
         self.group_state().public_tree.roster()
     }
 
+    /// Signature public keys of all current members of the group, in leaf order.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn signature_keys(&self) -> Vec<SignaturePublicKey> {
+        self.current_epoch_tree()
+            .non_empty_leaves()
+            .map(|(_, leaf)| leaf.signing_identity.signature_key.clone())
+            .collect()
+    }
+
+    /// Capabilities supported by every current member of the group.
+    pub fn common_capabilities(&self) -> Capabilities {
+        capability_diff::common_capabilities(
+            self.roster()
+                .members_iter()
+                .map(|member| member.capabilities),
+        )
+    }
+
+    /// Capabilities that became, or stopped being, supported by every member
+    /// since `previous`, which is typically this group at an earlier epoch.
+    pub fn capability_changes_since(&self, previous: &Group<C>) -> CapabilityDiff {
+        CapabilityDiff::new(&previous.common_capabilities(), &self.common_capabilities())
+    }
+
     /// Determines equality of two different groups internal states.
     /// Useful for testing.
     ///
@@ -1507,6 +1948,10 @@ where
                 .map(|psk| psk.proposal.psk.clone())
                 .collect::<Vec<_>>();
 
+            if psks.len() > usize::from(self.config.max_psks_per_commit()) {
+                return Err(MlsError::TooManyPsks);
+            }
+
             let psk = PskResolver {
                 group_context: Some(self.context()),
                 current_epoch: Some(&self.epoch_secrets),
@@ -1533,6 +1978,21 @@ where
         PskSecret::new(self.cipher_suite_provider())
     }
 
+    /// Highest application message generation used by each member in the
+    /// current epoch, keyed by the member's index.
+    ///
+    /// Members that have not sent or received application messages in the
+    /// current epoch are omitted.
+    #[cfg(any(feature = "secret_tree_access", feature = "private_message"))]
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn sender_generations(&self) -> BTreeMap<u32, u32> {
+        self.epoch_secrets
+            .secret_tree
+            .application_generations()
+            .map(|(node_index, generation)| (node_index / 2, generation))
+            .collect()
+    }
+
     #[cfg(feature = "secret_tree_access")]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     #[inline(never)]
@@ -1599,6 +2059,9 @@ where
     C: ClientConfig + Clone,
 {
     type MlsRules = C::MlsRules;
+    #[cfg(feature = "std")]
+    type IdentityProvider = CachedIdentityProvider<C::IdentityProvider>;
+    #[cfg(not(feature = "std"))]
     type IdentityProvider = C::IdentityProvider;
     type PreSharedKeyStorage = C::PskStore;
     type OutputType = ReceivedMessage;
@@ -1642,12 +2105,7 @@ where
         provisional_state: &mut ProvisionalState,
     ) -> Result<Option<(TreeKemPrivate, PathSecret)>, MlsError> {
         // Update the private tree to create a provisional private tree
-        let (mut provisional_private_tree, new_signer) =
-            self.provisional_private_tree(provisional_state)?;
-
-        if let Some(signer) = new_signer {
-            self.signer = signer;
-        }
+        let (mut provisional_private_tree, _) = self.provisional_private_tree(provisional_state)?;
 
         provisional_state
             .public_tree
@@ -1660,7 +2118,14 @@ where
             )
             .await?;
 
-        if let Some(pending) = &self.pending_commit {
+        // The secrets of a pending commit only apply to that commit. A commit from another
+        // member for the same epoch replaces it instead.
+        let pending = self
+            .pending_commit
+            .as_ref()
+            .filter(|_| sender == self.private_tree.self_index);
+
+        if let Some(pending) = pending {
             Ok(Some((
                 pending.pending_private_tree.clone(),
                 pending.pending_commit_secret.clone(),
@@ -1690,6 +2155,18 @@ where
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                group_id = ?mls_rs_core::debug::pretty_group_id(&provisional_state.group_context.group_id),
+                epoch = provisional_state.group_context.epoch,
+                member_count = provisional_state.public_tree.occupied_leaf_count(),
+            )
+        )
+    )]
     async fn update_key_schedule(
         &mut self,
         secrets: Option<(TreeKemPrivate, PathSecret)>,
@@ -1697,24 +2174,34 @@ where
         confirmation_tag: &ConfirmationTag,
         provisional_state: ProvisionalState,
     ) -> Result<(), MlsError> {
-        let commit_secret = if let Some(secrets) = secrets {
-            self.private_tree = secrets.0;
-            secrets.1
-        } else {
-            PathSecret::empty(&self.cipher_suite_provider)
+        // Nothing in `self` may be modified until the new epoch is fully computed, so that a
+        // failure below leaves the group in its current epoch.
+        let new_signer = match &secrets {
+            Some(_) => self.provisional_private_tree(&provisional_state)?.1,
+            None => None,
+        };
+
+        let (new_private_tree, commit_secret) = match secrets {
+            Some((private_tree, commit_secret)) => (Some(private_tree), commit_secret),
+            None => (None, PathSecret::empty(&self.cipher_suite_provider)),
         };
 
         // Use the commit_secret, the psk_secret, the provisional GroupContext, and the init secret
         // from the previous epoch (or from the external init) to compute the epoch secret and
         // derived secrets for the new epoch
 
+        // The external joiner already derived its key schedule from the external init
+        let external_sender = provisional_state
+            .external_init_index
+            .filter(|index| *index != self.private_tree.self_index);
+
         let key_schedule = match provisional_state
             .applied_proposals
             .external_initializations
             .first()
             .cloned()
         {
-            Some(ext_init) if self.pending_commit.is_none() => {
+            Some(ext_init) if external_sender.is_some() => {
                 self.key_schedule
                     .derive_for_external(&ext_init.proposal.kem_output, &self.cipher_suite_provider)
                     .await?
@@ -1774,10 +2261,30 @@ where
         #[cfg(feature = "prior_epoch")]
         self.state_repo.insert(past_epoch).await?;
 
+        if let Some(private_tree) = new_private_tree {
+            self.private_tree = private_tree;
+        }
+
+        if let Some(signer) = new_signer {
+            self.signer = signer;
+        }
+
         self.epoch_secrets = key_schedule_result.epoch_secrets;
         self.state.context = provisional_state.group_context;
         self.state.interim_transcript_hash = interim_transcript_hash;
         self.key_schedule = key_schedule_result.key_schedule;
+        // Members that sent an update may have changed their signing identity
+        #[cfg(all(feature = "std", feature = "by_ref_proposal"))]
+        self.identity_cache.invalidate(
+            provisional_state
+                .applied_proposals
+                .update_senders
+                .iter()
+                .filter_map(|index| self.state.public_tree.get_leaf_node(*index).ok())
+                .map(|leaf| &leaf.signing_identity),
+        );
+
+        self.update_leaf_rotation_epochs(&provisional_state.public_tree);
         self.state.public_tree = provisional_state.public_tree;
         self.state.confirmation_tag = new_confirmation_tag;
 
@@ -1800,6 +2307,12 @@ where
         self.config.mls_rules()
     }
 
+    #[cfg(feature = "std")]
+    fn identity_provider(&self) -> Self::IdentityProvider {
+        CachedIdentityProvider::new(self.config.identity_provider(), self.identity_cache.clone())
+    }
+
+    #[cfg(not(feature = "std"))]
     fn identity_provider(&self) -> Self::IdentityProvider {
         self.config.identity_provider()
     }
@@ -1825,6 +2338,10 @@ where
             && self.pending_commit.is_none())
     }
 
+    fn mark_self_removed(&mut self) {
+        self.removed = true;
+    }
+
     #[cfg(feature = "private_message")]
     fn min_epoch_available(&self) -> Option<u64> {
         None
@@ -2066,6 +2583,22 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn update_proposal_without_key_change_is_rejected() {
+        let mut test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        // Simulate a key generator returning the current key
+        test_group.group.commit_modifiers.modify_update_leaf = |new_leaf, current_leaf| {
+            new_leaf.public_key = current_leaf.public_key.clone();
+        };
+
+        let res = test_group.group.propose_update(vec![]).await;
+
+        assert_matches!(res, Err(MlsError::NoKeyChangeInUpdate));
+        assert!(test_group.group.pending_updates.is_empty());
+    }
+
     #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn update_proposal_with_bad_key_package_is_ignored_when_committing() {
@@ -2215,2022 +2748,4120 @@ mod tests {
         assert_matches!(proposal, Proposal::GroupContextExtensions(ext) if ext == extension_list);
     }
 
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn group_context_extension_proposal_test(
-        ext_list: ExtensionList,
-    ) -> (TestGroup, Result<MlsMessage, MlsError>) {
-        let protocol_version = TEST_PROTOCOL_VERSION;
-        let cipher_suite = TEST_CIPHER_SUITE;
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sender_generations_track_decrypted_messages() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
 
-        let mut test_group =
-            test_group_custom(protocol_version, cipher_suite, vec![42.into()], None, None).await;
+        for (sender, count) in [(0, 3), (2, 2)] {
+            for _ in 0..count {
+                let message = groups[sender]
+                    .group
+                    .encrypt_application_message(b"test", vec![])
+                    .await
+                    .unwrap();
 
-        let commit = test_group
-            .group
-            .commit_builder()
-            .set_group_context_ext(ext_list)
-            .unwrap()
-            .build()
-            .await
-            .map(|commit_output| commit_output.commit_message);
-
-        (test_group, commit)
-    }
-
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_group_context_ext_proposal_commit() {
-        let mut extension_list = ExtensionList::new();
-
-        extension_list
-            .set_from(RequiredCapabilitiesExt {
-                extensions: vec![42.into()],
-                proposals: vec![],
-                credentials: vec![],
-            })
-            .unwrap();
-
-        let (mut test_group, _) =
-            group_context_extension_proposal_test(extension_list.clone()).await;
-
-        #[cfg(feature = "state_update")]
-        {
-            let update = test_group.group.apply_pending_commit().await.unwrap();
-            assert!(update.state_update.active);
+                groups[1].process_message(message).await.unwrap();
+            }
         }
 
-        #[cfg(not(feature = "state_update"))]
-        test_group.group.apply_pending_commit().await.unwrap();
+        let generations = groups[1].group.sender_generations();
 
-        assert_eq!(test_group.group.state.context.extensions, extension_list)
+        assert_eq!(generations, [(0, 2), (2, 1)].into_iter().collect());
     }
 
+    #[cfg(all(feature = "psk", feature = "prior_epoch"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_group_context_ext_proposal_invalid() {
-        let mut extension_list = ExtensionList::new();
-        extension_list
-            .set_from(RequiredCapabilitiesExt {
-                extensions: vec![999.into()],
-                proposals: vec![],
-                credentials: vec![],
-            })
-            .unwrap();
-
-        let (_, commit) = group_context_extension_proposal_test(extension_list.clone()).await;
+    async fn resumption_psk_id_resolves_to_epoch_secret() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let psk_epoch = alice.group.current_epoch();
+        let resumption_secret = alice.group.epoch_secrets.resumption_secret.clone();
 
-        assert_matches!(
-            commit,
-            Err(MlsError::RequiredExtensionNotFound(a)) if a == 999.into()
-        );
-    }
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
 
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn make_group_with_required_capabilities(
-        required_caps: RequiredCapabilitiesExt,
-    ) -> Result<Group<TestClientConfig>, MlsError> {
-        test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice")
-            .await
-            .0
-            .create_group(core::iter::once(required_caps.into_extension().unwrap()).collect())
+        let psk_id = alice
+            .group
+            .resumption_psk_id(ResumptionPSKUsage::Branch, psk_epoch)
             .await
-    }
+            .unwrap();
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn creating_group_with_member_not_supporting_required_credential_type_fails() {
-        let group_creation = make_group_with_required_capabilities(RequiredCapabilitiesExt {
-            credentials: vec![CredentialType::BASIC, CredentialType::X509],
-            ..Default::default()
-        })
+        let resolved = PskResolver {
+            group_context: Some(alice.group.context()),
+            current_epoch: Some(&alice.group.epoch_secrets),
+            prior_epochs: Some(&alice.group.state_repo),
+            psk_store: &alice.group.config.secret_store(),
+        }
+        .resolve_to_secret(
+            core::slice::from_ref(&psk_id),
+            alice.group.cipher_suite_provider(),
+        )
         .await
-        .map(|_| ());
+        .unwrap();
 
-        assert_matches!(
-            group_creation,
-            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
-        );
-    }
+        let expected = PskSecret::calculate(
+            &[PskSecretInput {
+                id: psk_id,
+                psk: resumption_secret,
+            }],
+            alice.group.cipher_suite_provider(),
+        )
+        .await
+        .unwrap();
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn creating_group_with_member_not_supporting_required_extension_type_fails() {
-        const EXTENSION_TYPE: ExtensionType = ExtensionType::new(33);
+        assert_eq!(resolved, expected);
 
-        let group_creation = make_group_with_required_capabilities(RequiredCapabilitiesExt {
-            extensions: vec![EXTENSION_TYPE],
-            ..Default::default()
-        })
-        .await
-        .map(|_| ());
+        let res = alice
+            .group
+            .resumption_psk_id(ResumptionPSKUsage::Branch, psk_epoch + 5)
+            .await;
 
         assert_matches!(
-            group_creation,
-            Err(MlsError::RequiredExtensionNotFound(EXTENSION_TYPE))
+            res,
+            Err(MlsError::ResumptionPsksNotFound(missing))
+                if missing.len() == 1 && missing[0].psk_epoch == psk_epoch + 5
         );
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn creating_group_with_member_not_supporting_required_proposal_type_fails() {
-        const PROPOSAL_TYPE: ProposalType = ProposalType::new(33);
-
-        let group_creation = make_group_with_required_capabilities(RequiredCapabilitiesExt {
-            proposals: vec![PROPOSAL_TYPE],
-            ..Default::default()
-        })
-        .await
-        .map(|_| ());
+    async fn parent_hashes_can_be_validated_for_the_committer() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
 
-        assert_matches!(
-            group_creation,
-            Err(MlsError::RequiredProposalNotFound(PROPOSAL_TYPE))
-        );
-    }
+        let commit = alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
 
-    #[cfg(feature = "by_ref_proposal")]
-    #[cfg(not(target_arch = "wasm32"))]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn creating_group_with_member_not_supporting_external_sender_credential_fails() {
-        let ext_senders = make_x509_external_senders_ext()
+        bob.group
+            .validate_parent_hashes_for_member(0)
             .await
-            .into_extension()
             .unwrap();
 
-        let group_creation =
-            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice")
-                .await
-                .0
-                .create_group(core::iter::once(ext_senders).collect())
-                .await
-                .map(|_| ());
-
-        assert_matches!(
-            group_creation,
-            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
-        );
+        // Bob did not commit yet so his leaf is not part of a parent hash chain
+        let res = bob.group.validate_parent_hashes_for_member(1).await;
+        assert_matches!(res, Err(MlsError::InvalidLeafNodeSource));
     }
 
-    #[cfg(all(not(target_arch = "wasm32"), feature = "private_message"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_group_encrypt_plaintext_padding() {
-        let protocol_version = TEST_PROTOCOL_VERSION;
-        // This test requires a cipher suite whose signatures are not variable in length.
-        let cipher_suite = CipherSuite::CURVE25519_AES128;
+    async fn exported_secrets_match_across_members() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob, _) = alice.join("bob").await;
 
-        let mut test_group = test_group_custom_config(protocol_version, cipher_suite, |b| {
-            b.mls_rules(
-                DefaultMlsRules::default()
-                    .with_encryption_options(EncryptionOptions::new(true, PaddingMode::None)),
-            )
-        })
-        .await;
+        let alice_secret = alice
+            .group
+            .export_secret(b"side channel", b"context", 32)
+            .await
+            .unwrap();
 
-        let without_padding = test_group
+        let bob_secret = bob
             .group
-            .encrypt_application_message(&random_bytes(150), vec![])
+            .export_secret(b"side channel", b"context", 32)
             .await
             .unwrap();
 
-        let mut test_group =
-            test_group_custom_config(protocol_version, cipher_suite, |b| {
-                b.mls_rules(DefaultMlsRules::default().with_encryption_options(
-                    EncryptionOptions::new(true, PaddingMode::StepFunction),
-                ))
-            })
-            .await;
+        assert_eq!(alice_secret.len(), 32);
+        assert_eq!(alice_secret, bob_secret);
 
-        let with_padding = test_group
+        let other_label = bob
             .group
-            .encrypt_application_message(&random_bytes(150), vec![])
+            .export_secret(b"other channel", b"context", 32)
             .await
             .unwrap();
 
-        assert!(with_padding.mls_encoded_len() > without_padding.mls_encoded_len());
+        assert_ne!(alice_secret, other_label);
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn external_commit_requires_external_pub_extension() {
-        let protocol_version = TEST_PROTOCOL_VERSION;
-        let cipher_suite = TEST_CIPHER_SUITE;
-        let group = test_group(protocol_version, cipher_suite).await;
+    async fn signature_keys_skip_blank_leaves() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
 
-        let info = group
+        groups[0]
             .group
-            .group_info_message(false)
-            .await
+            .commit_builder()
+            .remove_member(1)
             .unwrap()
-            .into_group_info()
+            .build()
+            .await
             .unwrap();
 
-        let info_msg = MlsMessage::new(protocol_version, MlsMessagePayload::GroupInfo(info));
-
-        let signing_identity = group
-            .group
-            .current_member_signing_identity()
-            .unwrap()
-            .clone();
+        groups[0].process_pending_commit().await.unwrap();
 
-        let res = external_commit::ExternalCommitBuilder::new(
-            group.group.signer,
-            signing_identity,
-            group.group.config,
-        )
-        .build(info_msg)
-        .await
-        .map(|_| {});
+        let expected = [0, 2]
+            .into_iter()
+            .map(|i| {
+                groups[i]
+                    .group
+                    .current_member_signing_identity()
+                    .unwrap()
+                    .signature_key
+                    .clone()
+            })
+            .collect::<Vec<_>>();
 
-        assert_matches!(res, Err(MlsError::MissingExternalPubExtension));
+        assert_eq!(groups[0].group.signature_keys(), expected);
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn external_commit_via_commit_options_round_trip() {
-        let mut group = test_group_custom(
+    async fn removing_member_makes_extension_common() {
+        let mut alice = test_group_custom(
             TEST_PROTOCOL_VERSION,
             TEST_CIPHER_SUITE,
-            vec![],
+            vec![42.into()],
+            None,
             None,
-            CommitOptions::default()
-                .with_allow_external_commit(true)
-                .into(),
         )
         .await;
 
-        let commit_output = group.group.commit(vec![]).await.unwrap();
+        // Bob does not support extension 42
+        alice.join("bob").await;
+        assert!(!alice
+            .group
+            .common_capabilities()
+            .extensions
+            .contains(&42.into()));
 
-        let (test_client, _) =
-            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+        let previous = alice.group.clone();
 
-        test_client
-            .external_commit_builder()
+        alice
+            .group
+            .commit_builder()
+            .remove_member(1)
             .unwrap()
-            .build(commit_output.external_commit_group_info.unwrap())
+            .build()
             .await
             .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let diff = alice.group.capability_changes_since(&previous);
+
+        assert_eq!(diff.gained.extensions, vec![42.into()]);
+        assert!(diff.lost.extensions.is_empty());
     }
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_path_update_preference() {
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_context_extension_proposal_test(
+        ext_list: ExtensionList,
+    ) -> (TestGroup, Result<MlsMessage, MlsError>) {
         let protocol_version = TEST_PROTOCOL_VERSION;
         let cipher_suite = TEST_CIPHER_SUITE;
 
-        let mut test_group = test_group_custom(
-            protocol_version,
-            cipher_suite,
-            Default::default(),
-            None,
-            Some(CommitOptions::new()),
-        )
-        .await;
-
-        let test_key_package =
-            test_key_package_message(protocol_version, cipher_suite, "alice").await;
+        let mut test_group =
+            test_group_custom(protocol_version, cipher_suite, vec![42.into()], None, None).await;
 
-        test_group
+        let commit = test_group
             .group
             .commit_builder()
-            .add_member(test_key_package.clone())
+            .set_group_context_ext(ext_list)
             .unwrap()
             .build()
             .await
-            .unwrap();
-
-        assert!(test_group
-            .group
-            .pending_commit
-            .unwrap()
-            .pending_commit_secret
-            .iter()
-            .all(|x| x == &0));
+            .map(|commit_output| commit_output.commit_message);
 
-        let mut test_group = test_group_custom(
-            protocol_version,
-            cipher_suite,
-            Default::default(),
-            None,
-            Some(CommitOptions::new().with_path_required(true)),
-        )
-        .await;
+        (test_group, commit)
+    }
 
-        test_group
-            .group
-            .commit_builder()
-            .add_member(test_key_package)
-            .unwrap()
-            .build()
-            .await
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_group_context_ext_proposal_commit() {
+        let mut extension_list = ExtensionList::new();
+
+        extension_list
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![42.into()],
+                proposals: vec![],
+                credentials: vec![],
+            })
             .unwrap();
 
-        assert!(!test_group
-            .group
-            .pending_commit
-            .unwrap()
-            .pending_commit_secret
-            .iter()
-            .all(|x| x == &0));
+        let (mut test_group, _) =
+            group_context_extension_proposal_test(extension_list.clone()).await;
+
+        #[cfg(feature = "state_update")]
+        {
+            let update = test_group.group.apply_pending_commit().await.unwrap();
+            assert!(update.state_update.active);
+        }
+
+        #[cfg(not(feature = "state_update"))]
+        test_group.group.apply_pending_commit().await.unwrap();
+
+        assert_eq!(test_group.group.state.context.extensions, extension_list)
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_path_update_preference_override() {
-        let protocol_version = TEST_PROTOCOL_VERSION;
-        let cipher_suite = TEST_CIPHER_SUITE;
+    async fn test_group_context_ext_proposal_invalid() {
+        let mut extension_list = ExtensionList::new();
+        extension_list
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![999.into()],
+                proposals: vec![],
+                credentials: vec![],
+            })
+            .unwrap();
 
-        let mut test_group = test_group_custom(
-            protocol_version,
-            cipher_suite,
-            Default::default(),
+        let (_, commit) = group_context_extension_proposal_test(extension_list.clone()).await;
+
+        assert_matches!(commit, Err(MlsError::ExistingMemberViolatesCapabilities(0)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn required_capabilities_unsupported_by_existing_member_are_rejected() {
+        let mut alice = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![42.into()],
+            None,
             None,
-            Some(CommitOptions::new()),
         )
         .await;
 
-        test_group.group.commit(vec![]).await.unwrap();
+        // Bob does not support extension 42
+        alice.join("bob").await;
 
-        assert!(!test_group
+        let mut extension_list = ExtensionList::new();
+
+        extension_list
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![42.into()],
+                proposals: vec![],
+                credentials: vec![],
+            })
+            .unwrap();
+
+        let res = alice
             .group
-            .pending_commit
+            .commit_builder()
+            .set_group_context_ext(extension_list)
             .unwrap()
-            .pending_commit_secret
-            .iter()
-            .all(|x| x == &0));
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::ExistingMemberViolatesCapabilities(1)));
     }
 
-    #[cfg(feature = "private_message")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn group_rejects_unencrypted_application_message() {
-        let protocol_version = TEST_PROTOCOL_VERSION;
-        let cipher_suite = TEST_CIPHER_SUITE;
+    async fn required_capabilities_are_checked_against_remaining_members() {
+        let mut alice = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![42.into()],
+            None,
+            None,
+        )
+        .await;
 
-        let mut alice = test_group(protocol_version, cipher_suite).await;
-        let (mut bob, _) = alice.join("bob").await;
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", true, |c| {
+                c.0.settings.extension_types.push(42.into())
+            })
+            .await
+            .unwrap();
 
-        let message = alice
-            .make_plaintext(Content::Application(b"hello".to_vec().into()))
-            .await;
+        // Carol does not support extension 42
+        let (_, commit) = alice.join("carol").await;
+        bob.process_message(commit).await.unwrap();
 
-        let res = bob.group.process_incoming_message(message).await;
+        let mut extension_list = ExtensionList::new();
 
-        assert_matches!(res, Err(MlsError::UnencryptedApplicationMessage));
-    }
+        extension_list
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![42.into()],
+                ..Default::default()
+            })
+            .unwrap();
 
-    #[cfg(feature = "state_update")]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_state_update() {
-        let protocol_version = TEST_PROTOCOL_VERSION;
-        let cipher_suite = TEST_CIPHER_SUITE;
+        // Requiring extension 42 is valid once carol is removed by the same commit. The committer
+        // always remains in the group and supports the required capabilities, so the group can
+        // keep committing.
+        let commit = alice
+            .group
+            .commit_builder()
+            .remove_member(2)
+            .unwrap()
+            .set_group_context_ext(extension_list.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
 
-        // Create a group with 10 members
-        let mut alice = test_group(protocol_version, cipher_suite).await;
-        let (mut bob, _) = alice.join("bob").await;
-        let mut leaves = vec![];
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
 
-        for i in 0..8 {
-            let (group, commit) = alice.join(&format!("charlie{i}")).await;
-            leaves.push(group.group.current_user_leaf_node().unwrap().clone());
-            bob.process_message(commit).await.unwrap();
-        }
+        assert_eq!(bob.group.context().extensions, extension_list);
+        assert_eq!(bob.group.roster().members().len(), 2);
 
-        // Create many proposals, make Alice commit them
+        let commit = bob.group.commit(vec![]).await.unwrap();
+        bob.process_pending_commit().await.unwrap();
+        alice.process_message(commit.commit_message).await.unwrap();
+    }
 
-        let update_message = bob.group.propose_update(vec![]).await.unwrap();
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_context_ext_proposal_is_applied_by_receiver() {
+        let mut alice = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![42.into()],
+            None,
+            None,
+        )
+        .await;
 
-        alice.process_message(update_message).await.unwrap();
+        let (mut bob, _) = alice
+            .join_with_custom_config("bob", true, |c| {
+                c.0.settings.extension_types.push(42.into())
+            })
+            .await
+            .unwrap();
 
-        let external_psk_ids: Vec<ExternalPskId> = (0..5)
-            .map(|i| {
-                let external_id = ExternalPskId::new(vec![i]);
+        let mut extension_list = ExtensionList::new();
 
-                alice
-                    .group
-                    .config
-                    .secret_store()
-                    .insert(ExternalPskId::new(vec![i]), PreSharedKey::from(vec![i]));
+        extension_list
+            .set_from(RequiredCapabilitiesExt {
+                extensions: vec![42.into()],
+                ..Default::default()
+            })
+            .unwrap();
 
-                bob.group
-                    .config
-                    .secret_store()
-                    .insert(ExternalPskId::new(vec![i]), PreSharedKey::from(vec![i]));
+        let commit = alice
+            .group
+            .commit_builder()
+            .set_group_context_ext(extension_list.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
 
-                external_id
-            })
-            .collect();
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
 
-        let mut commit_builder = alice.group.commit_builder();
+        assert_eq!(bob.group.context().extensions, extension_list);
+        assert_eq!(bob.group.context(), alice.group.context());
+    }
 
-        for external_psk in external_psk_ids {
-            commit_builder = commit_builder.add_external_psk(external_psk).unwrap();
-        }
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn adding_key_package_with_wrong_leaf_source_fails() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
 
-        for index in [2, 5, 6] {
-            commit_builder = commit_builder.remove_member(index).unwrap();
-        }
+        for source in [
+            LeafNodeSource::Update,
+            LeafNodeSource::Commit(vec![0; 32].into()),
+        ] {
+            let mut key_package =
+                test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
 
-        for i in 0..5 {
-            let (key_package, _) = test_member(
-                protocol_version,
-                cipher_suite,
-                format!("dave{i}").as_bytes(),
-            )
-            .await;
+            key_package.leaf_node.leaf_node_source = source;
 
-            commit_builder = commit_builder
-                .add_member(key_package.key_package_message())
+            let key_package = MlsMessage::new(
+                TEST_PROTOCOL_VERSION,
+                MlsMessagePayload::KeyPackage(key_package),
+            );
+
+            let res = alice
+                .group
+                .commit_builder()
+                .add_member(key_package)
                 .unwrap()
-        }
+                .build()
+                .await
+                .map(|_| ());
 
-        let commit_output = commit_builder.build().await.unwrap();
+            assert_matches!(res, Err(MlsError::InvalidLeafNodeSource));
+        }
+    }
 
-        let commit_description = alice.process_pending_commit().await.unwrap();
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn adding_key_package_with_other_cipher_suite_reports_both_suites() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
 
-        assert!(!commit_description.is_external);
+        let other_cipher_suite = TestCryptoProvider::all_supported_cipher_suites()
+            .into_iter()
+            .find(|cs| cs != &TEST_CIPHER_SUITE)
+            .unwrap();
 
-        assert_eq!(
-            commit_description.committer,
-            alice.group.current_member_index()
-        );
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, other_cipher_suite, "bob").await;
 
-        // Check that applying pending commit and processing commit yields correct update.
-        let state_update_alice = commit_description.state_update.clone();
+        let res = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
 
-        assert_eq!(
-            state_update_alice
-                .roster_update
-                .added()
-                .iter()
-                .map(|m| m.index)
-                .collect::<Vec<_>>(),
-            vec![2, 5, 6, 10, 11]
+        assert_matches!(
+            res,
+            Err(MlsError::CipherSuiteMismatch { expected, found })
+                if expected == TEST_CIPHER_SUITE && found == other_cipher_suite
         );
+    }
 
-        assert_eq!(
-            state_update_alice.roster_update.removed(),
-            vec![2, 5, 6]
-                .into_iter()
-                .map(|i| member_from_leaf_node(&leaves[i as usize - 2], LeafIndex(i)))
-                .collect::<Vec<_>>()
-        );
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn make_group_with_required_capabilities(
+        required_caps: RequiredCapabilitiesExt,
+    ) -> Result<Group<TestClientConfig>, MlsError> {
+        test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice")
+            .await
+            .0
+            .create_group(core::iter::once(required_caps.into_extension().unwrap()).collect())
+            .await
+    }
 
-        assert_eq!(
-            state_update_alice
-                .roster_update
-                .updated()
-                .iter()
-                .map(|update| update.new.clone())
-                .collect_vec()
-                .as_slice(),
-            &alice.group.roster().members()[0..2]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn creating_group_with_member_not_supporting_required_credential_type_fails() {
+        let group_creation = make_group_with_required_capabilities(RequiredCapabilitiesExt {
+            credentials: vec![CredentialType::BASIC, CredentialType::X509],
+            ..Default::default()
+        })
+        .await
+        .map(|_| ());
+
+        assert_matches!(
+            group_creation,
+            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
         );
+    }
 
-        assert_eq!(
-            state_update_alice.added_psks,
-            (0..5)
-                .map(|i| ExternalPskId::new(vec![i]))
-                .collect::<Vec<_>>()
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn creating_group_with_member_not_supporting_required_extension_type_fails() {
+        const EXTENSION_TYPE: ExtensionType = ExtensionType::new(33);
+
+        let group_creation = make_group_with_required_capabilities(RequiredCapabilitiesExt {
+            extensions: vec![EXTENSION_TYPE],
+            ..Default::default()
+        })
+        .await
+        .map(|_| ());
+
+        assert_matches!(
+            group_creation,
+            Err(MlsError::RequiredExtensionNotFound(EXTENSION_TYPE))
         );
+    }
 
-        let payload = bob
-            .process_message(commit_output.commit_message)
-            .await
-            .unwrap();
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn creating_group_with_member_not_supporting_required_proposal_type_fails() {
+        const PROPOSAL_TYPE: ProposalType = ProposalType::new(33);
 
-        let ReceivedMessage::Commit(bob_commit_description) = payload else {
-            panic!("expected commit");
-        };
+        let group_creation = make_group_with_required_capabilities(RequiredCapabilitiesExt {
+            proposals: vec![PROPOSAL_TYPE],
+            ..Default::default()
+        })
+        .await
+        .map(|_| ());
 
-        assert_eq!(commit_description, bob_commit_description);
+        assert_matches!(
+            group_creation,
+            Err(MlsError::RequiredProposalNotFound(PROPOSAL_TYPE))
+        );
     }
 
-    #[cfg(feature = "state_update")]
+    #[cfg(feature = "std")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_description_external_commit() {
-        use crate::client::test_utils::TestClientBuilder;
+    async fn creating_group_with_creator_leaf_outside_lifetime_fails() {
+        let not_before = MlsTime::now().seconds_since_epoch() + 3600;
 
-        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let group_creation = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .key_package_not_before(not_before)
+            .build()
+            .create_group(Default::default())
+            .await
+            .map(|_| ());
 
-        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+        assert_matches!(group_creation, Err(MlsError::InvalidLifetime));
+    }
 
-        let bob = TestClientBuilder::new_for_test()
-            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
-            .build();
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn required_capabilities_set_at_creation_are_enforced_on_first_add() {
+        const EXTENSION_TYPE: ExtensionType = ExtensionType::new(33);
 
-        let (bob_group, commit) = bob
-            .external_commit_builder()
-            .unwrap()
-            .build(
-                alice_group
-                    .group
-                    .group_info_message_allowing_ext_commit(true)
-                    .await
-                    .unwrap(),
+        let required_caps = RequiredCapabilitiesExt {
+            extensions: vec![EXTENSION_TYPE],
+            ..Default::default()
+        };
+
+        let mut alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(EXTENSION_TYPE)
+            .build()
+            .create_group(
+                core::iter::once(required_caps.clone().into_extension().unwrap()).collect(),
             )
             .await
             .unwrap();
 
-        let event = alice_group.process_message(commit).await.unwrap();
+        let context_caps = alice
+            .context()
+            .extensions
+            .get_as::<RequiredCapabilitiesExt>()
+            .unwrap();
 
-        let ReceivedMessage::Commit(commit_description) = event else {
-            panic!("expected commit");
-        };
+        assert_eq!(context_caps, Some(required_caps));
 
-        assert!(commit_description.is_external);
-        assert_eq!(commit_description.committer, 1);
+        let bob_key_package = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .build()
+            .generate_key_package_message()
+            .await
+            .unwrap();
 
-        assert_eq!(
-            commit_description.state_update.roster_update.added(),
-            &bob_group.roster().members()[1..2]
-        );
+        let res = alice
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
 
-        itertools::assert_equal(
-            bob_group.roster().members_iter(),
-            alice_group.group.roster().members_iter(),
+        assert_matches!(
+            res,
+            Err(MlsError::RequiredExtensionNotFound(EXTENSION_TYPE))
         );
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg(not(target_arch = "wasm32"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn can_join_new_group_externally() {
-        use crate::client::test_utils::TestClientBuilder;
-
-        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
-
-        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
-
-        let bob = TestClientBuilder::new_for_test()
-            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
-            .build();
-
-        let (_, commit) = bob
-            .external_commit_builder()
-            .unwrap()
-            .with_tree_data(alice_group.group.export_tree().into_owned())
-            .build(
-                alice_group
-                    .group
-                    .group_info_message_allowing_ext_commit(false)
-                    .await
-                    .unwrap(),
-            )
+    async fn creating_group_with_member_not_supporting_external_sender_credential_fails() {
+        let ext_senders = make_x509_external_senders_ext()
             .await
+            .into_extension()
             .unwrap();
 
-        alice_group.process_message(commit).await.unwrap();
+        let group_creation =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice")
+                .await
+                .0
+                .create_group(core::iter::once(ext_senders).collect())
+                .await
+                .map(|_| ());
+
+        assert_matches!(
+            group_creation,
+            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
+        );
     }
 
+    #[cfg(all(not(target_arch = "wasm32"), feature = "private_message"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_membership_tag_from_non_member() {
-        let (mut alice_group, mut bob_group) =
-            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
-
-        let mut commit_output = alice_group.group.commit(vec![]).await.unwrap();
+    async fn test_group_encrypt_plaintext_padding() {
+        let protocol_version = TEST_PROTOCOL_VERSION;
+        // This test requires a cipher suite whose signatures are not variable in length.
+        let cipher_suite = CipherSuite::CURVE25519_AES128;
 
-        let plaintext = match commit_output.commit_message.payload {
-            MlsMessagePayload::Plain(ref mut plain) => plain,
-            _ => panic!("Non plaintext message"),
-        };
+        let mut test_group = test_group_custom_config(protocol_version, cipher_suite, |b| {
+            b.mls_rules(
+                DefaultMlsRules::default()
+                    .with_encryption_options(EncryptionOptions::new(true, PaddingMode::None)),
+            )
+        })
+        .await;
 
-        plaintext.content.sender = Sender::NewMemberCommit;
+        let without_padding = test_group
+            .group
+            .encrypt_application_message(&random_bytes(150), vec![])
+            .await
+            .unwrap();
 
-        let res = bob_group
-            .process_message(commit_output.commit_message)
+        let mut test_group =
+            test_group_custom_config(protocol_version, cipher_suite, |b| {
+                b.mls_rules(DefaultMlsRules::default().with_encryption_options(
+                    EncryptionOptions::new(true, PaddingMode::StepFunction),
+                ))
+            })
             .await;
 
-        assert_matches!(res, Err(MlsError::MembershipTagForNonMember));
+        let with_padding = test_group
+            .group
+            .encrypt_application_message(&random_bytes(150), vec![])
+            .await
+            .unwrap();
+
+        assert!(with_padding.mls_encoded_len() > without_padding.mls_encoded_len());
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn test_partial_commits() {
+    async fn external_commit_requires_external_pub_extension() {
         let protocol_version = TEST_PROTOCOL_VERSION;
         let cipher_suite = TEST_CIPHER_SUITE;
+        let group = test_group(protocol_version, cipher_suite).await;
 
-        let mut alice = test_group(protocol_version, cipher_suite).await;
-        let (mut bob, _) = alice.join("bob").await;
-        let (mut charlie, commit) = alice.join("charlie").await;
-        bob.process_message(commit).await.unwrap();
-
-        let (_, commit) = charlie.join("dave").await;
-
-        alice.process_message(commit.clone()).await.unwrap();
-        bob.process_message(commit.clone()).await.unwrap();
-
-        let Content::Commit(commit) = commit.into_plaintext().unwrap().content.content else {
-            panic!("Expected commit")
-        };
+        let info = group
+            .group
+            .group_info_message(false)
+            .await
+            .unwrap()
+            .into_group_info()
+            .unwrap();
 
-        assert!(commit.path.is_none());
-    }
+        let info_msg = MlsMessage::new(protocol_version, MlsMessagePayload::GroupInfo(info));
 
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn group_with_path_required() -> TestGroup {
-        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let signing_identity = group
+            .group
+            .current_member_signing_identity()
+            .unwrap()
+            .clone();
 
-        alice.group.config.0.mls_rules.commit_options.path_required = true;
+        let res = external_commit::ExternalCommitBuilder::new(
+            group.group.signer,
+            signing_identity,
+            group.group.config,
+            #[cfg(feature = "std")]
+            Default::default(),
+        )
+        .build(info_msg)
+        .await
+        .map(|_| {});
 
-        alice
+        assert_matches!(res, Err(MlsError::MissingExternalPubExtension));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn old_hpke_secrets_are_removed() {
-        let mut alice = group_with_path_required().await;
-        alice.join("bob").await;
-        alice.join("charlie").await;
+    async fn external_commit_via_commit_options_round_trip() {
+        let mut group = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![],
+            None,
+            CommitOptions::default()
+                .with_allow_external_commit(true)
+                .into(),
+        )
+        .await;
 
-        alice
+        let commit_output = group.group.commit(vec![]).await.unwrap();
+
+        let (test_client, _) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        test_client
+            .external_commit_builder()
+            .unwrap()
+            .build(commit_output.external_commit_group_info.unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_path_update_preference() {
+        let protocol_version = TEST_PROTOCOL_VERSION;
+        let cipher_suite = TEST_CIPHER_SUITE;
+
+        let mut test_group = test_group_custom(
+            protocol_version,
+            cipher_suite,
+            Default::default(),
+            None,
+            Some(CommitOptions::new()),
+        )
+        .await;
+
+        let test_key_package =
+            test_key_package_message(protocol_version, cipher_suite, "alice").await;
+
+        test_group
             .group
             .commit_builder()
-            .remove_member(1)
+            .add_member(test_key_package.clone())
             .unwrap()
             .build()
             .await
             .unwrap();
 
-        assert!(alice.group.private_tree.secret_keys[1].is_some());
-        alice.process_pending_commit().await.unwrap();
-        assert!(alice.group.private_tree.secret_keys[1].is_none());
-    }
+        assert!(test_group
+            .group
+            .pending_commit
+            .unwrap()
+            .pending_commit_secret
+            .iter()
+            .all(|x| x == &0));
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn old_hpke_secrets_of_removed_are_removed() {
-        let mut alice = group_with_path_required().await;
-        alice.join("bob").await;
-        let (mut charlie, _) = alice.join("charlie").await;
+        let mut test_group = test_group_custom(
+            protocol_version,
+            cipher_suite,
+            Default::default(),
+            None,
+            Some(CommitOptions::new().with_path_required(true)),
+        )
+        .await;
 
-        let commit = charlie
+        test_group
             .group
             .commit_builder()
-            .remove_member(1)
+            .add_member(test_key_package)
             .unwrap()
             .build()
             .await
             .unwrap();
 
-        assert!(alice.group.private_tree.secret_keys[1].is_some());
-        alice.process_message(commit.commit_message).await.unwrap();
-        assert!(alice.group.private_tree.secret_keys[1].is_none());
+        assert!(!test_group
+            .group
+            .pending_commit
+            .unwrap()
+            .pending_commit_secret
+            .iter()
+            .all(|x| x == &0));
     }
 
-    #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn old_hpke_secrets_of_updated_are_removed() {
-        let mut alice = group_with_path_required().await;
-        let (mut bob, _) = alice.join("bob").await;
-        let (mut charlie, commit) = alice.join("charlie").await;
-        bob.process_message(commit).await.unwrap();
+    async fn test_path_update_preference_override() {
+        let protocol_version = TEST_PROTOCOL_VERSION;
+        let cipher_suite = TEST_CIPHER_SUITE;
 
-        let update = bob.group.propose_update(vec![]).await.unwrap();
-        charlie.process_message(update.clone()).await.unwrap();
-        alice.process_message(update).await.unwrap();
+        let mut test_group = test_group_custom(
+            protocol_version,
+            cipher_suite,
+            Default::default(),
+            None,
+            Some(CommitOptions::new()),
+        )
+        .await;
 
-        let commit = charlie.group.commit(vec![]).await.unwrap();
+        test_group.group.commit(vec![]).await.unwrap();
 
-        assert!(alice.group.private_tree.secret_keys[1].is_some());
-        alice.process_message(commit.commit_message).await.unwrap();
-        assert!(alice.group.private_tree.secret_keys[1].is_none());
+        assert!(!test_group
+            .group
+            .pending_commit
+            .unwrap()
+            .pending_commit_secret
+            .iter()
+            .all(|x| x == &0));
     }
 
-    #[cfg(feature = "psk")]
+    #[cfg(feature = "private_message")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn only_selected_members_of_the_original_group_can_join_subgroup() {
-        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
-        let (mut bob, _) = alice.join("bob").await;
-        let (carol, commit) = alice.join("carol").await;
+    async fn group_rejects_unencrypted_application_message() {
+        let protocol_version = TEST_PROTOCOL_VERSION;
+        let cipher_suite = TEST_CIPHER_SUITE;
 
-        // Apply the commit that adds carol
-        bob.group.process_incoming_message(commit).await.unwrap();
+        let mut alice = test_group(protocol_version, cipher_suite).await;
+        let (mut bob, _) = alice.join("bob").await;
 
-        let bob_identity = bob.group.current_member_signing_identity().unwrap().clone();
-        let signer = bob.group.signer.clone();
+        let message = alice
+            .make_plaintext(Content::Application(b"hello".to_vec().into()))
+            .await;
 
-        let new_key_pkg = Client::new(
-            bob.group.config.clone(),
-            Some(signer),
-            Some((bob_identity, TEST_CIPHER_SUITE)),
-            TEST_PROTOCOL_VERSION,
-        )
-        .generate_key_package_message()
-        .await
-        .unwrap();
+        let res = bob.group.process_incoming_message(message).await;
 
-        let (mut alice_sub_group, welcome) = alice
-            .group
-            .branch(b"subgroup".to_vec(), vec![new_key_pkg])
-            .await
-            .unwrap();
+        assert_matches!(res, Err(MlsError::UnencryptedApplicationMessage));
+    }
 
-        let welcome = &welcome[0];
+    #[cfg(feature = "state_update")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_state_update() {
+        let protocol_version = TEST_PROTOCOL_VERSION;
+        let cipher_suite = TEST_CIPHER_SUITE;
 
-        let (mut bob_sub_group, _) = bob.group.join_subgroup(welcome, None).await.unwrap();
+        // Create a group with 10 members
+        let mut alice = test_group(protocol_version, cipher_suite).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let mut leaves = vec![];
 
-        // Carol can't join
-        let res = carol.group.join_subgroup(welcome, None).await.map(|_| ());
-        assert_matches!(res, Err(_));
+        for i in 0..8 {
+            let (group, commit) = alice.join(&format!("charlie{i}")).await;
+            leaves.push(group.group.current_user_leaf_node().unwrap().clone());
+            bob.process_message(commit).await.unwrap();
+        }
 
-        // Alice and Bob can still talk
-        let commit_output = alice_sub_group.commit(vec![]).await.unwrap();
+        // Create many proposals, make Alice commit them
 
-        bob_sub_group
-            .process_incoming_message(commit_output.commit_message)
-            .await
-            .unwrap();
-    }
+        let update_message = bob.group.propose_update(vec![]).await.unwrap();
 
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn joining_group_fails_if_unsupported<F>(
-        f: F,
-    ) -> Result<(TestGroup, MlsMessage), MlsError>
-    where
-        F: FnMut(&mut TestClientConfig),
-    {
-        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
-        alice_group.join_with_custom_config("alice", false, f).await
-    }
+        alice.process_message(update_message).await.unwrap();
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn joining_group_fails_if_protocol_version_is_not_supported() {
-        let res = joining_group_fails_if_unsupported(|config| {
-            config.0.settings.protocol_versions.clear();
-        })
-        .await
-        .map(|_| ());
+        let external_psk_ids: Vec<ExternalPskId> = (0..5)
+            .map(|i| {
+                let external_id = ExternalPskId::new(vec![i]);
 
-        assert_matches!(
-            res,
-            Err(MlsError::UnsupportedProtocolVersion(v)) if v ==
-                TEST_PROTOCOL_VERSION
-        );
-    }
+                alice
+                    .group
+                    .config
+                    .secret_store()
+                    .insert(ExternalPskId::new(vec![i]), PreSharedKey::from(vec![i]));
 
-    // WebCrypto does not support disabling ciphersuites
-    #[cfg(not(target_arch = "wasm32"))]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn joining_group_fails_if_cipher_suite_is_not_supported() {
-        let res = joining_group_fails_if_unsupported(|config| {
-            config
-                .0
-                .crypto_provider
-                .enabled_cipher_suites
-                .retain(|&x| x != TEST_CIPHER_SUITE);
-        })
-        .await
-        .map(|_| ());
+                bob.group
+                    .config
+                    .secret_store()
+                    .insert(ExternalPskId::new(vec![i]), PreSharedKey::from(vec![i]));
 
-        assert_matches!(
-            res,
-            Err(MlsError::UnsupportedCipherSuite(TEST_CIPHER_SUITE))
-        );
-    }
+                external_id
+            })
+            .collect();
 
-    #[cfg(feature = "private_message")]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn member_can_see_sender_creds() {
-        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
-        let (mut bob_group, _) = alice_group.join("bob").await;
+        let mut commit_builder = alice.group.commit_builder();
 
-        let bob_msg = b"I'm Bob";
+        for external_psk in external_psk_ids {
+            commit_builder = commit_builder.add_external_psk(external_psk).unwrap();
+        }
 
-        let msg = bob_group
-            .group
-            .encrypt_application_message(bob_msg, vec![])
-            .await
-            .unwrap();
+        for index in [2, 5, 6] {
+            commit_builder = commit_builder.remove_member(index).unwrap();
+        }
 
-        let received_by_alice = alice_group
-            .group
-            .process_incoming_message(msg)
+        for i in 0..5 {
+            let (key_package, _) = test_member(
+                protocol_version,
+                cipher_suite,
+                format!("dave{i}").as_bytes(),
+            )
+            .await;
+
+            commit_builder = commit_builder
+                .add_member(key_package.key_package_message())
+                .unwrap()
+        }
+
+        let commit_output = commit_builder.build().await.unwrap();
+
+        let commit_description = alice.process_pending_commit().await.unwrap();
+
+        assert!(!commit_description.is_external);
+
+        assert_eq!(
+            commit_description.committer,
+            alice.group.current_member_index()
+        );
+
+        // Check that applying pending commit and processing commit yields correct update.
+        let state_update_alice = commit_description.state_update.clone();
+
+        assert_eq!(
+            state_update_alice
+                .roster_update
+                .added()
+                .iter()
+                .map(|m| m.index)
+                .collect::<Vec<_>>(),
+            vec![2, 5, 6, 10, 11]
+        );
+
+        assert_eq!(
+            state_update_alice.roster_update.removed(),
+            vec![2, 5, 6]
+                .into_iter()
+                .map(|i| member_from_leaf_node(&leaves[i as usize - 2], LeafIndex(i)))
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            state_update_alice
+                .roster_update
+                .updated()
+                .iter()
+                .map(|update| update.new.clone())
+                .collect_vec()
+                .as_slice(),
+            &alice.group.roster().members()[0..2]
+        );
+
+        assert_eq!(
+            state_update_alice.added_psks,
+            (0..5)
+                .map(|i| ExternalPskId::new(vec![i]))
+                .collect::<Vec<_>>()
+        );
+
+        let payload = bob
+            .process_message(commit_output.commit_message)
             .await
             .unwrap();
 
-        assert_matches!(
-            received_by_alice,
-            ReceivedMessage::ApplicationMessage(ApplicationMessageDescription { sender_index, .. })
-                if sender_index == bob_group.group.current_member_index()
-        );
+        let ReceivedMessage::Commit(bob_commit_description) = payload else {
+            panic!("expected commit");
+        };
+
+        assert_eq!(commit_description, bob_commit_description);
     }
 
+    #[cfg(feature = "state_update")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn members_of_a_group_have_identical_authentication_secrets() {
+    async fn commit_description_external_commit() {
+        use crate::client::test_utils::TestClientBuilder;
+
         let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
-        let (bob_group, _) = alice_group.join("bob").await;
+
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let (bob_group, commit) = bob
+            .external_commit_builder()
+            .unwrap()
+            .build(
+                alice_group
+                    .group
+                    .group_info_message_allowing_ext_commit(true)
+                    .await
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let event = alice_group.process_message(commit).await.unwrap();
+
+        let ReceivedMessage::Commit(commit_description) = event else {
+            panic!("expected commit");
+        };
+
+        assert!(commit_description.is_external);
+        assert_eq!(commit_description.committer, 1);
 
         assert_eq!(
-            alice_group.group.epoch_authenticator().unwrap(),
-            bob_group.group.epoch_authenticator().unwrap()
+            commit_description.state_update.roster_update.added(),
+            &bob_group.roster().members()[1..2]
+        );
+
+        itertools::assert_equal(
+            bob_group.roster().members_iter(),
+            alice_group.group.roster().members_iter(),
         );
     }
 
-    #[cfg(feature = "private_message")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn member_cannot_decrypt_same_message_twice() {
+    async fn can_join_new_group_externally() {
+        use crate::client::test_utils::TestClientBuilder;
+
         let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
-        let (mut bob_group, _) = alice_group.join("bob").await;
 
-        let message = alice_group
-            .group
-            .encrypt_application_message(b"foobar", Vec::new())
-            .await
-            .unwrap();
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
 
-        let received_message = bob_group
-            .group
-            .process_incoming_message(message.clone())
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
+
+        let (_, commit) = bob
+            .external_commit_builder()
+            .unwrap()
+            .with_tree_data(alice_group.group.export_tree().into_owned())
+            .build(
+                alice_group
+                    .group
+                    .group_info_message_allowing_ext_commit(false)
+                    .await
+                    .unwrap(),
+            )
             .await
             .unwrap();
 
-        assert_matches!(
-            received_message,
-            ReceivedMessage::ApplicationMessage(m) if m.data() == b"foobar"
-        );
-
-        let res = bob_group.group.process_incoming_message(message).await;
-
-        assert_matches!(res, Err(MlsError::KeyMissing(0)));
+        alice_group.process_message(commit).await.unwrap();
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn removing_requirements_allows_to_add() {
-        let mut alice_group = test_group_custom(
-            TEST_PROTOCOL_VERSION,
-            TEST_CIPHER_SUITE,
-            vec![17.into()],
-            None,
-            None,
-        )
-        .await;
+    async fn external_commit_with_treeless_group_info_requires_tree() {
+        use crate::client::test_utils::TestClientBuilder;
 
-        alice_group
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let group_info = alice_group
             .group
-            .commit_builder()
-            .set_group_context_ext(
-                vec![RequiredCapabilitiesExt {
-                    extensions: vec![17.into()],
-                    ..Default::default()
-                }
-                .into_extension()
-                .unwrap()]
-                .try_into()
-                .unwrap(),
-            )
-            .unwrap()
-            .build()
+            .group_info_message_allowing_ext_commit(false)
             .await
             .unwrap();
 
-        alice_group.process_pending_commit().await.unwrap();
+        assert!(!group_info
+            .as_group_info()
+            .unwrap()
+            .extensions()
+            .has_extension(RatchetTreeExt::extension_type()));
 
-        let test_key_package =
-            test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
 
-        let test_key_package = MlsMessage::new(
-            TEST_PROTOCOL_VERSION,
-            MlsMessagePayload::KeyPackage(test_key_package),
-        );
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
 
-        alice_group
-            .group
-            .commit_builder()
-            .add_member(test_key_package)
+        let res = bob
+            .external_commit_builder()
             .unwrap()
-            .set_group_context_ext(Default::default())
+            .build(group_info.clone())
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::RatchetTreeNotFound));
+
+        // The signature on the treeless group info verifies once the tree is supplied
+        let (bob_group, commit) = bob
+            .external_commit_builder()
             .unwrap()
-            .build()
+            .with_tree_data(alice_group.group.export_tree().into_owned())
+            .build(group_info)
             .await
             .unwrap();
 
-        let state_update = alice_group
-            .process_pending_commit()
+        alice_group.process_message(commit).await.unwrap();
+
+        assert_eq!(
+            alice_group.group.context().tree_hash,
+            bob_group.context().tree_hash
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_transcript_hash_input_matches_confirmed_transcript_hash() {
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let interim_transcript_hash = bob_group.group.state.interim_transcript_hash.clone();
+
+        let commit = alice_group
+            .group
+            .commit(vec![])
             .await
             .unwrap()
-            .state_update;
+            .commit_message;
+        let input = commit.commit_transcript_hash_input().unwrap().unwrap();
+
+        bob_group.process_message(commit).await.unwrap();
+
+        let expected = bob_group
+            .group
+            .cipher_suite_provider
+            .hash(&[interim_transcript_hash.to_vec(), input].concat())
+            .await
+            .unwrap();
 
-        #[cfg(feature = "state_update")]
         assert_eq!(
-            state_update
-                .roster_update
-                .added()
-                .iter()
-                .map(|m| m.index)
-                .collect::<Vec<_>>(),
-            vec![1]
+            bob_group.group.context().confirmed_transcript_hash.to_vec(),
+            expected
         );
 
-        #[cfg(not(feature = "state_update"))]
-        assert!(state_update == StateUpdate {});
+        // Other messages have no transcript hash input
+        let group_info = alice_group.group.group_info_message(false).await.unwrap();
 
-        assert_eq!(alice_group.group.roster().members_iter().count(), 2);
+        assert_eq!(group_info.commit_transcript_hash_input().unwrap(), None);
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_wrong_source() {
-        // RFC, 13.4.2. "The leaf_node_source field MUST be set to commit."
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+    async fn pending_commit_is_discarded_when_another_commit_is_processed() {
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
 
-        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.leaf_node_source = LeafNodeSource::Update;
-            Some(sk.clone())
-        };
+        alice_group.group.commit(vec![]).await.unwrap();
+        assert!(alice_group.group.has_pending_commit());
 
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+        // Bob's commit for the same epoch is processed first
+        let bob_commit = bob_group.group.commit(vec![]).await.unwrap().commit_message;
+        bob_group.process_pending_commit().await.unwrap();
+        alice_group.process_message(bob_commit).await.unwrap();
 
-        let res = groups[2]
-            .process_message(commit_output.commit_message)
-            .await;
-
-        assert_matches!(res, Err(MlsError::InvalidLeafNodeSource));
-    }
-
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_same_hpke_key() {
-        // RFC 13.4.2. "Verify that the encryption_key value in the LeafNode is different from the committer's current leaf node"
+        assert!(!alice_group.group.has_pending_commit());
 
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
-
-        // Group 0 starts using fixed key
-        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.public_key = get_test_25519_key(1u8);
-            Some(sk.clone())
-        };
+        let res = alice_group.process_pending_commit().await.map(|_| ());
+        assert_matches!(res, Err(MlsError::PendingCommitNotFound));
 
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
-        groups[0].process_pending_commit().await.unwrap();
-        groups[2]
-            .process_message(commit_output.commit_message)
+        // A rebuilt commit for the new epoch is accepted
+        let commit = alice_group
+            .group
+            .commit(vec![])
             .await
-            .unwrap();
-
-        // Group 0 tries to use the fixed key againd
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
-
-        let res = groups[2]
-            .process_message(commit_output.commit_message)
-            .await;
+            .unwrap()
+            .commit_message;
+        alice_group.process_pending_commit().await.unwrap();
+        bob_group.process_message(commit).await.unwrap();
 
-        assert_matches!(res, Err(MlsError::SameHpkeKey(0)));
+        assert_eq!(alice_group.group.current_epoch(), 3);
+        assert_eq!(bob_group.group.current_epoch(), 3);
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_duplicate_hpke_key() {
-        // RFC 8.3 "Verify that the following fields are unique among the members of the group: `encryption_key`"
-
-        if TEST_CIPHER_SUITE != CipherSuite::CURVE25519_AES128
-            && TEST_CIPHER_SUITE != CipherSuite::CURVE25519_CHACHA
-        {
-            return;
-        }
-
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+    async fn external_commit_is_processed_while_a_commit_is_pending() {
+        use crate::client::test_utils::TestClientBuilder;
 
-        // Group 1 uses the fixed key
-        groups[1].group.commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.public_key = get_test_25519_key(1u8);
-            Some(sk.clone())
-        };
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
 
-        let commit_output = groups
-            .get_mut(1)
-            .unwrap()
+        let group_info = alice_group
             .group
-            .commit(vec![])
+            .group_info_message_allowing_ext_commit(true)
             .await
             .unwrap();
 
-        process_commit(&mut groups, commit_output.commit_message, 1).await;
-
-        // Group 0 tries to use the fixed key too
-        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.public_key = get_test_25519_key(1u8);
-            Some(sk.clone())
-        };
-
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
-
-        let res = groups[7]
-            .process_message(commit_output.commit_message)
-            .await;
-
-        assert_matches!(res, Err(MlsError::DuplicateLeafData(_)));
-    }
-
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_duplicate_signature_key() {
-        // RFC 8.3 "Verify that the following fields are unique among the members of the group: `signature_key`"
-
-        if TEST_CIPHER_SUITE != CipherSuite::CURVE25519_AES128
-            && TEST_CIPHER_SUITE != CipherSuite::CURVE25519_CHACHA
-        {
-            return;
-        }
-
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
-
-        // Group 1 uses the fixed key
-        groups[1].group.commit_modifiers.modify_leaf = |leaf, _| {
-            let sk = hex!(
-                "3468b4c890255c983e3d5cbf5cb64c1ef7f6433a518f2f3151d6672f839a06ebcad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b"
-            )
-            .into();
+        alice_group.group.commit(vec![]).await.unwrap();
 
-            leaf.signing_identity.signature_key =
-                hex!("cad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b").into();
+        let (bob_identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
 
-            Some(sk)
-        };
+        let bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_identity, secret_key, TEST_CIPHER_SUITE)
+            .build();
 
-        let commit_output = groups
-            .get_mut(1)
+        let (bob_group, commit) = bob
+            .external_commit_builder()
             .unwrap()
-            .group
-            .commit(vec![])
+            .build(group_info)
             .await
             .unwrap();
 
-        process_commit(&mut groups, commit_output.commit_message, 1).await;
+        alice_group.process_message(commit).await.unwrap();
 
-        // Group 0 tries to use the fixed key too
-        groups[0].group.commit_modifiers.modify_leaf = |leaf, _| {
-            let sk = hex!(
-                "3468b4c890255c983e3d5cbf5cb64c1ef7f6433a518f2f3151d6672f839a06ebcad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b"
-            )
-            .into();
+        assert!(!alice_group.group.has_pending_commit());
+        assert_eq!(alice_group.group.current_epoch(), bob_group.current_epoch());
+    }
 
-            leaf.signing_identity.signature_key =
-                hex!("cad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b").into();
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn test_membership_tag_from_non_member() {
+        let (mut alice_group, mut bob_group) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
 
-            Some(sk)
+        let mut commit_output = alice_group.group.commit(vec![]).await.unwrap();
+
+        let plaintext = match commit_output.commit_message.payload {
+            MlsMessagePayload::Plain(ref mut plain) => plain,
+            _ => panic!("Non plaintext message"),
         };
 
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+        plaintext.content.sender = Sender::NewMemberCommit;
 
-        let res = groups[7]
+        let res = bob_group
             .process_message(commit_output.commit_message)
             .await;
 
-        assert_matches!(res, Err(MlsError::DuplicateLeafData(_)));
+        assert_matches!(res, Err(MlsError::MembershipTagForNonMember));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_incorrect_signature() {
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+    async fn test_partial_commits() {
+        let protocol_version = TEST_PROTOCOL_VERSION;
+        let cipher_suite = TEST_CIPHER_SUITE;
 
-        groups[0].group.commit_modifiers.modify_leaf = |leaf, _| {
-            leaf.signature[0] ^= 1;
-            None
-        };
+        let mut alice = test_group(protocol_version, cipher_suite).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut charlie, commit) = alice.join("charlie").await;
+        bob.process_message(commit).await.unwrap();
 
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+        let (_, commit) = charlie.join("dave").await;
 
-        let res = groups[2]
-            .process_message(commit_output.commit_message)
-            .await;
+        alice.process_message(commit.clone()).await.unwrap();
+        bob.process_message(commit.clone()).await.unwrap();
 
-        assert_matches!(res, Err(MlsError::InvalidSignature));
-    }
+        let Content::Commit(commit) = commit.into_plaintext().unwrap().content.content else {
+            panic!("Expected commit")
+        };
 
-    #[cfg(not(target_arch = "wasm32"))]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_not_supporting_used_context_extension() {
-        const EXT_TYPE: ExtensionType = ExtensionType::new(999);
+        assert!(commit.path.is_none());
+    }
 
-        // The new leaf of the committer doesn't support an extension set in group context
-        let extension = Extension::new(EXT_TYPE, vec![]);
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn group_with_path_required() -> TestGroup {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
 
-        let mut groups =
-            get_test_groups_with_features(3, vec![extension].into(), Default::default()).await;
+        alice.group.config.0.mls_rules.commit_options.path_required = true;
 
-        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.capabilities = get_test_capabilities();
-            Some(sk.clone())
-        };
+        alice
+    }
 
-        let commit_output = groups[0].commit(vec![]).await.unwrap();
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn old_hpke_secrets_are_removed() {
+        let mut alice = group_with_path_required().await;
+        alice.join("bob").await;
+        alice.join("charlie").await;
 
-        let res = groups[1]
-            .process_incoming_message(commit_output.commit_message)
-            .await;
+        alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
 
-        assert_matches!(res, Err(MlsError::UnsupportedGroupExtension(EXT_TYPE)));
+        assert!(alice.group.private_tree.secret_keys[1].is_some());
+        alice.process_pending_commit().await.unwrap();
+        assert!(alice.group.private_tree.secret_keys[1].is_none());
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_not_supporting_required_extension() {
-        // The new leaf of the committer doesn't support an extension required by group context
+    async fn old_hpke_secrets_of_removed_are_removed() {
+        let mut alice = group_with_path_required().await;
+        alice.join("bob").await;
+        let (mut charlie, _) = alice.join("charlie").await;
 
-        let extension = RequiredCapabilitiesExt {
-            extensions: vec![999.into()],
-            proposals: vec![],
-            credentials: vec![],
-        };
+        let commit = charlie
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
 
-        let extensions = vec![extension.into_extension().unwrap()];
-        let mut groups =
-            get_test_groups_with_features(3, extensions.into(), Default::default()).await;
+        assert!(alice.group.private_tree.secret_keys[1].is_some());
+        alice.process_message(commit.commit_message).await.unwrap();
+        assert!(alice.group.private_tree.secret_keys[1].is_none());
+    }
 
-        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.capabilities = Capabilities::default();
-            Some(sk.clone())
-        };
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn old_hpke_secrets_of_updated_are_removed() {
+        let mut alice = group_with_path_required().await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (mut charlie, commit) = alice.join("charlie").await;
+        bob.process_message(commit).await.unwrap();
 
-        let commit_output = groups[0].commit(vec![]).await.unwrap();
+        let update = bob.group.propose_update(vec![]).await.unwrap();
+        charlie.process_message(update.clone()).await.unwrap();
+        alice.process_message(update).await.unwrap();
 
-        let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
-            .await;
+        let commit = charlie.group.commit(vec![]).await.unwrap();
 
-        assert!(res.is_err());
+        assert!(alice.group.private_tree.secret_keys[1].is_some());
+        alice.process_message(commit.commit_message).await.unwrap();
+        assert!(alice.group.private_tree.secret_keys[1].is_none());
     }
 
+    #[cfg(feature = "psk")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_has_unsupported_credential() {
-        // The new leaf of the committer has a credential unsupported by another leaf
-        let mut groups =
-            get_test_groups_with_features(3, Default::default(), Default::default()).await;
+    async fn only_selected_members_of_the_original_group_can_join_subgroup() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob, _) = alice.join("bob").await;
+        let (carol, commit) = alice.join("carol").await;
 
-        for group in groups.iter_mut() {
-            group.config.0.identity_provider.allow_any_custom = true;
-        }
-
-        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.signing_identity.credential = Credential::Custom(CustomCredential::new(
-                CredentialType::new(43),
-                leaf.signing_identity
-                    .credential
-                    .as_basic()
-                    .unwrap()
-                    .identifier
-                    .to_vec(),
-            ));
-
-            Some(sk.clone())
-        };
+        // Apply the commit that adds carol
+        bob.group.process_incoming_message(commit).await.unwrap();
 
-        let commit_output = groups[0].commit(vec![]).await.unwrap();
+        let bob_identity = bob.group.current_member_signing_identity().unwrap().clone();
+        let signer = bob.group.signer.clone();
 
-        let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
-            .await;
+        let new_key_pkg = Client::new(
+            bob.group.config.clone(),
+            Some(signer),
+            Some((bob_identity, TEST_CIPHER_SUITE)),
+            TEST_PROTOCOL_VERSION,
+        )
+        .generate_key_package_message()
+        .await
+        .unwrap();
 
-        assert_matches!(res, Err(MlsError::CredentialTypeOfNewLeafIsUnsupported));
-    }
+        let (mut alice_sub_group, welcome) = alice
+            .group
+            .branch(b"subgroup".to_vec(), vec![new_key_pkg])
+            .await
+            .unwrap();
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_not_supporting_credential_used_in_another_leaf() {
-        // The new leaf of the committer doesn't support another leaf's credential
+        let welcome = &welcome[0];
 
-        let mut groups =
-            get_test_groups_with_features(3, Default::default(), Default::default()).await;
+        let (mut bob_sub_group, _) = bob.group.join_subgroup(welcome, None).await.unwrap();
 
-        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.capabilities.credentials = vec![2.into()];
-            Some(sk.clone())
-        };
+        // Carol can't join
+        let res = carol.group.join_subgroup(welcome, None).await.map(|_| ());
+        assert_matches!(res, Err(_));
 
-        let commit_output = groups[0].commit(vec![]).await.unwrap();
+        // Alice and Bob can still talk
+        let commit_output = alice_sub_group.commit(vec![]).await.unwrap();
 
-        let res = groups[2]
+        bob_sub_group
             .process_incoming_message(commit_output.commit_message)
-            .await;
+            .await
+            .unwrap();
+    }
 
-        assert_matches!(res, Err(MlsError::InUseCredentialTypeUnsupportedByNewLeaf));
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn joining_group_fails_if_unsupported<F>(
+        f: F,
+    ) -> Result<(TestGroup, MlsMessage), MlsError>
+    where
+        F: FnMut(&mut TestClientConfig),
+    {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice_group.join_with_custom_config("alice", false, f).await
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_not_supporting_required_credential() {
-        // The new leaf of the committer doesn't support a credential required by group context
+    async fn joining_group_fails_if_protocol_version_is_not_supported() {
+        let res = joining_group_fails_if_unsupported(|config| {
+            config.0.settings.protocol_versions.clear();
+        })
+        .await
+        .map(|_| ());
 
-        let extension = RequiredCapabilitiesExt {
-            extensions: vec![],
-            proposals: vec![],
-            credentials: vec![1.into()],
-        };
+        assert_matches!(
+            res,
+            Err(MlsError::UnsupportedProtocolVersion(v)) if v ==
+                TEST_PROTOCOL_VERSION
+        );
+    }
 
-        let extensions = vec![extension.into_extension().unwrap()];
-        let mut groups =
-            get_test_groups_with_features(3, extensions.into(), Default::default()).await;
+    // WebCrypto does not support disabling ciphersuites
+    #[cfg(not(target_arch = "wasm32"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_group_fails_if_cipher_suite_is_not_supported() {
+        let res = joining_group_fails_if_unsupported(|config| {
+            config
+                .0
+                .crypto_provider
+                .enabled_cipher_suites
+                .retain(|&x| x != TEST_CIPHER_SUITE);
+        })
+        .await
+        .map(|_| ());
 
-        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.capabilities.credentials = vec![2.into()];
-            Some(sk.clone())
-        };
+        assert_matches!(
+            res,
+            Err(MlsError::UnsupportedCipherSuite(TEST_CIPHER_SUITE))
+        );
+    }
 
-        let commit_output = groups[0].commit(vec![]).await.unwrap();
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn member_can_see_sender_creds() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
 
-        let res = groups[2]
-            .process_incoming_message(commit_output.commit_message)
-            .await;
+        let bob_msg = b"I'm Bob";
 
-        assert_matches!(res, Err(MlsError::RequiredCredentialNotFound(_)));
-    }
+        let msg = bob_group
+            .group
+            .encrypt_application_message(bob_msg, vec![])
+            .await
+            .unwrap();
 
-    #[cfg(feature = "by_ref_proposal")]
-    #[cfg(not(target_arch = "wasm32"))]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn make_x509_external_senders_ext() -> ExternalSendersExt {
-        let (_, ext_sender_pk) = test_cipher_suite_provider(TEST_CIPHER_SUITE)
-            .signature_key_generate()
+        let received_by_alice = alice_group
+            .group
+            .process_incoming_message(msg)
             .await
             .unwrap();
 
-        let ext_sender_id = SigningIdentity {
-            signature_key: ext_sender_pk,
-            credential: Credential::X509(CertificateChain::from(vec![random_bytes(32)])),
-        };
+        assert_matches!(
+            received_by_alice,
+            ReceivedMessage::ApplicationMessage(ApplicationMessageDescription { sender_index, .. })
+                if sender_index == bob_group.group.current_member_index()
+        );
+    }
 
-        ExternalSendersExt::new(vec![ext_sender_id])
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn members_of_a_group_have_identical_authentication_secrets() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (bob_group, _) = alice_group.join("bob").await;
+
+        assert_eq!(
+            alice_group.group.epoch_authenticator().unwrap(),
+            bob_group.group.epoch_authenticator().unwrap()
+        );
     }
 
-    #[cfg(feature = "by_ref_proposal")]
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(feature = "private_message")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_leaf_not_supporting_external_sender_credential_leads_to_rejected_commit() {
-        let ext_senders = make_x509_external_senders_ext()
+    async fn member_cannot_decrypt_same_message_twice() {
+        let mut alice_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let (mut bob_group, _) = alice_group.join("bob").await;
+
+        let message = alice_group
+            .group
+            .encrypt_application_message(b"foobar", Vec::new())
             .await
-            .into_extension()
             .unwrap();
 
-        let mut alice = ClientBuilder::new()
-            .crypto_provider(TestCryptoProvider::new())
-            .identity_provider(
-                BasicWithCustomProvider::default().with_credential_type(CredentialType::X509),
-            )
-            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
-            .await
-            .build()
-            .create_group(core::iter::once(ext_senders).collect())
+        let received_message = bob_group
+            .group
+            .process_incoming_message(message.clone())
             .await
             .unwrap();
 
-        // New leaf supports only basic credentials (used by the group) but not X509 used by external sender
-        alice.commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.capabilities.credentials = vec![CredentialType::BASIC];
-            Some(sk.clone())
-        };
-
-        alice.commit(vec![]).await.unwrap();
-        let res = alice.apply_pending_commit().await;
-
         assert_matches!(
-            res,
-            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
+            received_message,
+            ReceivedMessage::ApplicationMessage(m) if m.data() == b"foobar"
         );
+
+        let res = bob_group.group.process_incoming_message(message).await;
+
+        assert_matches!(res, Err(MlsError::KeyMissing(0)));
     }
 
-    #[cfg(feature = "by_ref_proposal")]
-    #[cfg(not(target_arch = "wasm32"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn node_not_supporting_external_sender_credential_cannot_join_group() {
-        let ext_senders = make_x509_external_senders_ext()
-            .await
-            .into_extension()
-            .unwrap();
+    async fn removing_requirements_allows_to_add() {
+        let mut alice_group = test_group_custom(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            vec![17.into()],
+            None,
+            None,
+        )
+        .await;
 
-        let mut alice = ClientBuilder::new()
-            .crypto_provider(TestCryptoProvider::new())
-            .identity_provider(
-                BasicWithCustomProvider::default().with_credential_type(CredentialType::X509),
+        alice_group
+            .group
+            .commit_builder()
+            .set_group_context_ext(
+                vec![RequiredCapabilitiesExt {
+                    extensions: vec![17.into()],
+                    ..Default::default()
+                }
+                .into_extension()
+                .unwrap()]
+                .try_into()
+                .unwrap(),
             )
-            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
-            .await
+            .unwrap()
             .build()
-            .create_group(core::iter::once(ext_senders).collect())
             .await
             .unwrap();
 
-        let (_, bob_key_pkg) =
-            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+        alice_group.process_pending_commit().await.unwrap();
 
-        let commit = alice
-            .commit_builder()
-            .add_member(bob_key_pkg)
-            .unwrap()
-            .build()
-            .await;
+        let test_key_package =
+            test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
 
-        assert_matches!(
-            commit,
-            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
+        let test_key_package = MlsMessage::new(
+            TEST_PROTOCOL_VERSION,
+            MlsMessagePayload::KeyPackage(test_key_package),
+        );
+
+        alice_group
+            .group
+            .commit_builder()
+            .add_member(test_key_package)
+            .unwrap()
+            .set_group_context_ext(Default::default())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        let state_update = alice_group
+            .process_pending_commit()
+            .await
+            .unwrap()
+            .state_update;
+
+        #[cfg(feature = "state_update")]
+        assert_eq!(
+            state_update
+                .roster_update
+                .added()
+                .iter()
+                .map(|m| m.index)
+                .collect::<Vec<_>>(),
+            vec![1]
         );
+
+        #[cfg(not(feature = "state_update"))]
+        assert!(state_update == StateUpdate {});
+
+        assert_eq!(alice_group.group.roster().members_iter().count(), 2);
     }
 
-    #[cfg(feature = "by_ref_proposal")]
-    #[cfg(not(target_arch = "wasm32"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn external_senders_extension_is_rejected_if_member_does_not_support_credential_type() {
-        let mut alice = ClientBuilder::new()
-            .crypto_provider(TestCryptoProvider::new())
-            .identity_provider(
-                BasicWithCustomProvider::default().with_credential_type(CredentialType::X509),
+    async fn commit_leaf_wrong_source() {
+        // RFC, 13.4.2. "The leaf_node_source field MUST be set to commit."
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.leaf_node_source = LeafNodeSource::Update;
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidLeafNodeSource));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_same_hpke_key() {
+        // RFC 13.4.2. "Verify that the encryption_key value in the LeafNode is different from the committer's current leaf node"
+
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        // Group 0 starts using fixed key
+        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.public_key = get_test_25519_key(1u8);
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+        groups[0].process_pending_commit().await.unwrap();
+        groups[2]
+            .process_message(commit_output.commit_message)
+            .await
+            .unwrap();
+
+        // Group 0 tries to use the fixed key againd
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::SameHpkeKey(0)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_duplicate_hpke_key() {
+        // RFC 8.3 "Verify that the following fields are unique among the members of the group: `encryption_key`"
+
+        if TEST_CIPHER_SUITE != CipherSuite::CURVE25519_AES128
+            && TEST_CIPHER_SUITE != CipherSuite::CURVE25519_CHACHA
+        {
+            return;
+        }
+
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+
+        // Group 1 uses the fixed key
+        groups[1].group.commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.public_key = get_test_25519_key(1u8);
+            Some(sk.clone())
+        };
+
+        let commit_output = groups
+            .get_mut(1)
+            .unwrap()
+            .group
+            .commit(vec![])
+            .await
+            .unwrap();
+
+        process_commit(&mut groups, commit_output.commit_message, 1).await;
+
+        // Group 0 tries to use the fixed key too
+        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.public_key = get_test_25519_key(1u8);
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[7]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::DuplicateLeafData(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_duplicate_signature_key() {
+        // RFC 8.3 "Verify that the following fields are unique among the members of the group: `signature_key`"
+
+        if TEST_CIPHER_SUITE != CipherSuite::CURVE25519_AES128
+            && TEST_CIPHER_SUITE != CipherSuite::CURVE25519_CHACHA
+        {
+            return;
+        }
+
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+
+        // Group 1 uses the fixed key
+        groups[1].group.commit_modifiers.modify_leaf = |leaf, _| {
+            let sk = hex!(
+                "3468b4c890255c983e3d5cbf5cb64c1ef7f6433a518f2f3151d6672f839a06ebcad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b"
             )
-            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .into();
+
+            leaf.signing_identity.signature_key =
+                hex!("cad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b").into();
+
+            Some(sk)
+        };
+
+        let commit_output = groups
+            .get_mut(1)
+            .unwrap()
+            .group
+            .commit(vec![])
             .await
+            .unwrap();
+
+        process_commit(&mut groups, commit_output.commit_message, 1).await;
+
+        // Group 0 tries to use the fixed key too
+        groups[0].group.commit_modifiers.modify_leaf = |leaf, _| {
+            let sk = hex!(
+                "3468b4c890255c983e3d5cbf5cb64c1ef7f6433a518f2f3151d6672f839a06ebcad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b"
+            )
+            .into();
+
+            leaf.signing_identity.signature_key =
+                hex!("cad4fc381fe61822af45135c82921a348e6f46643d66ddefc70483565433714b").into();
+
+            Some(sk)
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[7]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::DuplicateLeafData(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_context_inconsistent_with_tree_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let cs = alice.group.cipher_suite_provider().clone();
+        let mut tree = alice.group.state.public_tree.clone();
+        let context = alice.group.context().clone();
+
+        context.validate_against(&mut tree, &cs).await.unwrap();
+
+        let mut wrong_tree_hash = context.clone();
+        wrong_tree_hash.tree_hash[0] ^= 1;
+        let res = wrong_tree_hash.validate_against(&mut tree, &cs).await;
+        assert_matches!(res, Err(MlsError::InconsistentGroupContext));
+
+        let mut first_epoch = context;
+        first_epoch.epoch = 0;
+        let res = first_epoch.validate_against(&mut tree, &cs).await;
+        assert_matches!(res, Err(MlsError::InconsistentGroupContext));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_adding_same_identity_twice_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice
+            .group
+            .commit_builder()
+            .add_member(
+                test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await,
+            )
+            .unwrap()
+            .add_member(
+                test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await,
+            )
+            .unwrap()
             .build()
-            .create_group(Default::default())
+            .await;
+
+        assert_matches!(res, Err(MlsError::DuplicateLeafData(1)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_adding_existing_identity_is_rejected() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let res = alice
+            .group
+            .commit_builder()
+            .add_member(
+                test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await,
+            )
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::DuplicateLeafData(1)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_can_readd_identity_it_removes() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        alice.join("bob").await;
+
+        let new_bob =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        alice
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .add_member(new_bob.clone())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+
+        let bob = alice.group.member_at_index(1).unwrap();
+        let new_bob = new_bob.into_key_package().unwrap();
+
+        assert_eq!(alice.group.roster().members().len(), 2);
+        assert_eq!(bob.signing_identity, new_bob.leaf_node.signing_identity);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_incorrect_signature() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        groups[0].group.commit_modifiers.modify_leaf = |leaf, _| {
+            leaf.signature[0] ^= 1;
+            None
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidSignature));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_not_supporting_used_context_extension() {
+        const EXT_TYPE: ExtensionType = ExtensionType::new(999);
+
+        // The new leaf of the committer doesn't support an extension set in group context
+        let extension = Extension::new(EXT_TYPE, vec![]);
+
+        let mut groups =
+            get_test_groups_with_features(3, vec![extension].into(), Default::default()).await;
+
+        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.capabilities = get_test_capabilities();
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].commit(vec![]).await.unwrap();
+
+        let res = groups[1]
+            .process_incoming_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::UnsupportedGroupExtension(EXT_TYPE)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_not_supporting_required_extension() {
+        // The new leaf of the committer doesn't support an extension required by group context
+
+        let extension = RequiredCapabilitiesExt {
+            extensions: vec![999.into()],
+            proposals: vec![],
+            credentials: vec![],
+        };
+
+        let extensions = vec![extension.into_extension().unwrap()];
+        let mut groups =
+            get_test_groups_with_features(3, extensions.into(), Default::default()).await;
+
+        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.capabilities = Capabilities::default();
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_incoming_message(commit_output.commit_message)
+            .await;
+
+        assert!(res.is_err());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_has_unsupported_credential() {
+        // The new leaf of the committer has a credential unsupported by another leaf
+        let mut groups =
+            get_test_groups_with_features(3, Default::default(), Default::default()).await;
+
+        for group in groups.iter_mut() {
+            group.config.0.identity_provider.allow_any_custom = true;
+        }
+
+        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.signing_identity.credential = Credential::Custom(CustomCredential::new(
+                CredentialType::new(43),
+                leaf.signing_identity
+                    .credential
+                    .as_basic()
+                    .unwrap()
+                    .identifier
+                    .to_vec(),
+            ));
+
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_incoming_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::CredentialTypeOfNewLeafIsUnsupported));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_not_supporting_credential_used_in_another_leaf() {
+        // The new leaf of the committer doesn't support another leaf's credential
+
+        let mut groups =
+            get_test_groups_with_features(3, Default::default(), Default::default()).await;
+
+        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.capabilities.credentials = vec![2.into()];
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_incoming_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InUseCredentialTypeUnsupportedByNewLeaf));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_not_supporting_required_credential() {
+        // The new leaf of the committer doesn't support a credential required by group context
+
+        let extension = RequiredCapabilitiesExt {
+            extensions: vec![],
+            proposals: vec![],
+            credentials: vec![1.into()],
+        };
+
+        let extensions = vec![extension.into_extension().unwrap()];
+        let mut groups =
+            get_test_groups_with_features(3, extensions.into(), Default::default()).await;
+
+        groups[0].commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.capabilities.credentials = vec![2.into()];
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].commit(vec![]).await.unwrap();
+
+        let res = groups[2]
+            .process_incoming_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::RequiredCredentialNotFound(_)));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn make_x509_external_senders_ext() -> ExternalSendersExt {
+        let (_, ext_sender_pk) = test_cipher_suite_provider(TEST_CIPHER_SUITE)
+            .signature_key_generate()
+            .await
+            .unwrap();
+
+        let ext_sender_id = SigningIdentity {
+            signature_key: ext_sender_pk,
+            credential: Credential::X509(CertificateChain::from(vec![random_bytes(32)])),
+        };
+
+        ExternalSendersExt::new(vec![ext_sender_id])
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_leaf_not_supporting_external_sender_credential_leads_to_rejected_commit() {
+        let ext_senders = make_x509_external_senders_ext()
+            .await
+            .into_extension()
+            .unwrap();
+
+        let mut alice = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(
+                BasicWithCustomProvider::default().with_credential_type(CredentialType::X509),
+            )
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build()
+            .create_group(core::iter::once(ext_senders).collect())
+            .await
+            .unwrap();
+
+        // New leaf supports only basic credentials (used by the group) but not X509 used by external sender
+        alice.commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.capabilities.credentials = vec![CredentialType::BASIC];
+            Some(sk.clone())
+        };
+
+        alice.commit(vec![]).await.unwrap();
+        let res = alice.apply_pending_commit().await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
+        );
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn node_not_supporting_external_sender_credential_cannot_join_group() {
+        let ext_senders = make_x509_external_senders_ext()
+            .await
+            .into_extension()
+            .unwrap();
+
+        let mut alice = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(
+                BasicWithCustomProvider::default().with_credential_type(CredentialType::X509),
+            )
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build()
+            .create_group(core::iter::once(ext_senders).collect())
+            .await
+            .unwrap();
+
+        let (_, bob_key_pkg) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit = alice
+            .commit_builder()
+            .add_member(bob_key_pkg)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(
+            commit,
+            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
+        );
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_senders_extension_is_rejected_if_member_does_not_support_credential_type() {
+        let mut alice = ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(
+                BasicWithCustomProvider::default().with_credential_type(CredentialType::X509),
+            )
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .build()
+            .create_group(Default::default())
+            .await
+            .unwrap();
+
+        let (_, bob_key_pkg) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        alice
+            .commit_builder()
+            .add_member(bob_key_pkg)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+        assert_eq!(alice.roster().members_iter().count(), 2);
+
+        let ext_senders = make_x509_external_senders_ext()
+            .await
+            .into_extension()
+            .unwrap();
+
+        let res = alice
+            .commit_builder()
+            .set_group_context_ext(core::iter::once(ext_senders).collect())
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
+        );
+    }
+
+    /*
+     * Edge case paths
+     */
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn committing_degenerate_path_succeeds() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+
+        groups[0].group.commit_modifiers.modify_tree = |tree: &mut TreeKemPublic| {
+            tree.update_node(get_test_25519_key(1u8), 1).unwrap();
+            tree.update_node(get_test_25519_key(1u8), 3).unwrap();
+        };
+
+        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
+            leaf.public_key = get_test_25519_key(1u8);
+            Some(sk.clone())
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[7]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert!(res.is_ok());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn inserting_key_in_filtered_node_fails() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+
+        let commit_output = groups[0]
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        for group in groups.iter_mut().skip(2) {
+            group
+                .process_message(commit_output.commit_message.clone())
+                .await
+                .unwrap();
+        }
+
+        groups[0].group.commit_modifiers.modify_tree = |tree: &mut TreeKemPublic| {
+            tree.update_node(get_test_25519_key(1u8), 1).unwrap();
+        };
+
+        groups[0].group.commit_modifiers.modify_path = |path: Vec<UpdatePathNode>| {
+            let mut path = path;
+            let mut node = path[0].clone();
+            node.public_key = get_test_25519_key(1u8);
+            path.insert(0, node);
+            path
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[7]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        // We should get a path validation error, since the path is too long
+        assert_matches!(res, Err(MlsError::WrongPathLen));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_with_too_short_path_fails() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+
+        let commit_output = groups[0]
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        for group in groups.iter_mut().skip(2) {
+            group
+                .process_message(commit_output.commit_message.clone())
+                .await
+                .unwrap();
+        }
+
+        groups[0].group.commit_modifiers.modify_path = |path: Vec<UpdatePathNode>| {
+            let mut path = path;
+            path.pop();
+            path
+        };
+
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+
+        let res = groups[7]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert!(res.is_err());
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn update_proposal_can_change_credential() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+
+        let update = groups[0]
+            .group
+            .propose_update_with_identity(secret_key, identity.clone(), vec![])
+            .await
+            .unwrap();
+
+        groups[1].process_message(update).await.unwrap();
+        let commit_output = groups[1].group.commit(vec![]).await.unwrap();
+
+        // Check that the credential was updated by in the committer's state.
+        groups[1].process_pending_commit().await.unwrap();
+        let new_member = groups[1].group.roster().member_with_index(0).unwrap();
+
+        assert_eq!(
+            new_member.signing_identity.credential,
+            get_test_basic_credential(b"member".to_vec())
+        );
+
+        assert_eq!(
+            new_member.signing_identity.signature_key,
+            identity.signature_key
+        );
+
+        // Check that the credential was updated in the updater's state.
+        groups[0]
+            .process_message(commit_output.commit_message)
+            .await
+            .unwrap();
+        let new_member = groups[0].group.roster().member_with_index(0).unwrap();
+
+        assert_eq!(
+            new_member.signing_identity.credential,
+            get_test_basic_credential(b"member".to_vec())
+        );
+
+        assert_eq!(
+            new_member.signing_identity.signature_key,
+            identity.signature_key
+        );
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sender_identity_resolves_members() {
+        let (alice, bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let bob_identity = bob.group.current_member_signing_identity().unwrap();
+
+        assert_eq!(
+            &alice.group.sender_identity(&Sender::Member(1)).unwrap(),
+            bob_identity
+        );
+
+        assert_matches!(
+            alice.group.sender_identity(&Sender::Member(5)),
+            Err(MlsError::LeafNotFound(5))
+        );
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sender_identity_resolves_external_senders() {
+        let (server_identity, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"server").await;
+
+        let ext_senders = ExternalSendersExt::new(vec![server_identity.clone()])
+            .into_extension()
+            .unwrap();
+
+        let group = test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice")
+            .await
+            .0
+            .create_group(core::iter::once(ext_senders).collect())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            group.sender_identity(&Sender::External(0)).unwrap(),
+            server_identity
+        );
+
+        assert_matches!(
+            group.sender_identity(&Sender::External(1)),
+            Err(MlsError::UnknownSigningIdentityForExternalSender)
+        );
+
+        assert_matches!(
+            group.sender_identity(&Sender::NewMemberProposal),
+            Err(MlsError::InvalidSender)
+        );
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn signature_key_rotation_keeps_hpke_key() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+
+        let old_leaf = groups[0].group.current_user_leaf_node().unwrap().clone();
+
+        let update = groups[0]
+            .group
+            .propose_signature_key_rotation(secret_key, identity.clone(), vec![])
+            .await
+            .unwrap();
+
+        for group in groups.iter_mut().skip(1) {
+            group.process_message(update.clone()).await.unwrap();
+        }
+
+        let commit_output = groups[1].group.commit(vec![]).await.unwrap();
+        groups[1].process_pending_commit().await.unwrap();
+
+        for group in groups.iter_mut().skip(2) {
+            group
+                .process_message(commit_output.commit_message.clone())
+                .await
+                .unwrap();
+        }
+
+        groups[0]
+            .process_message(commit_output.commit_message)
+            .await
+            .unwrap();
+
+        let new_leaf = groups[0].group.current_user_leaf_node().unwrap();
+
+        assert_eq!(new_leaf.public_key, old_leaf.public_key);
+        assert_eq!(new_leaf.signing_identity, identity);
+        assert_ne!(
+            new_leaf.signing_identity.signature_key,
+            old_leaf.signing_identity.signature_key
+        );
+
+        // The updater now signs with the new key and peers accept it
+        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+        groups[0].process_pending_commit().await.unwrap();
+
+        let (updater, others) = groups.split_first_mut().unwrap();
+
+        for group in others {
+            group
+                .process_message(commit_output.commit_message.clone())
+                .await
+                .unwrap();
+
+            assert!(Group::equal_group_state(&updater.group, &group.group));
+        }
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn signature_key_rotation_requires_new_signature_key() {
+        let mut test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let signer = test_group.group.signer.clone();
+        let identity = test_group
+            .group
+            .current_member_signing_identity()
+            .unwrap()
+            .clone();
+
+        let res = test_group
+            .group
+            .propose_signature_key_rotation(signer, identity, vec![])
+            .await;
+
+        assert_matches!(res, Err(MlsError::NoKeyChangeInUpdate));
+        assert!(test_group.group.pending_updates.is_empty());
+    }
+
+    #[cfg(all(feature = "by_ref_proposal", feature = "psk"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn failed_commit_leaves_group_unchanged() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+
+        let psk_ids = [ExternalPskId::new(vec![0]), ExternalPskId::new(vec![1])];
+
+        for group in groups.iter() {
+            for psk_id in psk_ids.iter() {
+                group
+                    .group
+                    .config
+                    .secret_store()
+                    .insert(psk_id.clone(), PreSharedKey::from(psk_id.to_vec()));
+            }
+        }
+
+        // Committing our update would replace our signer and private tree, but resolving
+        // the PSKs fails only once the update path has been decrypted.
+        groups[0].group.config.0.settings.max_psks_per_commit = 1;
+
+        let update = groups[0]
+            .group
+            .propose_update_with_identity(secret_key, identity, vec![])
+            .await
+            .unwrap();
+
+        groups[1].process_message(update).await.unwrap();
+
+        let commit_output = psk_ids
+            .iter()
+            .fold(groups[1].group.commit_builder(), |builder, psk_id| {
+                builder.add_external_psk(psk_id.clone()).unwrap()
+            })
+            .build()
+            .await
+            .unwrap();
+
+        let before = groups[0].group.snapshot();
+
+        let res = groups[0]
+            .process_message(commit_output.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::TooManyPsks));
+        assert_eq!(groups[0].group.snapshot(), before);
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_commit_with_old_adds_fails() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let key_package =
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "foobar").await;
+
+        let proposal = groups[0]
+            .group
+            .propose_add(key_package, vec![])
+            .await
+            .unwrap();
+
+        let commit = groups[0].group.commit(vec![]).await.unwrap().commit_message;
+
+        // 10 years from now
+        let future_time = MlsTime::now().seconds_since_epoch() + 10 * 365 * 24 * 3600;
+
+        let future_time =
+            MlsTime::from_duration_since_epoch(core::time::Duration::from_secs(future_time));
+
+        groups[1]
+            .group
+            .process_incoming_message(proposal)
+            .await
+            .unwrap();
+        let res = groups[1]
+            .group
+            .process_incoming_message_with_time(commit, future_time)
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidLifetime));
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_package_with_lifetime(
+        name: &str,
+        not_before: u64,
+        lifetime_in_s: u64,
+    ) -> MlsMessage {
+        TestClientBuilder::new_for_test()
+            .with_random_signing_identity(name, TEST_CIPHER_SUITE)
+            .await
+            .key_package_not_before(not_before)
+            .key_package_lifetime(lifetime_in_s)
+            .build()
+            .generate_key_package_message()
+            .await
+            .unwrap()
+    }
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn commit_add_with_key_package_lifetime(
+        not_before: u64,
+        lifetime_in_s: u64,
+    ) -> Result<(), MlsError> {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let key_package = key_package_with_lifetime("bob", not_before, lifetime_in_s).await;
+
+        group
+            .group
+            .commit_builder()
+            .add_member(key_package)?
+            .build()
+            .await
+            .map(|_| ())
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn committing_add_with_expired_key_package_fails() {
+        let now = MlsTime::now().seconds_since_epoch();
+        let res = commit_add_with_key_package_lifetime(now - 7200, 3600).await;
+
+        assert_matches!(res, Err(MlsError::InvalidLifetime));
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn committing_add_with_not_yet_valid_key_package_fails() {
+        let now = MlsTime::now().seconds_since_epoch();
+        let res = commit_add_with_key_package_lifetime(now + 3600, 3600).await;
+
+        assert_matches!(res, Err(MlsError::InvalidLifetime));
+    }
+
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn committing_add_with_valid_key_package_lifetime_succeeds() {
+        let now = MlsTime::now().seconds_since_epoch();
+        let res = commit_add_with_key_package_lifetime(now - 60, 3600).await;
+
+        assert_matches!(res, Ok(()));
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn join_with_min_cipher_suite_strength(bits: u16) -> Result<(), MlsError> {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .min_cipher_suite_strength(bits)
+            .build();
+
+        let key_package = bob.generate_key_package_message().await.unwrap();
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        bob.join_group(None, &commit.welcome_messages[0])
+            .await
+            .map(|_| ())
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_group_below_min_cipher_suite_strength_fails() {
+        assert_eq!(TEST_CIPHER_SUITE.security_level(), Some(128));
+
+        let res = join_with_min_cipher_suite_strength(256).await;
+
+        assert_matches!(res, Err(MlsError::CipherSuiteTooWeak(cs)) if cs == TEST_CIPHER_SUITE);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn joining_group_meeting_min_cipher_suite_strength_succeeds() {
+        let res = join_with_min_cipher_suite_strength(128).await;
+
+        assert_matches!(res, Ok(()));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_commit_below_min_cipher_suite_strength_fails() {
+        let alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let bob = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("bob", TEST_CIPHER_SUITE)
+            .await
+            .min_cipher_suite_strength(256)
+            .build();
+
+        let group_info = alice
+            .group
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let res = bob
+            .external_commit_builder()
+            .unwrap()
+            .build(group_info)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::CipherSuiteTooWeak(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn remove_by_identity_finds_member_after_earlier_removal() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 4).await;
+
+        let commit = groups[0]
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        groups[0].process_pending_commit().await.unwrap();
+        groups[3].process_message(commit).await.unwrap();
+
+        let credential = groups[0]
+            .group
+            .member_at_index(3)
+            .unwrap()
+            .signing_identity
+            .credential;
+
+        let commit = groups[0]
+            .group
+            .commit_builder()
+            .remove_member_by_identity(&credential)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        groups[0].process_pending_commit().await.unwrap();
+
+        assert!(groups[0].group.member_at_index(3).is_none());
+        assert!(groups[0].group.member_at_index(2).is_some());
+
+        groups[3].process_message(commit).await.unwrap();
+        assert!(!groups[3].group.status().is_active());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn remove_by_identity_fails_for_unknown_credential() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let credential =
+            crate::identity::test_utils::get_test_basic_credential(b"unknown".to_vec());
+
+        let res = group
+            .group
+            .commit_builder()
+            .remove_member_by_identity(&credential)
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MemberNotFound));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn remove_by_identity_fails_for_shared_credential() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let credential = groups[0]
+            .group
+            .member_at_index(1)
+            .unwrap()
+            .signing_identity
+            .credential;
+
+        groups[0]
+            .group
+            .state
+            .public_tree
+            .nodes
+            .borrow_as_leaf_mut(LeafIndex(2))
+            .unwrap()
+            .signing_identity
+            .credential = credential.clone();
+
+        let res = groups[0]
+            .group
+            .commit_builder()
+            .remove_member_by_identity(&credential)
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::AmbiguousIdentity));
+    }
+
+    #[cfg(all(feature = "std", feature = "by_ref_proposal"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn validating_proposals_flags_only_the_invalid_ones() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let now = MlsTime::now().seconds_since_epoch();
+
+        let valid = key_package_with_lifetime("bob", now - 60, 3600).await;
+        group.group.propose_add(valid, vec![]).await.unwrap();
+
+        let expired = key_package_with_lifetime("carol", now - 7200, 3600).await;
+        let expired = group.group.add_proposal(expired).unwrap();
+
+        let report = group
+            .group
+            .validate_proposals(vec![expired.clone()])
+            .await
+            .unwrap();
+
+        assert_eq!(report.results.len(), 2);
+        assert!(!report.is_valid());
+
+        let invalid = report.invalid_proposals().collect::<Vec<_>>();
+
+        assert_matches!(
+            invalid.as_slice(),
+            [(info, MlsError::InvalidLifetime)]
+                if info.proposal == expired && info.source == crate::mls_rules::ProposalSource::ByValue
+        );
+
+        // Validation does not touch the group state
+        assert_eq!(group.group.context().epoch, 0);
+        assert_eq!(group.group.state.proposals.proposals.len(), 1);
+    }
+
+    #[cfg(feature = "app_ack")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn app_ack_setup() -> (TestGroup, TestGroup) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.custom_proposal_type(ProposalType::APP_ACK)
+        })
+        .await;
+
+        let (bob, _) = alice
+            .join_with_custom_config("bob", true, |c| {
+                c.0.settings
+                    .custom_proposal_types
+                    .push(ProposalType::APP_ACK)
+            })
+            .await
+            .unwrap();
+
+        (alice, bob)
+    }
+
+    #[cfg(feature = "app_ack")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn received_app_ack_is_reported() {
+        let (mut alice, mut bob) = app_ack_setup().await;
+
+        let app_ack = AppAckProposal::new(vec![MessageRange::new(1, 0, 4)]);
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .app_ack(app_ack.clone())
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        alice.process_pending_commit().await.unwrap();
+
+        let res = bob.group.process_incoming_message(commit).await.unwrap();
+
+        #[cfg(feature = "state_update")]
+        assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update, .. })
+            if state_update.app_acks().len() == 1
+                && state_update.app_acks()[0].proposal == app_ack
+                && state_update.app_acks()[0].sender == Sender::Member(0));
+
+        #[cfg(not(feature = "state_update"))]
+        assert_matches!(res, ReceivedMessage::Commit(_));
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            let proposal = bob
+                .group
+                .propose_app_ack(app_ack.clone(), vec![])
+                .await
+                .unwrap();
+
+            let res = alice
+                .group
+                .process_incoming_message(proposal)
+                .await
+                .unwrap();
+
+            assert_matches!(res, ReceivedMessage::Proposal(ProposalMessageDescription { proposal: Proposal::AppAck(p), .. })
+                if p == app_ack);
+        }
+    }
+
+    #[cfg(feature = "app_ack")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn app_ack_with_invalid_range_is_rejected() {
+        let (mut alice, mut bob) = app_ack_setup().await;
+
+        for range in [MessageRange::new(1, 5, 4), MessageRange::new(7, 0, 1)] {
+            let app_ack = AppAckProposal::new(vec![range]);
+
+            let res = alice
+                .group
+                .commit_builder()
+                .app_ack(app_ack.clone())
+                .build()
+                .await
+                .map(|_| ());
+
+            assert_matches!(res, Err(MlsError::InvalidMessageRange));
+
+            #[cfg(feature = "by_ref_proposal")]
+            {
+                let proposal = alice.group.propose_app_ack(app_ack, vec![]).await.unwrap();
+                let res = bob.group.process_incoming_message(proposal).await;
+
+                assert_matches!(res, Err(MlsError::InvalidMessageRange));
+            }
+        }
+
+        #[cfg(not(feature = "by_ref_proposal"))]
+        let _ = &mut bob;
+    }
+
+    #[cfg(feature = "app_ack")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn app_ack_requires_support_from_all_members() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let res = alice
+            .group
+            .commit_builder()
+            .app_ack(AppAckProposal::new(vec![]))
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::UnsupportedAppAck));
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn custom_proposal_setup() -> (TestGroup, TestGroup) {
+        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
+            b.custom_proposal_type(TEST_CUSTOM_PROPOSAL_TYPE)
+        })
+        .await;
+
+        let (bob, _) = alice
+            .join_with_custom_config("bob", true, |c| {
+                c.0.settings
+                    .custom_proposal_types
+                    .push(TEST_CUSTOM_PROPOSAL_TYPE)
+            })
+            .await
+            .unwrap();
+
+        (alice, bob)
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_by_value() {
+        let (mut alice, mut bob) = custom_proposal_setup().await;
+
+        let custom_proposal = CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![0, 1, 2]);
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .custom_proposal(custom_proposal.clone())
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        let res = bob.group.process_incoming_message(commit).await.unwrap();
+
+        #[cfg(feature = "state_update")]
+        assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update: StateUpdate { custom_proposals, .. }, .. })
+            if custom_proposals.len() == 1 && custom_proposals[0].proposal == custom_proposal);
+
+        #[cfg(not(feature = "state_update"))]
+        assert_matches!(res, ReceivedMessage::Commit(_));
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_by_reference() {
+        let (mut alice, mut bob) = custom_proposal_setup().await;
+
+        let custom_proposal = CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![0, 1, 2]);
+
+        let proposal = alice
+            .group
+            .propose_custom(custom_proposal.clone(), vec![])
+            .await
+            .unwrap();
+
+        let recv_prop = bob.group.process_incoming_message(proposal).await.unwrap();
+
+        assert_matches!(recv_prop, ReceivedMessage::Proposal(ProposalMessageDescription { proposal: Proposal::Custom(c), ..})
+            if c == custom_proposal);
+
+        let commit = bob.group.commit(vec![]).await.unwrap().commit_message;
+        let res = alice.group.process_incoming_message(commit).await.unwrap();
+
+        #[cfg(feature = "state_update")]
+        assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update: StateUpdate { custom_proposals, .. }, .. })
+            if custom_proposals.len() == 1 && custom_proposals[0].proposal == custom_proposal);
+
+        #[cfg(not(feature = "state_update"))]
+        assert_matches!(res, ReceivedMessage::Commit(_));
+    }
+
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn can_join_with_psk() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+            .await
+            .group;
+
+        let (bob, key_pkg) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let psk_id = ExternalPskId::new(vec![0]);
+        let psk = PreSharedKey::from(vec![0]);
+
+        alice
+            .config
+            .secret_store()
+            .insert(psk_id.clone(), psk.clone());
+
+        bob.config.secret_store().insert(psk_id.clone(), psk);
+
+        let commit = alice
+            .commit_builder()
+            .add_member(key_pkg)
+            .unwrap()
+            .add_external_psk(psk_id)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        bob.join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn external_psk_is_mixed_into_epoch_by_all_members() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let (carol, key_pkg) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "carol").await;
+
+        let psk_id = ExternalPskId::new(vec![0]);
+        let psk = PreSharedKey::from(vec![1]);
+
+        // The committer must have the PSK before the commit can be built
+        let res = alice
+            .group
+            .commit_builder()
+            .add_external_psk(psk_id.clone())
+            .unwrap()
+            .build()
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::MissingRequiredPsk));
+
+        for config in [&alice.group.config, &bob.group.config, &carol.config] {
+            config.secret_store().insert(psk_id.clone(), psk.clone());
+        }
+
+        let commit = alice
+            .group
+            .commit_builder()
+            .add_member(key_pkg)
+            .unwrap()
+            .add_external_psk(psk_id)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.process_pending_commit().await.unwrap();
+        bob.process_message(commit.commit_message).await.unwrap();
+
+        let (carol, _) = carol
+            .join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap();
+
+        assert!(Group::equal_group_state(&alice.group, &bob.group));
+        assert!(alice.group.key_schedule == carol.key_schedule);
+    }
+
+    #[cfg(feature = "psk")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_exceeding_psk_limit_is_rejected() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let psk_ids = [ExternalPskId::new(vec![0]), ExternalPskId::new(vec![1])];
+
+        for psk_id in psk_ids.iter() {
+            let psk = PreSharedKey::from(psk_id.to_vec());
+
+            alice
+                .group
+                .config
+                .secret_store()
+                .insert(psk_id.clone(), psk.clone());
+
+            bob.group.config.secret_store().insert(psk_id.clone(), psk);
+        }
+
+        bob.group.config.0.settings.max_psks_per_commit = 1;
+
+        let commit = psk_ids
+            .iter()
+            .fold(alice.group.commit_builder(), |builder, psk_id| {
+                builder.add_external_psk(psk_id.clone()).unwrap()
+            })
+            .build()
+            .await
+            .unwrap();
+
+        let res = bob
+            .group
+            .process_incoming_message(commit.commit_message)
+            .await;
+
+        assert_matches!(res, Err(MlsError::TooManyPsks));
+
+        alice.group.clear_pending_commit();
+        alice.group.config.0.settings.max_psks_per_commit = 1;
+
+        let res = psk_ids
+            .iter()
+            .fold(alice.group.commit_builder(), |builder, psk_id| {
+                builder.add_external_psk(psk_id.clone()).unwrap()
+            })
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::TooManyPsks));
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn invalid_update_does_not_prevent_other_updates() {
+        const EXTENSION_TYPE: ExtensionType = ExtensionType::new(33);
+
+        let group_extensions = ExtensionList::from(vec![RequiredCapabilitiesExt {
+            extensions: vec![EXTENSION_TYPE],
+            ..Default::default()
+        }
+        .into_extension()
+        .unwrap()]);
+
+        // Alice creates a group requiring support for an extension
+        let mut alice = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(EXTENSION_TYPE)
+            .build()
+            .create_group(group_extensions.clone())
+            .await
+            .unwrap();
+
+        let (bob_signing_identity, bob_secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+
+        let bob_client = TestClientBuilder::new_for_test()
+            .signing_identity(
+                bob_signing_identity.clone(),
+                bob_secret_key.clone(),
+                TEST_CIPHER_SUITE,
+            )
+            .extension_type(EXTENSION_TYPE)
+            .build();
+
+        let carol_client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("carol", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(EXTENSION_TYPE)
+            .build();
+
+        let dave_client = TestClientBuilder::new_for_test()
+            .with_random_signing_identity("dave", TEST_CIPHER_SUITE)
+            .await
+            .extension_type(EXTENSION_TYPE)
+            .build();
+
+        // Alice adds Bob, Carol and Dave to the group. They all support the mandatory extension.
+        let commit = alice
+            .commit_builder()
+            .add_member(bob_client.generate_key_package_message().await.unwrap())
+            .unwrap()
+            .add_member(carol_client.generate_key_package_message().await.unwrap())
+            .unwrap()
+            .add_member(dave_client.generate_key_package_message().await.unwrap())
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let mut bob = bob_client
+            .join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap()
+            .0;
+
+        bob.write_to_storage().await.unwrap();
+
+        // Bob reloads his group data, but with parameters that will cause his generated leaves to
+        // not support the mandatory extension.
+        let mut bob = TestClientBuilder::new_for_test()
+            .signing_identity(bob_signing_identity, bob_secret_key, TEST_CIPHER_SUITE)
+            .key_package_repo(bob.config.key_package_repo())
+            .group_state_storage(bob.config.group_state_storage())
+            .build()
+            .load_group(alice.group_id())
+            .await
+            .unwrap();
+
+        let mut carol = carol_client
+            .join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap()
+            .0;
+
+        let mut dave = dave_client
+            .join_group(None, &commit.welcome_messages[0])
+            .await
+            .unwrap()
+            .0;
+
+        // Bob's updated leaf does not support the mandatory extension.
+        let bob_update = bob.propose_update(Vec::new()).await.unwrap();
+        let carol_update = carol.propose_update(Vec::new()).await.unwrap();
+        let dave_update = dave.propose_update(Vec::new()).await.unwrap();
+
+        // Alice receives the update proposals to be committed.
+        alice.process_incoming_message(bob_update).await.unwrap();
+        alice.process_incoming_message(carol_update).await.unwrap();
+        alice.process_incoming_message(dave_update).await.unwrap();
+
+        // Alice commits the update proposals.
+        alice.commit(Vec::new()).await.unwrap();
+        let commit_desc = alice.apply_pending_commit().await.unwrap();
+
+        let find_update_for = |id: &str| {
+            commit_desc
+                .state_update
+                .roster_update
+                .updated()
+                .iter()
+                .filter_map(|u| u.prior.signing_identity.credential.as_basic())
+                .any(|c| c.identifier == id.as_bytes())
+        };
+
+        // Check that all updates preserve identities.
+        let identities_are_preserved = commit_desc
+            .state_update
+            .roster_update
+            .updated()
+            .iter()
+            .filter_map(|u| {
+                let before = &u.prior.signing_identity.credential.as_basic()?.identifier;
+                let after = &u.new.signing_identity.credential.as_basic()?.identifier;
+                Some((before, after))
+            })
+            .all(|(before, after)| before == after);
+
+        assert!(identities_are_preserved);
+
+        // Carol's and Dave's updates should be part of the commit.
+        assert!(find_update_for("carol"));
+        assert!(find_update_for("dave"));
+
+        // Bob's update should be rejected.
+        assert!(!find_update_for("bob"));
+
+        // Check that all members are still in the group.
+        let all_members_are_in = alice
+            .roster()
+            .members_iter()
+            .zip(["alice", "bob", "carol", "dave"])
+            .all(|(member, id)| {
+                member
+                    .signing_identity
+                    .credential
+                    .as_basic()
+                    .unwrap()
+                    .identifier
+                    == id.as_bytes()
+            });
+
+        assert!(all_members_are_in);
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_may_enforce_path() {
+        test_custom_proposal_mls_rules(true).await;
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_need_not_enforce_path() {
+        test_custom_proposal_mls_rules(false).await;
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_custom_proposal_mls_rules(path_required_for_custom: bool) {
+        let mls_rules = CustomMlsRules {
+            path_required_for_custom,
+            external_joiner_can_send_custom: true,
+        };
+
+        let mut alice = client_with_custom_rules(b"alice", mls_rules.clone())
+            .await
+            .create_group(Default::default())
+            .await
+            .unwrap();
+
+        let alice_pub_before = alice.current_user_leaf_node().unwrap().public_key.clone();
+
+        let kp = client_with_custom_rules(b"bob", mls_rules)
+            .await
+            .generate_key_package_message()
+            .await
+            .unwrap();
+
+        alice
+            .commit_builder()
+            .custom_proposal(CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![]))
+            .add_member(kp)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        alice.apply_pending_commit().await.unwrap();
+
+        let alice_pub_after = &alice.current_user_leaf_node().unwrap().public_key;
+
+        if path_required_for_custom {
+            assert_ne!(alice_pub_after, &alice_pub_before);
+        } else {
+            assert_eq!(alice_pub_after, &alice_pub_before);
+        }
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_by_value_in_external_join_may_be_allowed() {
+        test_custom_proposal_by_value_in_external_join(true).await
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_by_value_in_external_join_may_not_be_allowed() {
+        test_custom_proposal_by_value_in_external_join(false).await
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn test_custom_proposal_by_value_in_external_join(external_joiner_can_send_custom: bool) {
+        let mls_rules = CustomMlsRules {
+            path_required_for_custom: true,
+            external_joiner_can_send_custom,
+        };
+
+        let mut alice = client_with_custom_rules(b"alice", mls_rules.clone())
+            .await
+            .create_group(Default::default())
+            .await
+            .unwrap();
+
+        let group_info = alice
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let commit = client_with_custom_rules(b"bob", mls_rules)
+            .await
+            .external_commit_builder()
+            .unwrap()
+            .with_custom_proposal(CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![]))
+            .build(group_info)
+            .await;
+
+        if external_joiner_can_send_custom {
+            let commit = commit.unwrap().1;
+            alice.process_incoming_message(commit).await.unwrap();
+        } else {
+            assert_matches!(commit.map(|_| ()), Err(MlsError::MlsRulesError(_)));
+        }
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn custom_proposal_by_ref_in_external_join() {
+        let mls_rules = CustomMlsRules {
+            path_required_for_custom: true,
+            external_joiner_can_send_custom: true,
+        };
+
+        let mut alice = client_with_custom_rules(b"alice", mls_rules.clone())
+            .await
+            .create_group(Default::default())
+            .await
+            .unwrap();
+
+        let by_ref = CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![]);
+        let by_ref = alice.propose_custom(by_ref, vec![]).await.unwrap();
+
+        let group_info = alice
+            .group_info_message_allowing_ext_commit(true)
+            .await
+            .unwrap();
+
+        let (_, commit) = client_with_custom_rules(b"bob", mls_rules)
+            .await
+            .external_commit_builder()
+            .unwrap()
+            .with_received_custom_proposal(by_ref)
+            .build(group_info)
+            .await
+            .unwrap();
+
+        alice.process_incoming_message(commit).await.unwrap();
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn client_with_custom_rules(
+        name: &[u8],
+        mls_rules: CustomMlsRules,
+    ) -> Client<impl MlsConfig> {
+        let (signing_identity, signer) = get_test_signing_identity(TEST_CIPHER_SUITE, name).await;
+
+        ClientBuilder::new()
+            .crypto_provider(TestCryptoProvider::new())
+            .identity_provider(BasicWithCustomProvider::new(BasicIdentityProvider::new()))
+            .signing_identity(signing_identity, signer, TEST_CIPHER_SUITE)
+            .custom_proposal_type(TEST_CUSTOM_PROPOSAL_TYPE)
+            .mls_rules(mls_rules)
+            .build()
+    }
+
+    #[derive(Debug, Clone)]
+    struct CustomMlsRules {
+        path_required_for_custom: bool,
+        external_joiner_can_send_custom: bool,
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    impl ProposalBundle {
+        fn has_test_custom_proposal(&self) -> bool {
+            self.custom_proposal_types()
+                .any(|t| t == TEST_CUSTOM_PROPOSAL_TYPE)
+        }
+    }
+
+    #[cfg(feature = "custom_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl crate::MlsRules for CustomMlsRules {
+        type Error = MlsError;
+
+        fn commit_options(
+            &self,
+            _: &Roster,
+            _: &ExtensionList,
+            proposals: &ProposalBundle,
+        ) -> Result<CommitOptions, MlsError> {
+            Ok(CommitOptions::default().with_path_required(
+                !proposals.has_test_custom_proposal() || self.path_required_for_custom,
+            ))
+        }
+
+        fn encryption_options(
+            &self,
+            _: &Roster,
+            _: &ExtensionList,
+        ) -> Result<crate::mls_rules::EncryptionOptions, MlsError> {
+            Ok(Default::default())
+        }
+
+        async fn filter_proposals(
+            &self,
+            _: CommitDirection,
+            sender: CommitSource,
+            _: &Roster,
+            _: &ExtensionList,
+            proposals: ProposalBundle,
+        ) -> Result<ProposalBundle, MlsError> {
+            let is_external = matches!(sender, CommitSource::NewMember(_));
+            let has_custom = proposals.has_test_custom_proposal();
+            let allowed = !has_custom || !is_external || self.external_joiner_can_send_custom;
+
+            allowed.then_some(proposals).ok_or(MlsError::InvalidSender)
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn group_can_receive_commit_from_self() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
             .await
-            .unwrap();
+            .group;
 
-        let (_, bob_key_pkg) =
-            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+        let commit = group.commit(vec![]).await.unwrap();
 
-        alice
-            .commit_builder()
-            .add_member(bob_key_pkg)
-            .unwrap()
-            .build()
+        let update = group
+            .process_incoming_message(commit.commit_message)
             .await
             .unwrap();
 
-        alice.apply_pending_commit().await.unwrap();
-        assert_eq!(alice.roster().members_iter().count(), 2);
+        let ReceivedMessage::Commit(update) = update else {
+            panic!("expected commit message")
+        };
 
-        let ext_senders = make_x509_external_senders_ext()
-            .await
-            .into_extension()
-            .unwrap();
+        assert_eq!(update.committer, *group.private_tree.self_index);
+    }
 
-        let res = alice
-            .commit_builder()
-            .set_group_context_ext(core::iter::once(ext_senders).collect())
-            .unwrap()
-            .build()
-            .await;
+    #[cfg(all(feature = "tracing", feature = "std"))]
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        spans: std::sync::Arc<std::sync::Mutex<Vec<RecordedSpan>>>,
+    }
 
-        assert_matches!(
-            res,
-            Err(MlsError::RequiredCredentialNotFound(CredentialType::X509))
-        );
+    #[cfg(all(feature = "tracing", feature = "std"))]
+    struct RecordedSpan {
+        name: &'static str,
+        fields: Vec<(&'static str, String)>,
     }
 
-    /*
-     * Edge case paths
-     */
+    #[cfg(all(feature = "tracing", feature = "std"))]
+    impl tracing::field::Visit for RecordedSpan {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn core::fmt::Debug) {
+            self.fields.push((field.name(), format!("{value:?}")));
+        }
+    }
 
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn committing_degenerate_path_succeeds() {
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+    #[cfg(all(feature = "tracing", feature = "std"))]
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
 
-        groups[0].group.commit_modifiers.modify_tree = |tree: &mut TreeKemPublic| {
-            tree.update_node(get_test_25519_key(1u8), 1).unwrap();
-            tree.update_node(get_test_25519_key(1u8), 3).unwrap();
-        };
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut recorded = RecordedSpan {
+                name: span.metadata().name(),
+                fields: Vec::new(),
+            };
 
-        groups[0].group.commit_modifiers.modify_leaf = |leaf, sk| {
-            leaf.public_key = get_test_25519_key(1u8);
-            Some(sk.clone())
-        };
+            span.record(&mut recorded);
 
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(recorded);
 
-        let res = groups[7]
-            .process_message(commit_output.commit_message)
-            .await;
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
 
-        assert!(res.is_ok());
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
     }
 
+    #[cfg(all(feature = "tracing", feature = "std"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn inserting_key_in_filtered_node_fails() {
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+    async fn processing_commit_emits_epoch_spans() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
 
-        let commit_output = groups[0]
-            .group
-            .commit_builder()
-            .remove_member(1)
-            .unwrap()
-            .build()
-            .await
-            .unwrap();
+        let commit = alice.group.commit(Vec::new()).await.unwrap();
 
-        groups[0].process_pending_commit().await.unwrap();
+        let recorder = SpanRecorder::default();
+        let guard = tracing::subscriber::set_default(recorder.clone());
 
-        for group in groups.iter_mut().skip(2) {
-            group
-                .process_message(commit_output.commit_message.clone())
-                .await
-                .unwrap();
-        }
+        bob.process_message(commit.commit_message).await.unwrap();
 
-        groups[0].group.commit_modifiers.modify_tree = |tree: &mut TreeKemPublic| {
-            tree.update_node(get_test_25519_key(1u8), 1).unwrap();
-        };
+        drop(guard);
 
-        groups[0].group.commit_modifiers.modify_path = |path: Vec<UpdatePathNode>| {
-            let mut path = path;
-            let mut node = path[0].clone();
-            node.public_key = get_test_25519_key(1u8);
-            path.insert(0, node);
-            path
-        };
+        let spans = recorder.spans.lock().unwrap();
+        let new_epoch = ("epoch", bob.group.current_epoch().to_string());
 
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
+        assert!(spans.iter().any(|s| s.name == "process_commit"));
 
-        let res = groups[7]
-            .process_message(commit_output.commit_message)
-            .await;
+        assert!(spans
+            .iter()
+            .any(|s| s.name == "update_key_schedule" && s.fields.contains(&new_epoch)));
 
-        // We should get a path validation error, since the path is too long
-        assert_matches!(res, Err(MlsError::WrongPathLen));
+        assert!(spans
+            .iter()
+            .any(|s| s.name == "from_key_schedule" && s.fields.contains(&new_epoch)));
+
+        assert!(spans
+            .iter()
+            .flat_map(|s| &s.fields)
+            .all(|(name, _)| !name.contains("secret") && !name.contains("key")));
     }
 
+    #[cfg(feature = "private_message")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn commit_with_too_short_path_fails() {
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 10).await;
+    async fn removed_member_cannot_decrypt_new_epoch() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
 
-        let commit_output = groups[0]
+        let before_removal = groups[0]
+            .group
+            .encrypt_application_message(b"before removal", vec![])
+            .await
+            .unwrap()
+            .into_ciphertext()
+            .unwrap();
+
+        // State of the removed member at the epoch preceding its removal
+        let retained = groups[2].group.clone();
+
+        let commit = groups[0]
             .group
             .commit_builder()
-            .remove_member(1)
+            .remove_member(2)
             .unwrap()
             .build()
             .await
             .unwrap();
 
         groups[0].process_pending_commit().await.unwrap();
+        groups[1]
+            .process_message(commit.commit_message.clone())
+            .await
+            .unwrap();
 
-        for group in groups.iter_mut().skip(2) {
-            group
-                .process_message(commit_output.commit_message.clone())
-                .await
-                .unwrap();
-        }
-
-        groups[0].group.commit_modifiers.modify_path = |path: Vec<UpdatePathNode>| {
-            let mut path = path;
-            path.pop();
-            path
-        };
-
-        let commit_output = groups[0].group.commit(vec![]).await.unwrap();
-
-        let res = groups[7]
-            .process_message(commit_output.commit_message)
-            .await;
-
-        assert!(res.is_err());
-    }
-
-    #[cfg(feature = "by_ref_proposal")]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn update_proposal_can_change_credential() {
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
-        let (identity, secret_key) = get_test_signing_identity(TEST_CIPHER_SUITE, b"member").await;
+        groups[2]
+            .process_message(commit.commit_message)
+            .await
+            .unwrap();
 
-        let update = groups[0]
+        let message = groups[0]
             .group
-            .propose_update_with_identity(secret_key, identity.clone(), vec![])
+            .encrypt_application_message(b"after removal", vec![])
             .await
             .unwrap();
 
-        groups[1].process_message(update).await.unwrap();
-        let commit_output = groups[1].group.commit(vec![]).await.unwrap();
+        let ciphertext = message.clone().into_ciphertext().unwrap();
 
-        // Check that the credential was updated by in the committer's state.
-        groups[1].process_pending_commit().await.unwrap();
-        let new_member = groups[1].group.roster().member_with_index(0).unwrap();
+        let bob = &groups[1].group;
 
-        assert_eq!(
-            new_member.signing_identity.credential,
-            get_test_basic_credential(b"member".to_vec())
-        );
+        let member_can_decrypt = bob.can_decrypt_with(&bob.private_tree, &ciphertext).await;
+        assert!(member_can_decrypt);
 
-        assert_eq!(
-            new_member.signing_identity.signature_key,
-            identity.signature_key
-        );
+        let removed_could_decrypt = retained
+            .can_decrypt_with(&retained.private_tree, &before_removal)
+            .await;
 
-        // Check that the credential was updated in the updater's state.
-        groups[0]
-            .process_message(commit_output.commit_message)
-            .await
-            .unwrap();
-        let new_member = groups[0].group.roster().member_with_index(0).unwrap();
+        assert!(removed_could_decrypt);
 
-        assert_eq!(
-            new_member.signing_identity.credential,
-            get_test_basic_credential(b"member".to_vec())
-        );
+        let removed_can_decrypt = retained
+            .can_decrypt_with(&retained.private_tree, &ciphertext)
+            .await;
 
-        assert_eq!(
-            new_member.signing_identity.signature_key,
-            identity.signature_key
-        );
+        assert!(!removed_can_decrypt);
+
+        let res = groups[2].group.process_incoming_message(message).await;
+
+        assert_matches!(res, Err(MlsError::EpochNotFound));
     }
 
-    #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn receiving_commit_with_old_adds_fails() {
-        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+    async fn receiver_agrees_on_remove_and_reuses_freed_leaf() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
 
         let key_package =
-            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "foobar").await;
+            test_key_package_message(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "dave").await;
 
-        let proposal = groups[0]
+        let new_identity = key_package
+            .clone()
+            .into_key_package()
+            .unwrap()
+            .leaf_node
+            .signing_identity;
+
+        let commit = groups[0]
             .group
-            .propose_add(key_package, vec![])
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .add_member(key_package)
+            .unwrap()
+            .build()
             .await
             .unwrap();
 
-        let commit = groups[0].group.commit(vec![]).await.unwrap().commit_message;
-
-        // 10 years from now
-        let future_time = MlsTime::now().seconds_since_epoch() + 10 * 365 * 24 * 3600;
-
-        let future_time =
-            MlsTime::from_duration_since_epoch(core::time::Duration::from_secs(future_time));
+        groups[0].process_pending_commit().await.unwrap();
 
-        groups[1]
-            .group
-            .process_incoming_message(proposal)
+        groups[2]
+            .process_message(commit.commit_message)
             .await
             .unwrap();
-        let res = groups[1]
-            .group
-            .process_incoming_message_with_time(commit, future_time)
-            .await;
 
-        assert_matches!(res, Err(MlsError::InvalidLifetime));
-    }
+        let (alice, carol) = (&groups[0].group, &groups[2].group);
 
-    #[cfg(feature = "custom_proposal")]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn custom_proposal_setup() -> (TestGroup, TestGroup) {
-        let mut alice = test_group_custom_config(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, |b| {
-            b.custom_proposal_type(TEST_CUSTOM_PROPOSAL_TYPE)
-        })
-        .await;
+        assert_eq!(alice.context().tree_hash, carol.context().tree_hash);
+        assert!(alice.key_schedule == carol.key_schedule);
 
-        let (bob, _) = alice
-            .join_with_custom_config("bob", true, |c| {
-                c.0.settings
-                    .custom_proposal_types
-                    .push(TEST_CUSTOM_PROPOSAL_TYPE)
-            })
-            .await
-            .unwrap();
+        // The new member is placed in the leaf freed by the remove
+        let new_member = carol.member_at_index(1).unwrap();
 
-        (alice, bob)
+        assert_eq!(new_member.signing_identity, new_identity);
+
+        assert_eq!(carol.roster().members_iter().count(), 3);
     }
 
-    #[cfg(feature = "custom_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_by_value() {
-        let (mut alice, mut bob) = custom_proposal_setup().await;
-
-        let custom_proposal = CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![0, 1, 2]);
+    async fn removed_member_group_is_suspended() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
 
-        let commit = alice
+        let commit = groups[0]
             .group
             .commit_builder()
-            .custom_proposal(custom_proposal.clone())
+            .remove_member(1)
+            .unwrap()
             .build()
             .await
-            .unwrap()
-            .commit_message;
-
-        let res = bob.group.process_incoming_message(commit).await.unwrap();
-
-        #[cfg(feature = "state_update")]
-        assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update: StateUpdate { custom_proposals, .. }, .. })
-            if custom_proposals.len() == 1 && custom_proposals[0].proposal == custom_proposal);
-
-        #[cfg(not(feature = "state_update"))]
-        assert_matches!(res, ReceivedMessage::Commit(_));
-    }
-
-    #[cfg(feature = "custom_proposal")]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_by_reference() {
-        let (mut alice, mut bob) = custom_proposal_setup().await;
+            .unwrap();
 
-        let custom_proposal = CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![0, 1, 2]);
+        groups[0].process_pending_commit().await.unwrap();
 
-        let proposal = alice
-            .group
-            .propose_custom(custom_proposal.clone(), vec![])
+        groups[1]
+            .process_message(commit.commit_message)
             .await
             .unwrap();
 
-        let recv_prop = bob.group.process_incoming_message(proposal).await.unwrap();
+        let bob = &mut groups[1].group;
 
-        assert_matches!(recv_prop, ReceivedMessage::Proposal(ProposalMessageDescription { proposal: Proposal::Custom(c), ..})
-            if c == custom_proposal);
+        assert_eq!(
+            bob.status(),
+            GroupStatus::Suspended(SuspensionReason::Removed)
+        );
 
-        let commit = bob.group.commit(vec![]).await.unwrap().commit_message;
-        let res = alice.group.process_incoming_message(commit).await.unwrap();
+        // The state of the last epoch the member was part of can still be read
+        assert_eq!(bob.current_epoch(), 2);
+        assert_eq!(bob.roster().members_iter().count(), 3);
 
-        #[cfg(feature = "state_update")]
-        assert_matches!(res, ReceivedMessage::Commit(CommitMessageDescription { state_update: StateUpdate { custom_proposals, .. }, .. })
-            if custom_proposals.len() == 1 && custom_proposals[0].proposal == custom_proposal);
+        let res = bob.commit(vec![]).await;
+        assert_matches!(res, Err(MlsError::GroupNotActive));
 
-        #[cfg(not(feature = "state_update"))]
-        assert_matches!(res, ReceivedMessage::Commit(_));
+        #[cfg(feature = "private_message")]
+        {
+            let res = bob.encrypt_application_message(b"hello", vec![]).await;
+            assert_matches!(res, Err(MlsError::GroupNotActive));
+        }
     }
 
-    #[cfg(feature = "psk")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn can_join_with_psk() {
-        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
-            .await
-            .group;
-
-        let (bob, key_pkg) =
-            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+    async fn last_committer_is_recorded() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
 
-        let psk_id = ExternalPskId::new(vec![0]);
-        let psk = PreSharedKey::from(vec![0]);
+        assert_eq!(groups[2].group.last_committer(), None);
 
-        alice
-            .config
-            .secret_store()
-            .insert(psk_id.clone(), psk.clone());
+        let commit = groups[2].group.commit(vec![]).await.unwrap();
 
-        bob.config.secret_store().insert(psk_id.clone(), psk);
+        groups[2].process_pending_commit().await.unwrap();
 
-        let commit = alice
-            .commit_builder()
-            .add_member(key_pkg)
-            .unwrap()
-            .add_external_psk(psk_id)
-            .unwrap()
-            .build()
+        groups[0]
+            .process_message(commit.commit_message)
             .await
             .unwrap();
 
-        bob.join_group(None, &commit.welcome_messages[0])
-            .await
-            .unwrap();
+        assert_eq!(groups[0].group.last_committer(), Some(2));
+        assert_eq!(groups[2].group.last_committer(), Some(2));
     }
 
-    #[cfg(feature = "by_ref_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn invalid_update_does_not_prevent_other_updates() {
-        const EXTENSION_TYPE: ExtensionType = ExtensionType::new(33);
+    async fn roster_and_epoch_reflect_added_members() {
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+        let group_id = alice.group.group_id().to_vec();
 
-        let group_extensions = ExtensionList::from(vec![RequiredCapabilitiesExt {
-            extensions: vec![EXTENSION_TYPE],
-            ..Default::default()
-        }
-        .into_extension()
-        .unwrap()]);
+        let (bob, _) = alice.join("bob").await;
+        let (carol, _) = alice.join("carol").await;
 
-        // Alice creates a group requiring support for an extension
-        let mut alice = TestClientBuilder::new_for_test()
-            .with_random_signing_identity("alice", TEST_CIPHER_SUITE)
-            .await
-            .extension_type(EXTENSION_TYPE)
-            .build()
-            .create_group(group_extensions.clone())
-            .await
-            .unwrap();
+        assert_eq!(alice.group.current_epoch(), 2);
+        assert_eq!(alice.group.group_id(), group_id);
 
-        let (bob_signing_identity, bob_secret_key) =
-            get_test_signing_identity(TEST_CIPHER_SUITE, b"bob").await;
+        let members = alice.group.roster().members();
 
-        let bob_client = TestClientBuilder::new_for_test()
-            .signing_identity(
-                bob_signing_identity.clone(),
-                bob_secret_key.clone(),
-                TEST_CIPHER_SUITE,
-            )
-            .extension_type(EXTENSION_TYPE)
-            .build();
+        assert_eq!(
+            members.iter().map(|m| m.index).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
 
-        let carol_client = TestClientBuilder::new_for_test()
-            .with_random_signing_identity("carol", TEST_CIPHER_SUITE)
-            .await
-            .extension_type(EXTENSION_TYPE)
-            .build();
+        assert_eq!(
+            members[1].signing_identity,
+            bob.group.current_member_signing_identity().unwrap().clone()
+        );
 
-        let dave_client = TestClientBuilder::new_for_test()
-            .with_random_signing_identity("dave", TEST_CIPHER_SUITE)
-            .await
-            .extension_type(EXTENSION_TYPE)
-            .build();
+        assert_eq!(
+            members[2].signing_identity,
+            carol
+                .group
+                .current_member_signing_identity()
+                .unwrap()
+                .clone()
+        );
+    }
 
-        // Alice adds Bob, Carol and Dave to the group. They all support the mandatory extension.
-        let commit = alice
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn epochs_since_rotation_resets_on_update() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        assert_eq!(groups[0].group.epochs_since_rotation(0), Some(2));
+        assert_eq!(groups[0].group.epochs_since_rotation(2), Some(0));
+        assert_eq!(groups[0].group.epochs_since_rotation(3), None);
+
+        // Carol joined after Bob and doesn't know when he last rotated
+        assert_eq!(groups[2].group.epochs_since_rotation(1), None);
+
+        let update = groups[1].group.propose_update(vec![]).await.unwrap();
+
+        groups[0].process_message(update.clone()).await.unwrap();
+        groups[2].process_message(update).await.unwrap();
+
+        let commit = groups[0]
+            .group
             .commit_builder()
-            .add_member(bob_client.generate_key_package_message().await.unwrap())
-            .unwrap()
-            .add_member(carol_client.generate_key_package_message().await.unwrap())
-            .unwrap()
-            .add_member(dave_client.generate_key_package_message().await.unwrap())
-            .unwrap()
             .build()
             .await
-            .unwrap();
+            .unwrap()
+            .commit_message;
 
-        alice.apply_pending_commit().await.unwrap();
+        groups[0].process_pending_commit().await.unwrap();
+        groups[1].process_message(commit.clone()).await.unwrap();
+        groups[2].process_message(commit).await.unwrap();
 
-        let mut bob = bob_client
-            .join_group(None, &commit.welcome_messages[0])
-            .await
-            .unwrap()
-            .0;
+        // Alice committed with a path and Bob's update was applied
+        for group in groups.iter() {
+            assert_eq!(group.group.epochs_since_rotation(0), Some(0));
+            assert_eq!(group.group.epochs_since_rotation(1), Some(0));
+        }
 
-        bob.write_to_storage().await.unwrap();
+        for _ in 0..2 {
+            let commit = groups[2].group.commit(vec![]).await.unwrap().commit_message;
 
-        // Bob reloads his group data, but with parameters that will cause his generated leaves to
-        // not support the mandatory extension.
-        let mut bob = TestClientBuilder::new_for_test()
-            .signing_identity(bob_signing_identity, bob_secret_key, TEST_CIPHER_SUITE)
-            .key_package_repo(bob.config.key_package_repo())
-            .group_state_storage(bob.config.group_state_storage())
-            .build()
-            .load_group(alice.group_id())
+            groups[2].process_pending_commit().await.unwrap();
+            groups[0].process_message(commit.clone()).await.unwrap();
+            groups[1].process_message(commit).await.unwrap();
+        }
+
+        assert_eq!(groups[0].group.epochs_since_rotation(1), Some(2));
+        assert_eq!(groups[0].group.epochs_since_rotation(2), Some(0));
+    }
+
+    #[cfg(all(feature = "prior_epoch", feature = "private_message"))]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn messages_from_retained_prior_epochs_can_be_decrypted() {
+        use crate::storage_provider::in_memory::DEFAULT_EPOCH_RETENTION_LIMIT;
+
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        let buffered = alice
+            .group
+            .encrypt_application_message(b"buffered", vec![])
             .await
             .unwrap();
 
-        let mut carol = carol_client
-            .join_group(None, &commit.welcome_messages[0])
+        let too_old = alice
+            .group
+            .encrypt_application_message(b"too old", vec![])
             .await
-            .unwrap()
-            .0;
+            .unwrap();
 
-        let mut dave = dave_client
-            .join_group(None, &commit.welcome_messages[0])
-            .await
-            .unwrap()
-            .0;
+        for _ in 0..2 {
+            let commit = alice.group.commit(vec![]).await.unwrap();
+            alice.process_pending_commit().await.unwrap();
+            bob.process_message(commit.commit_message).await.unwrap();
+        }
 
-        // Bob's updated leaf does not support the mandatory extension.
-        let bob_update = bob.propose_update(Vec::new()).await.unwrap();
-        let carol_update = carol.propose_update(Vec::new()).await.unwrap();
-        let dave_update = dave.propose_update(Vec::new()).await.unwrap();
+        let received = bob.process_message(buffered).await.unwrap();
 
-        // Alice receives the update proposals to be committed.
-        alice.process_incoming_message(bob_update).await.unwrap();
-        alice.process_incoming_message(carol_update).await.unwrap();
-        alice.process_incoming_message(dave_update).await.unwrap();
+        assert_matches!(
+            received,
+            ReceivedMessage::ApplicationMessage(m) if m.data() == b"buffered"
+        );
 
-        // Alice commits the update proposals.
-        alice.commit(Vec::new()).await.unwrap();
-        let commit_desc = alice.apply_pending_commit().await.unwrap();
+        // Advance past the retention window of the storage and persist the state so
+        // that the oldest epochs are evicted
+        for _ in 0..DEFAULT_EPOCH_RETENTION_LIMIT {
+            let commit = alice.group.commit(vec![]).await.unwrap();
+            alice.process_pending_commit().await.unwrap();
+            bob.process_message(commit.commit_message).await.unwrap();
+        }
 
-        let find_update_for = |id: &str| {
-            commit_desc
-                .state_update
-                .roster_update
-                .updated()
-                .iter()
-                .filter_map(|u| u.prior.signing_identity.credential.as_basic())
-                .any(|c| c.identifier == id.as_bytes())
-        };
+        bob.group.write_to_storage().await.unwrap();
 
-        // Check that all updates preserve identities.
-        let identities_are_preserved = commit_desc
-            .state_update
-            .roster_update
-            .updated()
-            .iter()
-            .filter_map(|u| {
-                let before = &u.prior.signing_identity.credential.as_basic()?.identifier;
-                let after = &u.new.signing_identity.credential.as_basic()?.identifier;
-                Some((before, after))
-            })
-            .all(|(before, after)| before == after);
+        let res = bob.process_message(too_old).await;
 
-        assert!(identities_are_preserved);
+        assert_matches!(res, Err(MlsError::EpochNotFound));
+    }
 
-        // Carol's and Dave's updates should be part of the commit.
-        assert!(find_update_for("carol"));
-        assert!(find_update_for("dave"));
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn commit_encrypted_to_stale_key_is_reported() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
 
-        // Bob's update should be rejected.
-        assert!(!find_update_for("bob"));
+        // Alice encrypts the path secret to bob's leaf key as she last saw it, which no longer
+        // matches the secret key bob holds.
+        let (new_secret, _) = bob
+            .group
+            .cipher_suite_provider
+            .kem_generate()
+            .await
+            .unwrap();
+        bob.group.private_tree.secret_keys[0] = Some(new_secret);
 
-        // Check that all members are still in the group.
-        let all_members_are_in = alice
-            .roster()
-            .members_iter()
-            .zip(["alice", "bob", "carol", "dave"])
-            .all(|(member, id)| {
-                member
-                    .signing_identity
-                    .credential
-                    .as_basic()
-                    .unwrap()
-                    .identifier
-                    == id.as_bytes()
-            });
+        let commit = alice.group.commit(Vec::new()).await.unwrap();
+        let res = bob.process_message(commit.commit_message).await;
 
-        assert!(all_members_are_in);
+        assert_matches!(res, Err(MlsError::StalePathEncryption));
     }
 
-    #[cfg(feature = "custom_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_may_enforce_path() {
-        test_custom_proposal_mls_rules(true).await;
+    async fn corrupted_path_encryption_is_not_reported_as_stale() {
+        let (mut alice, mut bob) =
+            test_two_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, true).await;
+
+        alice.group.commit_modifiers.modify_path = |mut nodes| {
+            for ct in nodes
+                .iter_mut()
+                .flat_map(|node| &mut node.encrypted_path_secret)
+            {
+                ct.ciphertext[0] ^= 1;
+            }
+
+            nodes
+        };
+
+        let commit = alice.group.commit(Vec::new()).await.unwrap();
+        let res = bob.process_message(commit.commit_message).await;
+
+        assert_matches!(res, Err(MlsError::CryptoProviderError(_)));
     }
 
-    #[cfg(feature = "custom_proposal")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_need_not_enforce_path() {
-        test_custom_proposal_mls_rules(false).await;
-    }
+    async fn welcome_group_secrets_report_ciphertext_lengths() {
+        let mut test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
 
-    #[cfg(feature = "custom_proposal")]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn test_custom_proposal_mls_rules(path_required_for_custom: bool) {
-        let mls_rules = CustomMlsRules {
-            path_required_for_custom,
-            external_joiner_can_send_custom: true,
-        };
+        let (_, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
 
-        let mut alice = client_with_custom_rules(b"alice", mls_rules.clone())
-            .await
-            .create_group(Default::default())
+        let commit_output = test_group
+            .group
+            .commit_builder()
+            .add_member(bob_key_package)
+            .unwrap()
+            .build()
             .await
             .unwrap();
 
-        let alice_pub_before = alice.current_user_leaf_node().unwrap().public_key.clone();
+        let welcome = commit_output.welcome_messages[0]
+            .clone()
+            .into_welcome()
+            .unwrap();
 
-        let kp = client_with_custom_rules(b"bob", mls_rules)
-            .await
-            .generate_key_package_message()
+        let secrets = &welcome.secrets[0].encrypted_group_secrets;
+
+        assert_eq!(secrets.kem_output_len(), secrets.kem_output.len());
+        assert_eq!(secrets.ciphertext_len(), secrets.ciphertext.len());
+
+        // For DHKEM, the encapsulated key is an ephemeral public key.
+        let (_, public_key) = test_group
+            .group
+            .cipher_suite_provider
+            .kem_generate()
             .await
             .unwrap();
 
-        alice
+        assert_eq!(secrets.kem_output_len(), public_key.len());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn welcome_with_duplicate_secrets_for_joiner_is_rejected() {
+        let mut test_group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        let (bob, bob_key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        let commit_output = test_group
+            .group
             .commit_builder()
-            .custom_proposal(CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![]))
-            .add_member(kp)
+            .add_member(bob_key_package)
             .unwrap()
             .build()
             .await
             .unwrap();
 
-        alice.apply_pending_commit().await.unwrap();
+        let mut welcome = commit_output.welcome_messages[0].clone();
 
-        let alice_pub_after = &alice.current_user_leaf_node().unwrap().public_key;
+        let MlsMessagePayload::Welcome(ref mut inner) = welcome.payload else {
+            panic!("expected welcome message");
+        };
 
-        if path_required_for_custom {
-            assert_ne!(alice_pub_after, &alice_pub_before);
-        } else {
-            assert_eq!(alice_pub_after, &alice_pub_before);
-        }
-    }
+        let duplicate = inner.secrets[0].clone();
+        inner.secrets.push(duplicate);
 
-    #[cfg(feature = "custom_proposal")]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_by_value_in_external_join_may_be_allowed() {
-        test_custom_proposal_by_value_in_external_join(true).await
+        let res = bob
+            .join_group(commit_output.ratchet_tree, &welcome)
+            .await
+            .map(|_| ());
+
+        assert_matches!(res, Err(MlsError::AmbiguousWelcome));
     }
 
-    #[cfg(feature = "custom_proposal")]
+    #[cfg(all(feature = "std", feature = "by_ref_proposal"))]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_by_value_in_external_join_may_not_be_allowed() {
-        test_custom_proposal_by_value_in_external_join(false).await
-    }
+    async fn identity_cache_skips_validation_until_update() {
+        use core::sync::atomic::Ordering;
+        use mls_rs_core::identity::IdentityProvider;
 
-    #[cfg(feature = "custom_proposal")]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn test_custom_proposal_by_value_in_external_join(external_joiner_can_send_custom: bool) {
-        let mls_rules = CustomMlsRules {
-            path_required_for_custom: true,
-            external_joiner_can_send_custom,
-        };
+        let mut alice = test_utils::test_group_custom_config(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            |builder| builder.identity_cache_ttl(3600),
+        )
+        .await;
 
-        let mut alice = client_with_custom_rules(b"alice", mls_rules.clone())
-            .await
-            .create_group(Default::default())
+        let (mut bob, _) = alice.join("bob").await;
+
+        let calls = alice
+            .group
+            .config
+            .identity_provider()
+            .validate_member_calls
+            .clone();
+
+        let provider = alice.group.identity_provider();
+
+        // A second validation within the TTL is answered from the cache
+        let (carol, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"carol").await;
+        let before = calls.load(Ordering::Relaxed);
+
+        provider.validate_member(&carol, None, None).await.unwrap();
+        provider.validate_member(&carol, None, None).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::Relaxed), before + 1);
+
+        // Results are not reused for other extensions or for validations with a timestamp
+        let extensions = alice.group.context().extensions.clone();
+        let before = calls.load(Ordering::Relaxed);
+
+        provider
+            .validate_member(&carol, None, Some(&extensions))
             .await
             .unwrap();
 
-        let group_info = alice
-            .group_info_message_allowing_ext_commit(true)
+        provider
+            .validate_member(&carol, Some(MlsTime::from(7200)), None)
             .await
             .unwrap();
 
-        let commit = client_with_custom_rules(b"bob", mls_rules)
+        provider
+            .validate_member(&carol, Some(MlsTime::from(7300)), None)
             .await
-            .external_commit_builder()
-            .unwrap()
-            .with_custom_proposal(CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![]))
-            .build(group_info)
-            .await;
+            .unwrap();
 
-        if external_joiner_can_send_custom {
-            let commit = commit.unwrap().1;
-            alice.process_incoming_message(commit).await.unwrap();
-        } else {
-            assert_matches!(commit.map(|_| ()), Err(MlsError::MlsRulesError(_)));
-        }
-    }
+        assert_eq!(calls.load(Ordering::Relaxed), before + 2);
 
-    #[cfg(feature = "custom_proposal")]
-    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn custom_proposal_by_ref_in_external_join() {
-        let mls_rules = CustomMlsRules {
-            path_required_for_custom: true,
-            external_joiner_can_send_custom: true,
-        };
+        let bob_identity = bob.group.current_member_signing_identity().unwrap().clone();
 
-        let mut alice = client_with_custom_rules(b"alice", mls_rules.clone())
-            .await
-            .create_group(Default::default())
+        provider
+            .validate_member(&bob_identity, None, None)
             .await
             .unwrap();
 
-        let by_ref = CustomProposal::new(TEST_CUSTOM_PROPOSAL_TYPE, vec![]);
-        let by_ref = alice.propose_custom(by_ref, vec![]).await.unwrap();
+        let before = calls.load(Ordering::Relaxed);
 
-        let group_info = alice
-            .group_info_message_allowing_ext_commit(true)
+        provider
+            .validate_member(&bob_identity, None, None)
             .await
             .unwrap();
 
-        let (_, commit) = client_with_custom_rules(b"bob", mls_rules)
-            .await
-            .external_commit_builder()
-            .unwrap()
-            .with_received_custom_proposal(by_ref)
-            .build(group_info)
+        assert_eq!(calls.load(Ordering::Relaxed), before);
+
+        // Committing an update from bob drops his cached validation
+        let update = bob.group.propose_update(vec![]).await.unwrap();
+        alice.process_message(update).await.unwrap();
+        alice.group.commit(vec![]).await.unwrap();
+        alice.process_pending_commit().await.unwrap();
+
+        let before = calls.load(Ordering::Relaxed);
+
+        provider
+            .validate_member(&bob_identity, None, None)
             .await
             .unwrap();
 
-        alice.process_incoming_message(commit).await.unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), before + 1);
     }
 
-    #[cfg(feature = "custom_proposal")]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    async fn client_with_custom_rules(
-        name: &[u8],
-        mls_rules: CustomMlsRules,
-    ) -> Client<impl MlsConfig> {
-        let (signing_identity, signer) = get_test_signing_identity(TEST_CIPHER_SUITE, name).await;
+    #[cfg(feature = "std")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn identity_cache_is_not_used_after_credential_expiration() {
+        use core::sync::atomic::Ordering;
+        use mls_rs_core::identity::IdentityProvider;
 
-        ClientBuilder::new()
-            .crypto_provider(TestCryptoProvider::new())
-            .identity_provider(BasicWithCustomProvider::new(BasicIdentityProvider::new()))
-            .signing_identity(signing_identity, signer, TEST_CIPHER_SUITE)
-            .custom_proposal_type(TEST_CUSTOM_PROPOSAL_TYPE)
-            .mls_rules(mls_rules)
-            .build()
-    }
+        let mut alice = test_utils::test_group_custom_config(
+            TEST_PROTOCOL_VERSION,
+            TEST_CIPHER_SUITE,
+            |builder| builder.identity_cache_ttl(3600),
+        )
+        .await;
 
-    #[derive(Debug, Clone)]
-    struct CustomMlsRules {
-        path_required_for_custom: bool,
-        external_joiner_can_send_custom: bool,
-    }
+        alice.group.config.0.identity_provider.credential_expiration = Some(MlsTime::from(7250));
 
-    #[cfg(feature = "custom_proposal")]
-    impl ProposalBundle {
-        fn has_test_custom_proposal(&self) -> bool {
-            self.custom_proposal_types()
-                .any(|t| t == TEST_CUSTOM_PROPOSAL_TYPE)
+        let calls = alice
+            .group
+            .config
+            .identity_provider()
+            .validate_member_calls
+            .clone();
+
+        let provider = alice.group.identity_provider();
+        let (carol, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"carol").await;
+        let before = calls.load(Ordering::Relaxed);
+
+        for time in [7200, 7250] {
+            provider
+                .validate_member(&carol, Some(MlsTime::from(time)), None)
+                .await
+                .unwrap();
         }
+
+        assert_eq!(calls.load(Ordering::Relaxed), before + 1);
+
+        provider
+            .validate_member(&carol, Some(MlsTime::from(7251)), None)
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::Relaxed), before + 2);
     }
 
-    #[cfg(feature = "custom_proposal")]
-    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
-    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
-    impl crate::MlsRules for CustomMlsRules {
-        type Error = MlsError;
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn oversized_credential_is_rejected_before_identity_validation() {
+        use core::sync::atomic::Ordering;
 
-        fn commit_options(
-            &self,
-            _: &Roster,
-            _: &ExtensionList,
-            proposals: &ProposalBundle,
-        ) -> Result<CommitOptions, MlsError> {
-            Ok(CommitOptions::default().with_path_required(
-                !proposals.has_test_custom_proposal() || self.path_required_for_custom,
-            ))
-        }
+        let mut alice = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
 
-        fn encryption_options(
-            &self,
-            _: &Roster,
-            _: &ExtensionList,
-        ) -> Result<crate::mls_rules::EncryptionOptions, MlsError> {
-            Ok(Default::default())
-        }
+        alice.group.config.0.identity_provider.max_credential_size = Some(256);
 
-        async fn filter_proposals(
-            &self,
-            _: CommitDirection,
-            sender: CommitSource,
-            _: &Roster,
-            _: &ExtensionList,
-            proposals: ProposalBundle,
-        ) -> Result<ProposalBundle, MlsError> {
-            let is_external = matches!(sender, CommitSource::NewMember(_));
-            let has_custom = proposals.has_test_custom_proposal();
-            let allowed = !has_custom || !is_external || self.external_joiner_can_send_custom;
+        let calls = alice
+            .group
+            .config
+            .0
+            .identity_provider
+            .validate_member_calls
+            .clone();
 
-            allowed.then_some(proposals).ok_or(MlsError::InvalidSender)
-        }
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, &"bob".repeat(100))
+                .await;
+
+        let before = calls.load(Ordering::Relaxed);
+
+        let res = alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await;
+
+        assert_matches!(res, Err(MlsError::CredentialTooLarge(size)) if size > 256);
+        assert_eq!(calls.load(Ordering::Relaxed), before);
+
+        // Credentials within the limit are still validated by the provider
+        let (_, key_package) =
+            test_client_with_key_pkg(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "bob").await;
+
+        alice
+            .group
+            .commit_builder()
+            .add_member(key_package)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+
+        assert!(calls.load(Ordering::Relaxed) > before);
     }
 
+    #[cfg(feature = "private_message")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
-    async fn group_can_receive_commit_from_self() {
-        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE)
+    async fn suspended_group_refuses_to_encrypt() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        assert_eq!(groups[0].group.status(), GroupStatus::Active);
+
+        let res = groups[0]
+            .group
+            .encrypt_application_message(b"hello", vec![])
+            .await;
+
+        assert!(res.is_ok());
+
+        let commit = groups[0]
+            .group
+            .commit_builder()
+            .reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                TEST_CIPHER_SUITE,
+                Default::default(),
+            )
+            .unwrap()
+            .build()
             .await
-            .group;
+            .unwrap()
+            .commit_message;
 
-        let commit = group.commit(vec![]).await.unwrap();
+        groups[0].process_pending_commit().await.unwrap();
+        groups[1].process_message(commit).await.unwrap();
 
-        let update = group
-            .process_incoming_message(commit.commit_message)
+        for group in groups.iter_mut() {
+            assert_eq!(
+                group.group.status(),
+                GroupStatus::Suspended(SuspensionReason::ReInit)
+            );
+
+            let res = group
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await;
+
+            assert_matches!(res, Err(MlsError::GroupUsedAfterReInit));
+
+            let res = group.group.commit(vec![]).await;
+            assert_matches!(res, Err(MlsError::GroupUsedAfterReInit));
+        }
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn received_reinit_proposal_is_pending() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let proposal = groups[1]
+            .group
+            .propose_reinit(
+                None,
+                TEST_PROTOCOL_VERSION,
+                TEST_CIPHER_SUITE,
+                Default::default(),
+                vec![],
+            )
             .await
             .unwrap();
 
-        let ReceivedMessage::Commit(update) = update else {
-            panic!("expected commit message")
-        };
+        groups[0].process_message(proposal).await.unwrap();
 
-        assert_eq!(update.committer, *group.private_tree.self_index);
+        assert_eq!(groups[0].group.status(), GroupStatus::PendingReinit);
+        assert!(groups[0].group.status().is_active());
+
+        #[cfg(feature = "private_message")]
+        {
+            let res = groups[0]
+                .group
+                .encrypt_application_message(b"hello", vec![])
+                .await;
+
+            assert_matches!(res, Err(MlsError::CommitRequired));
+        }
+
+        let res = groups[0].group.commit(vec![]).await;
+        assert!(res.is_ok());
     }
 }
@@ -14,10 +14,16 @@ pub enum PaddingMode {
     StepFunction,
     /// No padding.
     None,
+    /// Pad the message to the next multiple of the block size set with
+    /// [`EncryptionOptions::with_padding_block_size`](crate::mls_rules::EncryptionOptions::with_padding_block_size).
+    /// Encrypting with a block size of zero fails.
+    FixedBlock,
+    /// Pad the message to the next power of two.
+    PowerOfTwo,
 }
 
 impl PaddingMode {
-    pub(super) fn padded_size(&self, content_size: usize) -> usize {
+    pub(super) fn padded_size(&self, content_size: usize, block_size: usize) -> usize {
         match self {
             PaddingMode::StepFunction => {
                 // The padding hides all but 2 most significant bits of `length`. The hidden bits are replaced
@@ -32,6 +38,10 @@ impl PaddingMode {
                 (content_size | (blind - 1)) + 1
             }
             PaddingMode::None => content_size,
+            PaddingMode::FixedBlock => {
+                content_size + (block_size - content_size % block_size) % block_size
+            }
+            PaddingMode::PowerOfTwo => content_size.next_power_of_two(),
         }
     }
 }
@@ -57,7 +67,7 @@ mod tests {
         for x in 1..1024 {
             test_cases.push(TestCase {
                 input: x,
-                output: PaddingMode::StepFunction.padded_size(x),
+                output: PaddingMode::StepFunction.padded_size(x, 0),
             });
         }
         test_cases
@@ -73,36 +83,52 @@ mod tests {
     #[test]
     fn test_no_padding() {
         for i in [0, 100, 1000, 10000] {
-            assert_eq!(PaddingMode::None.padded_size(i), i)
+            assert_eq!(PaddingMode::None.padded_size(i, 64), i)
         }
     }
 
+    #[test]
+    fn test_fixed_block_padding() {
+        assert_eq!(PaddingMode::FixedBlock.padded_size(0, 64), 0);
+        assert_eq!(PaddingMode::FixedBlock.padded_size(1, 64), 64);
+        assert_eq!(PaddingMode::FixedBlock.padded_size(64, 64), 64);
+        assert_eq!(PaddingMode::FixedBlock.padded_size(65, 64), 128);
+    }
+
+    #[test]
+    fn test_power_of_two_padding() {
+        assert_eq!(PaddingMode::PowerOfTwo.padded_size(1, 0), 1);
+        assert_eq!(PaddingMode::PowerOfTwo.padded_size(3, 0), 4);
+        assert_eq!(PaddingMode::PowerOfTwo.padded_size(64, 0), 64);
+        assert_eq!(PaddingMode::PowerOfTwo.padded_size(65, 0), 128);
+    }
+
     #[test]
     fn test_padding_length() {
-        assert_eq!(PaddingMode::StepFunction.padded_size(0), 32);
+        assert_eq!(PaddingMode::StepFunction.padded_size(0, 0), 32);
 
         // Short
-        assert_eq!(PaddingMode::StepFunction.padded_size(63), 64);
-        assert_eq!(PaddingMode::StepFunction.padded_size(64), 96);
-        assert_eq!(PaddingMode::StepFunction.padded_size(65), 96);
+        assert_eq!(PaddingMode::StepFunction.padded_size(63, 0), 64);
+        assert_eq!(PaddingMode::StepFunction.padded_size(64, 0), 96);
+        assert_eq!(PaddingMode::StepFunction.padded_size(65, 0), 96);
 
         // Almost long and almost short
-        assert_eq!(PaddingMode::StepFunction.padded_size(127), 128);
-        assert_eq!(PaddingMode::StepFunction.padded_size(128), 160);
-        assert_eq!(PaddingMode::StepFunction.padded_size(129), 160);
+        assert_eq!(PaddingMode::StepFunction.padded_size(127, 0), 128);
+        assert_eq!(PaddingMode::StepFunction.padded_size(128, 0), 160);
+        assert_eq!(PaddingMode::StepFunction.padded_size(129, 0), 160);
 
         // One length from each of the 4 buckets between 256 and 512
-        assert_eq!(PaddingMode::StepFunction.padded_size(260), 320);
-        assert_eq!(PaddingMode::StepFunction.padded_size(330), 384);
-        assert_eq!(PaddingMode::StepFunction.padded_size(390), 448);
-        assert_eq!(PaddingMode::StepFunction.padded_size(490), 512);
+        assert_eq!(PaddingMode::StepFunction.padded_size(260, 0), 320);
+        assert_eq!(PaddingMode::StepFunction.padded_size(330, 0), 384);
+        assert_eq!(PaddingMode::StepFunction.padded_size(390, 0), 448);
+        assert_eq!(PaddingMode::StepFunction.padded_size(490, 0), 512);
 
         // All test cases
         let test_cases: Vec<TestCase> = load_test_cases();
         for test_case in test_cases {
             assert_eq!(
                 test_case.output,
-                PaddingMode::StepFunction.padded_size(test_case.input)
+                PaddingMode::StepFunction.padded_size(test_case.input, 0)
             );
         }
     }
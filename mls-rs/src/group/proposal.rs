@@ -18,6 +18,9 @@ use mls_rs_core::{group::Capabilities, identity::SigningIdentity};
 #[cfg(feature = "by_ref_proposal")]
 use crate::group::proposal_ref::ProposalRef;
 
+#[cfg(feature = "app_ack")]
+use crate::tree_kem::TreeKemPublic;
+
 pub use mls_rs_core::extension::ExtensionList;
 pub use mls_rs_core::group::ProposalType;
 
@@ -226,6 +229,82 @@ impl Debug for ExternalInit {
     }
 }
 
+#[cfg(feature = "app_ack")]
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A range of application message generations received from a single
+/// [`Member`](mls_rs_core::group::Member).
+pub struct MessageRange {
+    pub(crate) sender: LeafIndex,
+    pub(crate) first_generation: u32,
+    pub(crate) last_generation: u32,
+}
+
+#[cfg(feature = "app_ack")]
+impl MessageRange {
+    /// Create a range covering the generations `first_generation` to
+    /// `last_generation` inclusive of messages sent by the member at leaf
+    /// index `sender`.
+    pub fn new(sender: u32, first_generation: u32, last_generation: u32) -> Self {
+        Self {
+            sender: LeafIndex(sender),
+            first_generation,
+            last_generation,
+        }
+    }
+
+    /// The leaf index of the member that sent the messages.
+    pub fn sender(&self) -> u32 {
+        *self.sender
+    }
+
+    /// The generation of the first received message in this range.
+    pub fn first_generation(&self) -> u32 {
+        self.first_generation
+    }
+
+    /// The generation of the last received message in this range.
+    pub fn last_generation(&self) -> u32 {
+        self.last_generation
+    }
+}
+
+#[cfg(feature = "app_ack")]
+#[derive(Clone, Debug, PartialEq, Eq, MlsSize, MlsEncode, MlsDecode)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A proposal acknowledging the receipt of application messages.
+///
+/// This proposal does not change the group state. It allows members to
+/// detect messages that were lost in transit and must be retransmitted.
+pub struct AppAckProposal {
+    pub(crate) received_ranges: Vec<MessageRange>,
+}
+
+#[cfg(feature = "app_ack")]
+impl AppAckProposal {
+    /// Create an acknowledgement of the messages in `received_ranges`.
+    pub fn new(received_ranges: Vec<MessageRange>) -> Self {
+        Self { received_ranges }
+    }
+
+    /// Ranges of application messages acknowledged by this proposal.
+    pub fn received_ranges(&self) -> &[MessageRange] {
+        &self.received_ranges
+    }
+
+    /// Check that every range is well formed and refers to a member of `tree`.
+    pub(crate) fn validate(&self, tree: &TreeKemPublic) -> Result<(), MlsError> {
+        self.received_ranges.iter().try_for_each(|range| {
+            let valid = range.first_generation <= range.last_generation
+                && tree.get_leaf_node(range.sender).is_ok();
+
+            valid.then_some(()).ok_or(MlsError::InvalidMessageRange)
+        })
+    }
+}
+
 #[cfg(feature = "custom_proposal")]
 #[derive(Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
@@ -326,6 +405,8 @@ pub enum Proposal {
     ReInit(ReInitProposal),
     ExternalInit(ExternalInit),
     GroupContextExtensions(ExtensionList),
+    #[cfg(feature = "app_ack")]
+    AppAck(AppAckProposal),
     #[cfg(feature = "custom_proposal")]
     Custom(CustomProposal),
 }
@@ -342,6 +423,8 @@ impl MlsSize for Proposal {
             Proposal::ReInit(p) => p.mls_encoded_len(),
             Proposal::ExternalInit(p) => p.mls_encoded_len(),
             Proposal::GroupContextExtensions(p) => p.mls_encoded_len(),
+            #[cfg(feature = "app_ack")]
+            Proposal::AppAck(p) => p.mls_encoded_len(),
             #[cfg(feature = "custom_proposal")]
             Proposal::Custom(p) => mls_rs_codec::byte_vec::mls_encoded_len(&p.data),
         };
@@ -364,6 +447,8 @@ impl MlsEncode for Proposal {
             Proposal::ReInit(p) => p.mls_encode(writer),
             Proposal::ExternalInit(p) => p.mls_encode(writer),
             Proposal::GroupContextExtensions(p) => p.mls_encode(writer),
+            #[cfg(feature = "app_ack")]
+            Proposal::AppAck(p) => p.mls_encode(writer),
             #[cfg(feature = "custom_proposal")]
             Proposal::Custom(p) => {
                 if p.proposal_type.raw_value() <= 7 {
@@ -375,6 +460,12 @@ impl MlsEncode for Proposal {
                     // #[cfg(not(feature = "std"))]
                     return Err(mls_rs_codec::Error::Custom(2));
                 }
+
+                #[cfg(feature = "app_ack")]
+                if p.proposal_type == ProposalType::APP_ACK {
+                    return Err(mls_rs_codec::Error::Custom(2));
+                }
+
                 mls_rs_codec::byte_vec::mls_encode(&p.data, writer)
             }
         }
@@ -401,6 +492,8 @@ impl MlsDecode for Proposal {
             ProposalType::GROUP_CONTEXT_EXTENSIONS => {
                 Proposal::GroupContextExtensions(ExtensionList::mls_decode(reader)?)
             }
+            #[cfg(feature = "app_ack")]
+            ProposalType::APP_ACK => Proposal::AppAck(AppAckProposal::mls_decode(reader)?),
             #[cfg(feature = "custom_proposal")]
             custom => Proposal::Custom(CustomProposal {
                 proposal_type: custom,
@@ -425,13 +518,59 @@ impl Proposal {
             Proposal::ReInit(_) => ProposalType::RE_INIT,
             Proposal::ExternalInit(_) => ProposalType::EXTERNAL_INIT,
             Proposal::GroupContextExtensions(_) => ProposalType::GROUP_CONTEXT_EXTENSIONS,
+            #[cfg(feature = "app_ack")]
+            Proposal::AppAck(_) => ProposalType::APP_ACK,
             #[cfg(feature = "custom_proposal")]
             Proposal::Custom(c) => c.proposal_type,
         }
     }
 }
 
+macro_rules! impl_proposal_accessors {
+    ($($(#[$meta:meta])* $variant:ident($ty:ty), $is:ident, $as:ident;)*) => {
+        impl Proposal {
+            $(
+                $(#[$meta])*
+                #[doc = concat!("Whether this is a `", stringify!($variant), "` proposal.")]
+                pub fn $is(&self) -> bool {
+                    matches!(self, Proposal::$variant(_))
+                }
+
+                $(#[$meta])*
+                #[doc = concat!("The content of this proposal if it is a `", stringify!($variant), "` proposal.")]
+                pub fn $as(&self) -> Option<&$ty> {
+                    match self {
+                        Proposal::$variant(p) => {
+                            let p: &$ty = p;
+                            Some(p)
+                        }
+                        #[allow(unreachable_patterns)]
+                        _ => None,
+                    }
+                }
+            )*
+        }
+    };
+}
+
+impl_proposal_accessors! {
+    Add(AddProposal), is_add, as_add;
+    #[cfg(feature = "by_ref_proposal")]
+    Update(UpdateProposal), is_update, as_update;
+    Remove(RemoveProposal), is_remove, as_remove;
+    #[cfg(feature = "psk")]
+    Psk(PreSharedKeyProposal), is_psk, as_psk;
+    ReInit(ReInitProposal), is_reinit, as_reinit;
+    ExternalInit(ExternalInit), is_external_init, as_external_init;
+    GroupContextExtensions(ExtensionList), is_group_context_extensions, as_group_context_extensions;
+    #[cfg(feature = "app_ack")]
+    AppAck(AppAckProposal), is_app_ack, as_app_ack;
+    #[cfg(feature = "custom_proposal")]
+    Custom(CustomProposal), is_custom, as_custom;
+}
+
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 /// An enum that represents a borrowed version of [`Proposal`].
 pub enum BorrowedProposal<'a> {
     Add(&'a AddProposal),
@@ -443,6 +582,8 @@ pub enum BorrowedProposal<'a> {
     ReInit(&'a ReInitProposal),
     ExternalInit(&'a ExternalInit),
     GroupContextExtensions(&'a ExtensionList),
+    #[cfg(feature = "app_ack")]
+    AppAck(&'a AppAckProposal),
     #[cfg(feature = "custom_proposal")]
     Custom(&'a CustomProposal),
 }
@@ -461,6 +602,8 @@ impl<'a> From<BorrowedProposal<'a>> for Proposal {
             BorrowedProposal::GroupContextExtensions(ext) => {
                 Proposal::GroupContextExtensions(ext.clone())
             }
+            #[cfg(feature = "app_ack")]
+            BorrowedProposal::AppAck(app_ack) => Proposal::AppAck(app_ack.clone()),
             #[cfg(feature = "custom_proposal")]
             BorrowedProposal::Custom(custom) => Proposal::Custom(custom.clone()),
         }
@@ -479,6 +622,8 @@ impl BorrowedProposal<'_> {
             BorrowedProposal::ReInit(_) => ProposalType::RE_INIT,
             BorrowedProposal::ExternalInit(_) => ProposalType::EXTERNAL_INIT,
             BorrowedProposal::GroupContextExtensions(_) => ProposalType::GROUP_CONTEXT_EXTENSIONS,
+            #[cfg(feature = "app_ack")]
+            BorrowedProposal::AppAck(_) => ProposalType::APP_ACK,
             #[cfg(feature = "custom_proposal")]
             BorrowedProposal::Custom(c) => c.proposal_type,
         }
@@ -497,6 +642,8 @@ impl<'a> From<&'a Proposal> for BorrowedProposal<'a> {
             Proposal::ReInit(p) => BorrowedProposal::ReInit(p),
             Proposal::ExternalInit(p) => BorrowedProposal::ExternalInit(p),
             Proposal::GroupContextExtensions(p) => BorrowedProposal::GroupContextExtensions(p),
+            #[cfg(feature = "app_ack")]
+            Proposal::AppAck(p) => BorrowedProposal::AppAck(p),
             #[cfg(feature = "custom_proposal")]
             Proposal::Custom(p) => BorrowedProposal::Custom(p),
         }
@@ -547,6 +694,13 @@ impl<'a> From<&'a ExtensionList> for BorrowedProposal<'a> {
     }
 }
 
+#[cfg(feature = "app_ack")]
+impl<'a> From<&'a AppAckProposal> for BorrowedProposal<'a> {
+    fn from(p: &'a AppAckProposal) -> Self {
+        Self::AppAck(p)
+    }
+}
+
 #[cfg(feature = "custom_proposal")]
 impl<'a> From<&'a CustomProposal> for BorrowedProposal<'a> {
     fn from(p: &'a CustomProposal) -> Self {
@@ -576,3 +730,191 @@ impl From<ProposalRef> for ProposalOrRef {
         Self::Reference(r)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec, vec::Vec};
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        key_package::test_utils::test_key_package,
+    };
+
+    #[cfg(feature = "by_ref_proposal")]
+    use crate::tree_kem::leaf_node::test_utils::get_basic_test_node;
+
+    #[cfg(feature = "psk")]
+    use crate::psk::PskNonce;
+
+    use super::*;
+
+    #[cfg(all(feature = "app_ack", feature = "custom_proposal"))]
+    use assert_matches::assert_matches;
+
+    fn matching_accessors(proposal: &Proposal) -> Vec<&'static str> {
+        let mut matching = Vec::new();
+
+        let mut check = |name, is: bool, has: bool| {
+            assert_eq!(is, has);
+
+            if is {
+                matching.push(name);
+            }
+        };
+
+        check("add", proposal.is_add(), proposal.as_add().is_some());
+        #[cfg(feature = "by_ref_proposal")]
+        check(
+            "update",
+            proposal.is_update(),
+            proposal.as_update().is_some(),
+        );
+        check(
+            "remove",
+            proposal.is_remove(),
+            proposal.as_remove().is_some(),
+        );
+        #[cfg(feature = "psk")]
+        check("psk", proposal.is_psk(), proposal.as_psk().is_some());
+        check(
+            "reinit",
+            proposal.is_reinit(),
+            proposal.as_reinit().is_some(),
+        );
+
+        check(
+            "external_init",
+            proposal.is_external_init(),
+            proposal.as_external_init().is_some(),
+        );
+
+        check(
+            "group_context_extensions",
+            proposal.is_group_context_extensions(),
+            proposal.as_group_context_extensions().is_some(),
+        );
+
+        #[cfg(feature = "app_ack")]
+        check(
+            "app_ack",
+            proposal.is_app_ack(),
+            proposal.as_app_ack().is_some(),
+        );
+
+        #[cfg(feature = "custom_proposal")]
+        check(
+            "custom",
+            proposal.is_custom(),
+            proposal.as_custom().is_some(),
+        );
+
+        matching
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn proposal_accessors_match_only_their_variant() {
+        let key_package = test_key_package(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, "alice").await;
+
+        let add = Proposal::Add(Box::new(AddProposal {
+            key_package: key_package.clone(),
+        }));
+
+        assert_eq!(matching_accessors(&add), ["add"]);
+        assert_eq!(add.as_add().unwrap().key_package, key_package);
+
+        #[cfg(feature = "by_ref_proposal")]
+        {
+            let leaf_node = get_basic_test_node(TEST_CIPHER_SUITE, "bob").await;
+            let update = Proposal::Update(UpdateProposal { leaf_node });
+            assert_eq!(matching_accessors(&update), ["update"]);
+        }
+
+        let remove = Proposal::Remove(RemoveProposal::from(3));
+        assert_eq!(matching_accessors(&remove), ["remove"]);
+        assert_eq!(remove.as_remove().unwrap().to_remove(), 3);
+
+        #[cfg(feature = "psk")]
+        {
+            let psk = Proposal::Psk(PreSharedKeyProposal {
+                psk: PreSharedKeyID {
+                    key_id: JustPreSharedKeyID::External(ExternalPskId::new(vec![1])),
+                    psk_nonce: PskNonce(vec![2]),
+                },
+            });
+
+            assert_eq!(matching_accessors(&psk), ["psk"]);
+        }
+
+        let reinit = Proposal::ReInit(ReInitProposal {
+            group_id: vec![1],
+            version: TEST_PROTOCOL_VERSION,
+            cipher_suite: TEST_CIPHER_SUITE,
+            extensions: ExtensionList::new(),
+        });
+
+        assert_eq!(matching_accessors(&reinit), ["reinit"]);
+
+        let external_init = Proposal::ExternalInit(ExternalInit {
+            kem_output: vec![1],
+        });
+
+        assert_eq!(matching_accessors(&external_init), ["external_init"]);
+
+        let extensions = Proposal::GroupContextExtensions(ExtensionList::new());
+
+        assert_eq!(
+            matching_accessors(&extensions),
+            ["group_context_extensions"]
+        );
+
+        #[cfg(feature = "app_ack")]
+        {
+            let app_ack = Proposal::AppAck(AppAckProposal::new(vec![MessageRange::new(1, 0, 3)]));
+            assert_eq!(matching_accessors(&app_ack), ["app_ack"]);
+        }
+
+        #[cfg(feature = "custom_proposal")]
+        {
+            let custom = Proposal::Custom(CustomProposal::new(ProposalType::new(42), vec![1]));
+            assert_eq!(matching_accessors(&custom), ["custom"]);
+        }
+    }
+
+    #[cfg(feature = "app_ack")]
+    #[test]
+    fn app_ack_proposal_round_trips() {
+        let proposal = Proposal::AppAck(AppAckProposal::new(vec![
+            MessageRange::new(0, 0, 10),
+            MessageRange::new(3, 5, 5),
+        ]));
+
+        let encoded = proposal.mls_encode_to_vec().unwrap();
+
+        assert_eq!(
+            encoded[..2],
+            ProposalType::APP_ACK.raw_value().to_be_bytes()
+        );
+        assert_eq!(encoded.len(), proposal.mls_encoded_len());
+
+        let decoded = Proposal::mls_decode(&mut &*encoded).unwrap();
+
+        assert_eq!(decoded, proposal);
+
+        let ranges = decoded.as_app_ack().unwrap().received_ranges();
+
+        assert_eq!(ranges[1].sender(), 3);
+        assert_eq!(ranges[1].first_generation(), 5);
+        assert_eq!(ranges[1].last_generation(), 5);
+    }
+
+    #[cfg(all(feature = "app_ack", feature = "custom_proposal"))]
+    #[test]
+    fn custom_proposal_can_not_use_app_ack_type() {
+        let proposal = Proposal::Custom(CustomProposal::new(ProposalType::APP_ACK, vec![1]));
+
+        assert_matches!(
+            proposal.mls_encode_to_vec(),
+            Err(mls_rs_codec::Error::Custom(2))
+        );
+    }
+}
@@ -104,6 +104,16 @@ impl ProposalCache {
         self.proposals.is_empty()
     }
 
+    pub fn has_reinit(&self) -> bool {
+        #[cfg(feature = "std")]
+        let mut proposals = self.proposals.values();
+
+        #[cfg(not(feature = "std"))]
+        let mut proposals = self.proposals.iter().map(|(_, cached)| cached);
+
+        proposals.any(|cached| matches!(cached.proposal, Proposal::ReInit(_)))
+    }
+
     pub fn insert(&mut self, proposal_ref: ProposalRef, proposal: Proposal, sender: Sender) {
         let cached_proposal = CachedProposal { proposal, sender };
 
@@ -2246,6 +2256,81 @@ mod tests {
         assert_eq!(processed_proposals.1.unused_proposals, vec![proposal_info]);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_duplicate_removes_keeps_one() {
+        let (alice, mut tree) = new_tree("alice").await;
+        let bob = add_member(&mut tree, "bob").await;
+        let carol = add_member(&mut tree, "carol").await;
+
+        let remove = Proposal::Remove(RemoveProposal { to_remove: carol });
+        let alice_ref = make_proposal_ref(&remove, alice).await;
+        let bob_ref = make_proposal_ref(&remove, bob).await;
+
+        let (committed, state) =
+            CommitSender::new(&tree, alice, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+                .cache(alice_ref.clone(), remove.clone(), alice)
+                .cache(bob_ref.clone(), remove, bob)
+                .send()
+                .await
+                .unwrap();
+
+        assert_eq!(committed.len(), 1);
+        assert!(committed[0] == alice_ref.into() || committed[0] == bob_ref.into());
+        assert!(state.public_tree.get_leaf_node(carol).is_err());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_additional_duplicate_removes_keeps_one() {
+        let (alice, mut tree) = new_tree("alice").await;
+        let bob = add_member(&mut tree, "bob").await;
+
+        let remove = Proposal::Remove(RemoveProposal { to_remove: bob });
+
+        let (committed, _) =
+            CommitSender::new(&tree, alice, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+                .with_additional([remove.clone(), remove.clone()])
+                .send()
+                .await
+                .unwrap();
+
+        assert_eq!(committed, vec![remove.into()]);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn sending_additional_remove_of_blank_leaf_fails() {
+        let (alice, mut tree) = new_tree("alice").await;
+        let bob = add_member(&mut tree, "bob").await;
+        add_member(&mut tree, "carol").await;
+
+        tree.nodes.blank_leaf_node(bob).unwrap();
+
+        let res = CommitSender::new(&tree, alice, test_cipher_suite_provider(TEST_CIPHER_SUITE))
+            .with_additional([Proposal::Remove(RemoveProposal { to_remove: bob })])
+            .send()
+            .await;
+
+        assert_matches!(res, Err(MlsError::RemovingNonExistingMember));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_duplicate_removes_fails() {
+        let (alice, mut tree) = new_tree("alice").await;
+        let bob = add_member(&mut tree, "bob").await;
+
+        let remove = Proposal::Remove(RemoveProposal { to_remove: bob });
+
+        let res = CommitReceiver::new(
+            &tree,
+            alice,
+            alice,
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+        )
+        .receive([remove.clone(), remove])
+        .await;
+
+        assert_matches!(res, Err(MlsError::RemovingNonExistingMember));
+    }
+
     #[cfg(feature = "psk")]
     fn make_external_psk(id: &[u8], nonce: PskNonce) -> PreSharedKeyProposal {
         PreSharedKeyProposal {
@@ -2496,6 +2581,45 @@ mod tests {
         assert_eq!(processed_proposals.1.unused_proposals, vec![proposal_info]);
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_reinit_downgrading_cipher_suite_fails() {
+        // P384_AES256 and P256_AES128 are supported by every test crypto provider.
+        let cs_provider = test_cipher_suite_provider(CipherSuite::P384_AES256);
+        let (alice, tree) = new_tree("alice").await;
+
+        let reinit = ReInitProposal {
+            cipher_suite: CipherSuite::P256_AES128,
+            ..make_reinit(TEST_PROTOCOL_VERSION)
+        };
+
+        let res = CommitReceiver::new(&tree, alice, alice, cs_provider)
+            .receive([Proposal::ReInit(reinit)])
+            .await;
+
+        assert_matches!(res, Err(MlsError::DowngradeDetected));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_reinit_upgrading_cipher_suite_succeeds() {
+        let (alice, tree) = new_tree("alice").await;
+
+        let reinit = ReInitProposal {
+            cipher_suite: CipherSuite::P384_AES256,
+            ..make_reinit(TEST_PROTOCOL_VERSION)
+        };
+
+        let res = CommitReceiver::new(
+            &tree,
+            alice,
+            alice,
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+        )
+        .receive([Proposal::ReInit(reinit)])
+        .await;
+
+        assert!(res.is_ok());
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn receiving_update_for_committer_fails() {
         let (alice, tree) = new_tree("alice").await;
@@ -3369,10 +3493,7 @@ mod tests {
         .receive([required_capabilities_proposal(33)])
         .await;
 
-        assert_matches!(
-            res,
-            Err(MlsError::RequiredExtensionNotFound(v)) if v == 33.into()
-        );
+        assert_matches!(res, Err(MlsError::ExistingMemberViolatesCapabilities(0)));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -3384,10 +3505,7 @@ mod tests {
             .send()
             .await;
 
-        assert_matches!(
-            res,
-            Err(MlsError::RequiredExtensionNotFound(v)) if v == 33.into()
-        );
+        assert_matches!(res, Err(MlsError::ExistingMemberViolatesCapabilities(0)));
     }
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
@@ -3623,6 +3741,83 @@ mod tests {
         assert_matches!(res, Err(MlsError::InUseCredentialTypeUnsupportedByNewLeaf));
     }
 
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn key_package_with_proposal_support(
+        name: &str,
+        proposals: Vec<ProposalType>,
+    ) -> KeyPackage {
+        let (signing_identity, secret_key) =
+            get_test_signing_identity(TEST_CIPHER_SUITE, name.as_bytes()).await;
+
+        let generator = KeyPackageGenerator {
+            protocol_version: TEST_PROTOCOL_VERSION,
+            cipher_suite_provider: &test_cipher_suite_provider(TEST_CIPHER_SUITE),
+            signing_identity: &signing_identity,
+            signing_key: &secret_key,
+            identity_provider: &BasicIdentityProvider,
+        };
+
+        generator
+            .generate(
+                Lifetime::years(1).unwrap(),
+                Capabilities {
+                    proposals,
+                    ..get_test_capabilities()
+                },
+                Default::default(),
+                Default::default(),
+            )
+            .await
+            .unwrap()
+            .key_package
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    async fn receive_add_with_required_proposal(
+        supported_proposals: Vec<ProposalType>,
+    ) -> Result<(), MlsError> {
+        let (alice, tree) = new_tree("alice").await;
+
+        let required_capabilities = RequiredCapabilitiesExt {
+            proposals: vec![ProposalType::new(33)],
+            ..Default::default()
+        };
+
+        CommitReceiver::new(
+            &tree,
+            alice,
+            alice,
+            test_cipher_suite_provider(TEST_CIPHER_SUITE),
+        )
+        .with_extensions(vec![required_capabilities.into_extension().unwrap()].into())
+        .receive([Proposal::Add(Box::new(AddProposal {
+            key_package: key_package_with_proposal_support("bob", supported_proposals).await,
+        }))])
+        .await
+        .map(|_| ())
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_add_not_supporting_required_capabilities_fails() {
+        let res = receive_add_with_required_proposal(vec![]).await;
+
+        assert_matches!(
+            res,
+            Err(MlsError::RequiredProposalNotFound(p)) if p == ProposalType::new(33)
+        );
+    }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn receiving_add_supporting_required_capabilities_succeeds() {
+        let res = receive_add_with_required_proposal(vec![ProposalType::new(33)]).await;
+
+        assert_matches!(res, Ok(()));
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn sending_additional_add_with_leaf_not_supporting_credential_type_of_other_leaf_fails() {
         let (alice, tree) = new_tree("alice").await;
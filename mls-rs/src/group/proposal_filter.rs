@@ -9,7 +9,10 @@ mod filtering_common;
 mod filtering;
 #[cfg(not(feature = "by_ref_proposal"))]
 pub mod filtering_lite;
-#[cfg(all(feature = "custom_proposal", not(feature = "by_ref_proposal")))]
+#[cfg(all(
+    any(feature = "custom_proposal", feature = "app_ack"),
+    not(feature = "by_ref_proposal")
+))]
 use filtering_lite as filtering;
 
 pub use bundle::{ProposalBundle, ProposalInfo, ProposalSource};
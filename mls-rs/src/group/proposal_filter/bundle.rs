@@ -25,6 +25,9 @@ use crate::group::PreSharedKeyProposal;
 #[cfg(feature = "custom_proposal")]
 use crate::group::proposal::CustomProposal;
 
+#[cfg(feature = "app_ack")]
+use crate::group::proposal::AppAckProposal;
+
 use crate::group::ExternalInit;
 
 use core::iter::empty;
@@ -44,6 +47,8 @@ pub struct ProposalBundle {
     pub(crate) reinitializations: Vec<ProposalInfo<ReInitProposal>>,
     pub(crate) external_initializations: Vec<ProposalInfo<ExternalInit>>,
     pub(crate) group_context_extensions: Vec<ProposalInfo<ExtensionList>>,
+    #[cfg(feature = "app_ack")]
+    pub(crate) app_acks: Vec<ProposalInfo<AppAckProposal>>,
     #[cfg(feature = "custom_proposal")]
     pub(crate) custom_proposals: Vec<ProposalInfo<CustomProposal>>,
 }
@@ -90,6 +95,12 @@ impl ProposalBundle {
                     source,
                 })
             }
+            #[cfg(feature = "app_ack")]
+            Proposal::AppAck(proposal) => self.app_acks.push(ProposalInfo {
+                proposal,
+                sender,
+                source,
+            }),
             #[cfg(feature = "custom_proposal")]
             Proposal::Custom(proposal) => self.custom_proposals.push(ProposalInfo {
                 proposal,
@@ -198,6 +209,11 @@ impl ProposalBundle {
             f(&proposal.as_ref().map(BorrowedProposal::from))
         })?;
 
+        #[cfg(feature = "app_ack")]
+        self.retain_by_type::<AppAckProposal, _, _>(|proposal| {
+            f(&proposal.as_ref().map(BorrowedProposal::from))
+        })?;
+
         Ok(())
     }
 
@@ -216,6 +232,9 @@ impl ProposalBundle {
         #[cfg(feature = "by_ref_proposal")]
         let len = len + self.updates.len();
 
+        #[cfg(feature = "app_ack")]
+        let len = len + self.app_acks.len();
+
         len + self.additions.len()
             + self.removals.len()
             + self.reinitializations.len()
@@ -265,6 +284,13 @@ impl ProposalBundle {
                 .map(|p| p.as_ref().map(BorrowedProposal::GroupContextExtensions)),
         );
 
+        #[cfg(feature = "app_ack")]
+        let res = res.chain(
+            self.app_acks
+                .iter()
+                .map(|p| p.as_ref().map(BorrowedProposal::AppAck)),
+        );
+
         #[cfg(feature = "custom_proposal")]
         let res = res.chain(
             self.custom_proposals
@@ -298,6 +324,9 @@ impl ProposalBundle {
         #[cfg(feature = "by_ref_proposal")]
         let res = res.chain(self.updates.into_iter().map(|p| p.map(Proposal::Update)));
 
+        #[cfg(feature = "app_ack")]
+        let res = res.chain(self.app_acks.into_iter().map(|p| p.map(Proposal::AppAck)));
+
         res.chain(
             self.additions
                 .into_iter()
@@ -370,6 +399,12 @@ impl ProposalBundle {
         &self.group_context_extensions
     }
 
+    /// AppAck proposals in the bundle.
+    #[cfg(feature = "app_ack")]
+    pub fn app_ack_proposals(&self) -> &[ProposalInfo<AppAckProposal>] {
+        &self.app_acks
+    }
+
     /// Custom proposals in the bundle.
     #[cfg(feature = "custom_proposal")]
     pub fn custom_proposals(&self) -> &[ProposalInfo<CustomProposal>] {
@@ -419,6 +454,9 @@ impl ProposalBundle {
             (!self.external_initializations.is_empty()).then_some(ProposalType::EXTERNAL_INIT),
         );
 
+        #[cfg(feature = "app_ack")]
+        let res = res.chain((!self.app_acks.is_empty()).then_some(ProposalType::APP_ACK));
+
         #[cfg(not(feature = "custom_proposal"))]
         return res.chain(
             (!self.group_context_extensions.is_empty())
@@ -631,3 +669,5 @@ impl_proposable!(
     GROUP_CONTEXT_EXTENSIONS,
     group_context_extensions
 );
+#[cfg(feature = "app_ack")]
+impl_proposable!(AppAckProposal, APP_ACK, app_acks);
@@ -17,10 +17,12 @@ use crate::{
         node::LeafIndex,
         TreeKemPublic,
     },
-    CipherSuiteProvider, ExtensionList,
+    CipherSuite, CipherSuiteProvider, ExtensionList,
 };
 
-use super::filtering_common::{filter_out_invalid_psks, ApplyProposalsOutput, ProposalApplier};
+use super::filtering_common::{
+    ensure_reinit_is_not_downgrade, filter_out_invalid_psks, ApplyProposalsOutput, ProposalApplier,
+};
 
 #[cfg(feature = "by_ref_proposal")]
 use crate::extension::ExternalSendersExt;
@@ -39,6 +41,9 @@ use crate::group::ExternalInit;
 #[cfg(feature = "psk")]
 use crate::group::proposal::PreSharedKeyProposal;
 
+#[cfg(feature = "app_ack")]
+use crate::group::proposal::AppAckProposal;
+
 #[cfg(all(not(mls_build_async), feature = "rayon"))]
 use {crate::iter::ParallelIteratorExt, rayon::prelude::*};
 
@@ -91,7 +96,12 @@ where
         .await?;
 
         let proposals = filter_out_extra_group_context_extensions(strategy, proposals)?;
-        let proposals = filter_out_invalid_reinit(strategy, proposals, self.protocol_version)?;
+        let proposals = filter_out_invalid_reinit(
+            strategy,
+            proposals,
+            self.protocol_version,
+            self.cipher_suite_provider.cipher_suite(),
+        )?;
         let proposals = filter_out_reinit_if_other_proposals(strategy.is_ignore(), proposals)?;
 
         let proposals = filter_out_external_init(strategy, proposals)?;
@@ -326,10 +336,11 @@ where
 
         let res = match ext {
             Ok(None) => Ok(()),
-            Ok(Some(extension)) => extension
-                .verify_all(identity_provider, commit_time, &p.proposal)
-                .await
-                .map_err(|e| MlsError::IdentityProviderError(e.into_any_error())),
+            Ok(Some(extension)) => {
+                extension
+                    .verify_all(identity_provider, commit_time, &p.proposal)
+                    .await
+            }
             Err(e) => Err(MlsError::from(e)),
         };
 
@@ -369,6 +380,7 @@ fn filter_out_invalid_reinit(
     strategy: FilterStrategy,
     mut proposals: ProposalBundle,
     protocol_version: ProtocolVersion,
+    cipher_suite: CipherSuite,
 ) -> Result<ProposalBundle, MlsError> {
     proposals.retain_by_type::<ReInitProposal, _, _>(|p| {
         apply_strategy(
@@ -376,7 +388,10 @@ fn filter_out_invalid_reinit(
             p.is_by_reference(),
             (p.proposal.version >= protocol_version)
                 .then_some(())
-                .ok_or(MlsError::InvalidProtocolVersionInReInit),
+                .ok_or(MlsError::InvalidProtocolVersionInReInit)
+                .and_then(|_| {
+                    ensure_reinit_is_not_downgrade(cipher_suite, p.proposal.cipher_suite)
+                }),
         )
     })?;
 
@@ -439,6 +454,7 @@ pub(crate) fn proposer_can_propose(
                 | ProposalType::PSK
                 | ProposalType::RE_INIT
                 | ProposalType::GROUP_CONTEXT_EXTENSIONS
+                | ProposalType::APP_ACK
         ),
         (Sender::Member(_), true) => matches!(
             proposal_type,
@@ -448,6 +464,7 @@ pub(crate) fn proposer_can_propose(
                 | ProposalType::PSK
                 | ProposalType::RE_INIT
                 | ProposalType::GROUP_CONTEXT_EXTENSIONS
+                | ProposalType::APP_ACK
         ),
         #[cfg(feature = "by_ref_proposal")]
         (Sender::External(_), false) => false,
@@ -544,6 +561,16 @@ pub(crate) fn filter_out_invalid_proposers(
         }
     }
 
+    #[cfg(feature = "app_ack")]
+    for i in (0..proposals.app_ack_proposals().len()).rev() {
+        let p = &proposals.app_ack_proposals()[i];
+        let res = proposer_can_propose(p.sender, ProposalType::APP_ACK, p.is_by_reference());
+
+        if !apply_strategy(strategy, p.is_by_reference(), res)? {
+            proposals.remove::<AppAckProposal>(i);
+        }
+    }
+
     Ok(proposals)
 }
 
@@ -554,6 +581,25 @@ fn leaf_index_of_update_sender(p: &ProposalInfo<UpdateProposal>) -> Result<LeafI
     }
 }
 
+#[cfg(feature = "app_ack")]
+pub(super) fn filter_out_invalid_app_acks(
+    proposals: &mut ProposalBundle,
+    original_tree: &TreeKemPublic,
+    new_tree: &TreeKemPublic,
+    strategy: FilterStrategy,
+) -> Result<(), MlsError> {
+    let supported = new_tree.can_support_proposal(ProposalType::APP_ACK);
+
+    proposals.retain_by_type::<AppAckProposal, _, _>(|p| {
+        let res = supported
+            .then_some(())
+            .ok_or(MlsError::UnsupportedAppAck)
+            .and_then(|_| p.proposal.validate(original_tree));
+
+        apply_strategy(strategy, p.is_by_reference(), res)
+    })
+}
+
 #[cfg(feature = "custom_proposal")]
 pub(super) fn filter_out_unsupported_custom_proposals(
     proposals: &mut ProposalBundle,
@@ -13,7 +13,7 @@ use crate::{
         node::LeafIndex,
         TreeKemPublic,
     },
-    CipherSuiteProvider, ExtensionList,
+    CipherSuite, CipherSuiteProvider, ExtensionList,
 };
 
 use crate::tree_kem::leaf_node::LeafNode;
@@ -44,6 +44,9 @@ use std::collections::HashSet;
 #[cfg(feature = "by_ref_proposal")]
 use super::filtering::{apply_strategy, filter_out_invalid_proposers, FilterStrategy};
 
+#[cfg(feature = "app_ack")]
+use super::filtering::filter_out_invalid_app_acks;
+
 #[cfg(feature = "custom_proposal")]
 use super::filtering::filter_out_unsupported_custom_proposals;
 
@@ -130,9 +133,23 @@ where
             Sender::NewMemberProposal => Err(MlsError::ExternalSenderCannotCommit),
         }?;
 
-        #[cfg(all(feature = "by_ref_proposal", feature = "custom_proposal"))]
+        #[cfg(all(
+            feature = "by_ref_proposal",
+            any(feature = "custom_proposal", feature = "app_ack")
+        ))]
         let mut output = output;
 
+        #[cfg(all(feature = "by_ref_proposal", feature = "app_ack"))]
+        filter_out_invalid_app_acks(
+            &mut output.applied_proposals,
+            self.original_tree,
+            &output.new_tree,
+            strategy,
+        )?;
+
+        #[cfg(all(not(feature = "by_ref_proposal"), feature = "app_ack"))]
+        filter_out_invalid_app_acks(proposals, self.original_tree, &output.new_tree)?;
+
         #[cfg(all(feature = "by_ref_proposal", feature = "custom_proposal"))]
         filter_out_unsupported_custom_proposals(
             &mut output.applied_proposals,
@@ -259,8 +276,17 @@ where
             output
                 .new_tree
                 .non_empty_leaves()
-                .try_for_each(|(_, leaf)| {
-                    leaf_validator.validate_required_capabilities(leaf)?;
+                .try_for_each(|(index, leaf)| {
+                    leaf_validator
+                        .validate_required_capabilities(leaf)
+                        .map_err(|e| match e {
+                            MlsError::RequiredExtensionNotFound(_)
+                            | MlsError::RequiredProposalNotFound(_)
+                            | MlsError::RequiredCredentialNotFound(_) => {
+                                MlsError::ExistingMemberViolatesCapabilities(*index)
+                            }
+                            e => e,
+                        })?;
 
                     #[cfg(feature = "by_ref_proposal")]
                     leaf_validator.validate_external_senders_ext_credentials(leaf)?;
@@ -322,6 +348,8 @@ where
         key_package: &KeyPackage,
         commit_time: Option<MlsTime>,
     ) -> Result<(), MlsError> {
+        self.check_key_package_cipher_suite(key_package)?;
+
         leaf_node_validator
             .check_if_valid(&key_package.leaf_node, ValidationContext::Add(commit_time))
             .await?;
@@ -341,6 +369,8 @@ where
         key_package: &KeyPackage,
         commit_time: Option<MlsTime>,
     ) -> Result<(), MlsError> {
+        self.check_key_package_cipher_suite(key_package)?;
+
         let (a, b) = rayon::join(
             || {
                 leaf_node_validator
@@ -357,6 +387,21 @@ where
         a?;
         b
     }
+
+    // Checked ahead of signatures, which would otherwise fail to verify under the group's
+    // cipher suite and hide the actual mismatch.
+    fn check_key_package_cipher_suite(&self, key_package: &KeyPackage) -> Result<(), MlsError> {
+        let expected = self.cipher_suite_provider.cipher_suite();
+
+        if key_package.cipher_suite != expected {
+            return Err(MlsError::CipherSuiteMismatch {
+                expected,
+                found: key_package.cipher_suite,
+            });
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(feature = "psk")]
@@ -476,6 +521,22 @@ where
     Ok(())
 }
 
+/// Reject a ReInit that would move the group to a cipher suite with a lower security level
+/// than the one the group currently uses. Suites of unknown strength are not compared.
+///
+/// The negotiated suite is already part of the group context, which is bound into the key
+/// schedule, and joining the new group verifies that it uses the suite of the ReInit. A ReInit
+/// is therefore the only place where a suite change has to be checked.
+pub(crate) fn ensure_reinit_is_not_downgrade(
+    current: CipherSuite,
+    reinit: CipherSuite,
+) -> Result<(), MlsError> {
+    match (current.security_level(), reinit.security_level()) {
+        (Some(current), Some(reinit)) if reinit < current => Err(MlsError::DowngradeDetected),
+        _ => Ok(()),
+    }
+}
+
 fn ensure_exactly_one_external_init(proposals: &ProposalBundle) -> Result<(), MlsError> {
     (proposals.by_type::<ExternalInit>().count() == 1)
         .then_some(())
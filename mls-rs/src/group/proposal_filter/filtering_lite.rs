@@ -9,13 +9,15 @@ use crate::{
     protocol_version::ProtocolVersion,
     time::MlsTime,
     tree_kem::{leaf_node_validator::LeafNodeValidator, node::LeafIndex},
-    CipherSuiteProvider, ExtensionList,
+    CipherSuite, CipherSuiteProvider, ExtensionList,
 };
 
-use super::filtering_common::{filter_out_invalid_psks, ApplyProposalsOutput, ProposalApplier};
+use super::filtering_common::{
+    ensure_reinit_is_not_downgrade, filter_out_invalid_psks, ApplyProposalsOutput, ProposalApplier,
+};
 
 #[cfg(feature = "by_ref_proposal")]
-use {crate::extension::ExternalSendersExt, mls_rs_core::error::IntoAnyError};
+use crate::extension::ExternalSendersExt;
 
 use mls_rs_core::{identity::IdentityProvider, psk::PreSharedKeyStorage};
 
@@ -28,9 +30,12 @@ use rayon::prelude::*;
 #[cfg(mls_build_async)]
 use futures::{StreamExt, TryStreamExt};
 
-#[cfg(feature = "custom_proposal")]
+#[cfg(any(feature = "custom_proposal", feature = "app_ack"))]
 use crate::tree_kem::TreeKemPublic;
 
+#[cfg(feature = "app_ack")]
+use crate::group::ProposalType;
+
 #[cfg(feature = "psk")]
 use crate::group::{
     proposal::PreSharedKeyProposal, JustPreSharedKeyID, ResumptionPSKUsage, ResumptionPsk,
@@ -59,7 +64,11 @@ where
         filter_out_invalid_group_extensions(proposals, self.identity_provider, commit_time).await?;
 
         filter_out_extra_group_context_extensions(proposals)?;
-        filter_out_invalid_reinit(proposals, self.protocol_version)?;
+        filter_out_invalid_reinit(
+            proposals,
+            self.protocol_version,
+            self.cipher_suite_provider.cipher_suite(),
+        )?;
         filter_out_reinit_if_other_proposals(proposals)?;
 
         self.apply_proposal_changes(proposals, commit_time).await
@@ -169,8 +178,7 @@ where
     if let Some(p) = proposals.group_context_extensions.first() {
         if let Some(ext) = p.proposal.get_as::<ExternalSendersExt>()? {
             ext.verify_all(identity_provider, commit_time, p.proposal())
-                .await
-                .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+                .await?;
         }
     }
 
@@ -186,11 +194,14 @@ fn filter_out_extra_group_context_extensions(proposals: &ProposalBundle) -> Resu
 fn filter_out_invalid_reinit(
     proposals: &ProposalBundle,
     protocol_version: ProtocolVersion,
+    cipher_suite: CipherSuite,
 ) -> Result<(), MlsError> {
     if let Some(p) = proposals.reinitializations.first() {
         (p.proposal.version >= protocol_version)
             .then_some(())
             .ok_or(MlsError::InvalidProtocolVersionInReInit)?;
+
+        ensure_reinit_is_not_downgrade(cipher_suite, p.proposal.cipher_suite)?;
     }
 
     Ok(())
@@ -202,6 +213,26 @@ fn filter_out_reinit_if_other_proposals(proposals: &ProposalBundle) -> Result<()
         .ok_or(MlsError::OtherProposalWithReInit)
 }
 
+#[cfg(feature = "app_ack")]
+pub(super) fn filter_out_invalid_app_acks(
+    proposals: &ProposalBundle,
+    original_tree: &TreeKemPublic,
+    new_tree: &TreeKemPublic,
+) -> Result<(), MlsError> {
+    if proposals.app_acks.is_empty() {
+        return Ok(());
+    }
+
+    if !new_tree.can_support_proposal(ProposalType::APP_ACK) {
+        return Err(MlsError::UnsupportedAppAck);
+    }
+
+    proposals
+        .app_acks
+        .iter()
+        .try_for_each(|p| p.proposal.validate(original_tree))
+}
+
 #[cfg(feature = "custom_proposal")]
 pub(super) fn filter_out_unsupported_custom_proposals(
     proposals: &ProposalBundle,
@@ -40,6 +40,19 @@ impl ProposalRef {
         ))
     }
 
+    /// Check whether this is the reference of `content`.
+    #[cfg(feature = "by_ref_proposal")]
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn matches_content<CS: CipherSuiteProvider>(
+        &self,
+        cipher_suite_provider: &CS,
+        content: &AuthenticatedContent,
+    ) -> Result<bool, MlsError> {
+        let computed = Self::from_content(cipher_suite_provider, content).await?;
+
+        Ok(&computed == self)
+    }
+
     pub fn as_slice(&self) -> &[u8] {
         &self.0
     }
@@ -83,6 +96,7 @@ mod test {
     use super::test_utils::auth_content_from_proposal;
     use super::*;
     use crate::{
+        client::test_utils::TEST_CIPHER_SUITE,
         crypto::test_utils::{test_cipher_suite_provider, try_test_cipher_suite_provider},
         key_package::test_utils::test_key_package,
         tree_kem::leaf_node::test_utils::get_basic_test_node,
@@ -223,4 +237,35 @@ mod test {
             assert_eq!(expected_out, proposal_ref);
         }
     }
+
+    #[cfg(feature = "by_ref_proposal")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn reference_matches_only_its_source_content() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let remove = |to_remove| {
+            auth_content_from_proposal(
+                Proposal::Remove(RemoveProposal {
+                    to_remove: LeafIndex(to_remove),
+                }),
+                LeafIndex(0),
+            )
+        };
+
+        let content = remove(1);
+        let proposal_ref = ProposalRef::from_content(&cs, &content).await.unwrap();
+
+        let other = remove(2);
+
+        let mut encrypted = content.clone();
+        encrypted.wire_format = WireFormat::PrivateMessage;
+
+        let matches_source = proposal_ref.matches_content(&cs, &content).await.unwrap();
+        let matches_other = proposal_ref.matches_content(&cs, &other).await.unwrap();
+        let matches_encrypted = proposal_ref.matches_content(&cs, &encrypted).await.unwrap();
+
+        assert!(matches_source);
+        assert!(!matches_other);
+        assert!(!matches_encrypted);
+    }
 }
@@ -288,7 +288,10 @@ async fn resumption_join_group<C: ClientConfig + Clone>(
     if group.protocol_version() != expected_new_group_params.version {
         Err(MlsError::ProtocolVersionMismatch)
     } else if group.cipher_suite() != expected_new_group_params.cipher_suite {
-        Err(MlsError::CipherSuiteMismatch)
+        Err(MlsError::CipherSuiteMismatch {
+            expected: expected_new_group_params.cipher_suite,
+            found: group.cipher_suite(),
+        })
     } else if verify_group_id && group.group_id() != expected_new_group_params.group_id {
         Err(MlsError::GroupIdMismatch)
     } else if &group.group_state().context.extensions != expected_new_group_params.extensions {
@@ -109,6 +109,10 @@ impl<T: TreeIndex> TreeSecretsVec<T> {
     fn take_node(&mut self, index: &T) -> Option<SecretTreeNode> {
         self.inner.remove(index)
     }
+
+    fn iter(&self) -> impl Iterator<Item = (&T, &SecretTreeNode)> {
+        self.inner.iter()
+    }
 }
 
 #[cfg(not(feature = "std"))]
@@ -125,6 +129,10 @@ impl<T: TreeIndex> TreeSecretsVec<T> {
         self.find_node(index).map(|i| self.inner.remove(i).1)
     }
 
+    fn iter(&self) -> impl Iterator<Item = (&T, &SecretTreeNode)> {
+        self.inner.iter().map(|(index, node)| (index, node))
+    }
+
     fn find_node(&self, index: &T) -> Option<usize> {
         use itertools::Itertools;
 
@@ -206,6 +214,21 @@ impl<T: TreeIndex> SecretTree<T> {
         }
     }
 
+    /// Highest application generation consumed by each leaf whose ratchet
+    /// has been used in this epoch.
+    pub(crate) fn application_generations(&self) -> impl Iterator<Item = (&T, u32)> {
+        self.known_secrets
+            .iter()
+            .filter_map(|(index, node)| match node {
+                SecretTreeNode::Ratchet(ratchets) => ratchets
+                    .application
+                    .generation
+                    .checked_sub(1)
+                    .map(|generation| (index, generation)),
+                SecretTreeNode::Secret(_) => None,
+            })
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn consume_node<P: CipherSuiteProvider>(
         &mut self,
@@ -689,6 +712,83 @@ mod tests {
         }
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn lazily_derived_keys_match_eager_derivation() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let root_secret = vec![1u8; cs.kdf_extract_size()];
+        let leaf_count = 8u32;
+
+        // Expand every node of the tree up front
+        let mut eager = Vec::new();
+        let mut pending = vec![(leaf_count.root(), root_secret.clone())];
+
+        while let Some((index, secret)) = pending.pop() {
+            match (index.left(), index.right()) {
+                (Some(left), Some(right)) => {
+                    let left_secret = kdf_expand_with_label(&cs, &secret, b"tree", b"left", None)
+                        .await
+                        .unwrap();
+
+                    let right_secret = kdf_expand_with_label(&cs, &secret, b"tree", b"right", None)
+                        .await
+                        .unwrap();
+
+                    pending.push((left, left_secret.to_vec()));
+                    pending.push((right, right_secret.to_vec()));
+                }
+                _ => {
+                    let ratchets = SecretRatchets {
+                        application: SecretKeyRatchet::new(&cs, &secret, KeyType::Application)
+                            .await
+                            .unwrap(),
+                        handshake: SecretKeyRatchet::new(&cs, &secret, KeyType::Handshake)
+                            .await
+                            .unwrap(),
+                    };
+
+                    eager.push((index, ratchets));
+                }
+            }
+        }
+
+        assert_eq!(eager.len(), 8);
+
+        let mut lazy = get_test_tree(root_secret, leaf_count);
+
+        // Only the leaves at node indices 2 and 10 are used
+        for leaf in [2u32, 10] {
+            let (_, expected) = eager.iter_mut().find(|(i, _)| *i == leaf).unwrap();
+
+            for key_type in [KeyType::Application, KeyType::Handshake] {
+                let key = lazy.next_message_key(&cs, leaf, key_type).await.unwrap();
+                let expected_key = expected.next_message_key(&cs, key_type).await.unwrap();
+
+                assert_eq!(key, expected_key);
+            }
+        }
+
+        let mut derived_ratchets = lazy
+            .known_secrets
+            .iter()
+            .filter(|(_, node)| matches!(node, SecretTreeNode::Ratchet(_)))
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+
+        derived_ratchets.sort();
+        assert_eq!(derived_ratchets, [2, 10]);
+
+        // Besides the used leaves, only the secrets of their copath were
+        // computed. Nothing was derived for the leaves below nodes 5 and 13.
+        let mut known = lazy
+            .known_secrets
+            .iter()
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+
+        known.sort();
+        assert_eq!(known, [0, 2, 5, 8, 10, 13]);
+    }
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_secret_key_ratchet() {
         for cipher_suite in TestCryptoProvider::all_supported_cipher_suites() {
@@ -10,6 +10,7 @@ use crate::{
         GroupState, InterimTranscriptHash, ReInitProposal, TreeKemPublic,
     },
     tree_kem::TreeKemPrivate,
+    MlsMessage,
 };
 
 #[cfg(feature = "by_ref_proposal")]
@@ -26,21 +27,29 @@ use mls_rs_codec::{MlsDecode, MlsEncode, MlsSize};
 use mls_rs_core::crypto::SignatureSecretKey;
 #[cfg(feature = "tree_index")]
 use mls_rs_core::identity::IdentityProvider;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "std")]
+use super::identity_cache::IdentityCache;
 
 #[cfg(all(feature = "std", feature = "by_ref_proposal"))]
 use std::collections::HashMap;
 
-#[cfg(all(feature = "by_ref_proposal", not(feature = "std")))]
-use alloc::vec::Vec;
+use alloc::{collections::BTreeMap, vec::Vec};
 
 use super::{cipher_suite_provider, epoch::EpochSecrets, state_repo::GroupStateRepository};
 
-#[derive(Debug, PartialEq, Clone, MlsEncode, MlsDecode, MlsSize)]
+/// Version 2 added `leaf_rotation_epochs` and version 3 added `removed`.
+const SNAPSHOT_VERSION: u16 = 3;
+
+#[derive(Debug, PartialEq, Clone, MlsEncode, MlsSize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Snapshot {
     version: u16,
     pub(crate) state: RawGroupState,
     private_tree: TreeKemPrivate,
+    /// Includes the per-sender secret tree ratchets, so a restored group resumes
+    /// at the same generations and never reuses a message key or nonce.
     epoch_secrets: EpochSecrets,
     key_schedule: KeySchedule,
     #[cfg(all(feature = "std", feature = "by_ref_proposal"))]
@@ -49,6 +58,61 @@ pub(crate) struct Snapshot {
     pending_updates: Vec<(HpkePublicKey, (HpkeSecretKey, Option<SignatureSecretKey>))>,
     pending_commit: Option<CommitGeneration>,
     signer: SignatureSecretKey,
+    #[cfg_attr(feature = "serde", serde(default))]
+    leaf_rotation_epochs: BTreeMap<u32, u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    #[mls_codec(with = "bool_codec")]
+    removed: bool,
+}
+
+mod bool_codec {
+    use alloc::vec::Vec;
+    use mls_rs_codec::{Error, MlsDecode, MlsEncode};
+
+    pub fn mls_encoded_len(_: &bool) -> usize {
+        1
+    }
+
+    pub fn mls_encode(value: &bool, writer: &mut Vec<u8>) -> Result<(), Error> {
+        u8::from(*value).mls_encode(writer)
+    }
+
+    pub fn mls_decode(reader: &mut &[u8]) -> Result<bool, Error> {
+        match u8::mls_decode(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::UnsupportedEnumDiscriminant),
+        }
+    }
+}
+
+impl MlsDecode for Snapshot {
+    fn mls_decode(reader: &mut &[u8]) -> Result<Self, mls_rs_codec::Error> {
+        let version = u16::mls_decode(reader)?;
+
+        Ok(Self {
+            version,
+            state: RawGroupState::mls_decode(reader)?,
+            private_tree: TreeKemPrivate::mls_decode(reader)?,
+            epoch_secrets: EpochSecrets::mls_decode(reader)?,
+            key_schedule: KeySchedule::mls_decode(reader)?,
+            #[cfg(feature = "by_ref_proposal")]
+            pending_updates: MlsDecode::mls_decode(reader)?,
+            pending_commit: MlsDecode::mls_decode(reader)?,
+            signer: SignatureSecretKey::mls_decode(reader)?,
+            // Version 1 snapshots were written before rotation epochs were tracked
+            leaf_rotation_epochs: if version >= 2 {
+                MlsDecode::mls_decode(reader)?
+            } else {
+                Default::default()
+            },
+            removed: if version >= 3 {
+                bool_codec::mls_decode(reader)?
+            } else {
+                false
+            },
+        })
+    }
 }
 
 #[derive(Debug, MlsEncode, MlsDecode, MlsSize, PartialEq, Clone)]
@@ -144,6 +208,32 @@ impl RawGroupState {
     }
 }
 
+/// Complete state of a [`Group`] at a given point in time, as obtained from
+/// [`Group::export_snapshot`].
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Debug, MlsSize, MlsEncode, MlsDecode, PartialEq, Clone)]
+pub struct GroupSnapshot(pub(crate) Snapshot);
+
+#[cfg_attr(all(feature = "ffi", not(test)), ::safer_ffi_gen::safer_ffi_gen)]
+impl GroupSnapshot {
+    /// Serialize the snapshot.
+    ///
+    /// The output contains secret key material and is zeroized on drop.
+    #[cfg_attr(all(feature = "ffi", not(test)), safer_ffi_gen::safer_ffi_gen_ignore)]
+    pub fn to_bytes(&self) -> Result<Zeroizing<Vec<u8>>, MlsError> {
+        self.mls_encode_to_vec()
+            .map(Zeroizing::new)
+            .map_err(Into::into)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MlsError> {
+        Self::mls_decode(&mut &*bytes).map_err(Into::into)
+    }
+}
+
 impl<C> Group<C>
 where
     C: ClientConfig + Clone,
@@ -165,11 +255,60 @@ where
             pending_updates: self.pending_updates.clone(),
             pending_commit: self.pending_commit.clone(),
             epoch_secrets: self.epoch_secrets.clone(),
-            version: 1,
+            version: SNAPSHOT_VERSION,
             signer: self.signer.clone(),
+            leaf_rotation_epochs: self.leaf_rotation_epochs.clone(),
+            removed: self.removed,
         }
     }
 
+    /// Export the current state of the group.
+    ///
+    /// The returned snapshot contains secret key material.
+    pub fn export_snapshot(&self) -> GroupSnapshot {
+        GroupSnapshot(self.snapshot())
+    }
+
+    /// Recompute the interim transcript hash from the confirmed transcript
+    /// hash and confirmation tag of the current epoch.
+    ///
+    /// This allows recovering a group restored from a snapshot in which the
+    /// interim transcript hash is missing or corrupted. Without it, the next
+    /// commit cannot be processed.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn recompute_interim_transcript_hash(&mut self) -> Result<(), MlsError> {
+        self.state.interim_transcript_hash = InterimTranscriptHash::create(
+            &self.cipher_suite_provider,
+            &self.state.context.confirmed_transcript_hash,
+            &self.state.confirmation_tag,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Process `messages` in order, starting from the state in `initial`,
+    /// and return the resulting state.
+    ///
+    /// The messages are applied to a temporary group created from `initial`
+    /// with the configuration of this group. Neither this group nor the
+    /// [`GroupStateStorage`](crate::GroupStateStorage) in use is modified.
+    /// Application messages are decrypted and their content is discarded.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn replay_transcript(
+        &self,
+        initial: GroupSnapshot,
+        messages: &[MlsMessage],
+    ) -> Result<GroupSnapshot, MlsError> {
+        let mut group = Group::from_snapshot(self.config.clone(), initial.0).await?;
+
+        for message in messages {
+            group.process_incoming_message(message.clone()).await?;
+        }
+
+        Ok(group.export_snapshot())
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn from_snapshot(config: C, snapshot: Snapshot) -> Result<Self, MlsError> {
         let cipher_suite_provider = cipher_suite_provider(
@@ -180,6 +319,9 @@ where
         #[cfg(feature = "tree_index")]
         let identity_provider = config.identity_provider();
 
+        #[cfg(feature = "std")]
+        let identity_cache = IdentityCache::new(config.identity_cache_ttl_in_s());
+
         let state_repo = GroupStateRepository::new(
             #[cfg(feature = "prior_epoch")]
             snapshot.state.context.group_id.clone(),
@@ -202,6 +344,8 @@ where
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: snapshot.pending_updates,
             pending_commit: snapshot.pending_commit,
+            last_committer: None,
+            removed: snapshot.removed,
             #[cfg(test)]
             commit_modifiers: Default::default(),
             epoch_secrets: snapshot.epoch_secrets,
@@ -209,7 +353,10 @@ where
             cipher_suite_provider,
             #[cfg(feature = "psk")]
             previous_psk: None,
+            #[cfg(feature = "std")]
+            identity_cache,
             signer: snapshot.signer,
+            leaf_rotation_epochs: snapshot.leaf_rotation_epochs,
         })
     }
 }
@@ -229,7 +376,7 @@ pub(crate) mod test_utils {
         tree_kem::{node::LeafIndex, TreeKemPrivate},
     };
 
-    use super::{RawGroupState, Snapshot};
+    use super::{RawGroupState, Snapshot, SNAPSHOT_VERSION};
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn get_test_snapshot(cipher_suite: CipherSuite, epoch_id: u64) -> Snapshot {
@@ -250,24 +397,38 @@ pub(crate) mod test_utils {
             #[cfg(feature = "by_ref_proposal")]
             pending_updates: Default::default(),
             pending_commit: None,
-            version: 1,
+            version: SNAPSHOT_VERSION,
             signer: vec![].into(),
+            leaf_rotation_epochs: Default::default(),
+            removed: false,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use alloc::vec;
+    use alloc::{vec, vec::Vec};
+    use assert_matches::assert_matches;
 
     use crate::{
-        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        client::{
+            test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+            MlsError,
+        },
         group::{
-            test_utils::{test_group, TestGroup},
-            Group,
+            test_utils::{test_group, test_n_member_group, TestGroup},
+            transcript_hash::InterimTranscriptHash,
+            Group, GroupStatus, SuspensionReason,
         },
     };
 
+    #[cfg(feature = "private_message")]
+    use crate::{group::secret_tree::KeyType, tree_kem::node::NodeIndex};
+
+    use mls_rs_codec::{MlsDecode, MlsEncode};
+
+    use super::Snapshot;
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn snapshot_restore(group: TestGroup) {
         let snapshot = group.group.snapshot();
@@ -314,6 +475,164 @@ mod tests {
         snapshot_restore(group).await
     }
 
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn recomputed_interim_transcript_hash_allows_processing_commits() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 2).await;
+
+        let mut snapshot = groups[1].group.snapshot();
+        snapshot.state.interim_transcript_hash = InterimTranscriptHash::from(vec![]);
+
+        let mut restored = Group::from_snapshot(groups[1].group.config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        let commit = groups[0].group.commit(vec![]).await.unwrap().commit_message;
+
+        let res = restored
+            .clone()
+            .process_incoming_message(commit.clone())
+            .await;
+
+        assert_matches!(res, Err(MlsError::InvalidConfirmationTag));
+
+        restored.recompute_interim_transcript_hash().await.unwrap();
+        restored.process_incoming_message(commit).await.unwrap();
+
+        groups[0].process_pending_commit().await.unwrap();
+        assert_eq!(restored.current_epoch(), groups[0].group.current_epoch());
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn replayed_transcript_matches_live_group() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let initial = groups[1].group.export_snapshot();
+        let mut transcript = Vec::new();
+
+        for committer in [0, 2, 0] {
+            let commit = groups[committer]
+                .group
+                .commit(vec![])
+                .await
+                .unwrap()
+                .commit_message;
+
+            groups[committer].process_pending_commit().await.unwrap();
+
+            for receiver in [0, 1, 2].into_iter().filter(|i| *i != committer) {
+                groups[receiver]
+                    .process_message(commit.clone())
+                    .await
+                    .unwrap();
+            }
+
+            transcript.push(commit);
+
+            #[cfg(feature = "private_message")]
+            {
+                let message = groups[committer]
+                    .group
+                    .encrypt_application_message(b"test", vec![])
+                    .await
+                    .unwrap();
+
+                groups[1].process_message(message.clone()).await.unwrap();
+                transcript.push(message);
+            }
+        }
+
+        let live = groups[1].group.export_snapshot();
+
+        let replayed = groups[1]
+            .group
+            .replay_transcript(initial, &transcript)
+            .await
+            .unwrap();
+
+        assert_eq!(replayed, live);
+        assert_eq!(groups[1].group.export_snapshot(), live);
+    }
+
+    #[cfg(feature = "private_message")]
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn restored_group_does_not_reuse_generations() {
+        let mut group = test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await;
+
+        for _ in 0..3 {
+            group
+                .group
+                .encrypt_application_message(b"test", vec![])
+                .await
+                .unwrap();
+        }
+
+        let snapshot = group.group.snapshot().mls_encode_to_vec().unwrap();
+        let snapshot = Snapshot::mls_decode(&mut &*snapshot).unwrap();
+
+        let mut restored = Group::from_snapshot(group.group.config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        let self_index = NodeIndex::from(restored.private_tree.self_index);
+
+        let key_data = restored
+            .epoch_secrets
+            .secret_tree
+            .next_message_key(
+                &restored.cipher_suite_provider,
+                self_index,
+                KeyType::Application,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(key_data.generation(), 3);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn previous_snapshot_versions_can_be_decoded() {
+        // Versions 1 and 2 end before the empty rotation epochs map and the removed flag
+        for (version, missing_fields) in [(1, 2), (2, 1)] {
+            let mut snapshot = super::test_utils::get_test_snapshot(TEST_CIPHER_SUITE, 5).await;
+            snapshot.version = version;
+
+            let mut bytes = snapshot.mls_encode_to_vec().unwrap();
+            bytes.truncate(bytes.len() - missing_fields);
+
+            let decoded = Snapshot::mls_decode(&mut &*bytes).unwrap();
+            assert_eq!(decoded, snapshot);
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn removed_member_is_suspended_after_restore() {
+        let mut groups = test_n_member_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE, 3).await;
+
+        let commit = groups[0]
+            .group
+            .commit_builder()
+            .remove_member(1)
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+            .commit_message;
+
+        groups[1].process_message(commit).await.unwrap();
+
+        let snapshot = groups[1].group.snapshot().mls_encode_to_vec().unwrap();
+        let snapshot = Snapshot::mls_decode(&mut &*snapshot).unwrap();
+
+        let restored = Group::from_snapshot(groups[1].group.config.clone(), snapshot)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            restored.status(),
+            GroupStatus::Suspended(SuspensionReason::Removed)
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn serde() {
@@ -0,0 +1,50 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// Copyright by contributors to this project.
+// SPDX-License-Identifier: (Apache-2.0 OR MIT)
+
+/// Reason why a [`Group`](crate::group::Group) was suspended.
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SuspensionReason {
+    /// A commit containing a [`ReInit`](crate::group::proposal::Proposal::ReInit)
+    /// proposal was applied. The group must be replaced by the reinitialized
+    /// group.
+    ReInit,
+    /// A commit removing the local member from the group was processed. The
+    /// group remains in the last epoch the member was part of.
+    Removed,
+}
+
+/// Status of a [`Group`](crate::group::Group), as returned by
+/// [`Group::status`](crate::group::Group::status).
+#[cfg_attr(
+    all(feature = "ffi", not(test)),
+    safer_ffi_gen::ffi_type(clone, opaque)
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GroupStatus {
+    /// The group can be used to send and receive messages.
+    Active,
+    /// The group can no longer be used to encrypt messages or create commits.
+    Suspended(SuspensionReason),
+    /// A [`ReInit`](crate::group::proposal::Proposal::ReInit) proposal was
+    /// received and must be committed before application messages can be
+    /// sent.
+    PendingReinit,
+}
+
+impl GroupStatus {
+    /// Returns `true` unless the group is suspended.
+    ///
+    /// A group that is [`PendingReinit`](GroupStatus::PendingReinit) is
+    /// active, since the pending ReInit still has to be committed. Application
+    /// messages can only be sent once it is.
+    pub fn is_active(&self) -> bool {
+        !matches!(self, GroupStatus::Suspended(_))
+    }
+}
@@ -50,6 +50,13 @@ impl From<Vec<u8>> for ConfirmedTranscriptHash {
     }
 }
 
+#[derive(Debug, MlsSize, MlsEncode)]
+pub(crate) struct ConfirmedTranscriptHashInput<'a> {
+    pub(crate) wire_format: WireFormat,
+    pub(crate) content: &'a FramedContent,
+    pub(crate) signature: &'a MessageSignature,
+}
+
 impl ConfirmedTranscriptHash {
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn create<P: CipherSuiteProvider>(
@@ -57,13 +64,6 @@ impl ConfirmedTranscriptHash {
         interim_transcript_hash: &InterimTranscriptHash,
         content: &AuthenticatedContent,
     ) -> Result<Self, MlsError> {
-        #[derive(Debug, MlsSize, MlsEncode)]
-        struct ConfirmedTranscriptHashInput<'a> {
-            wire_format: WireFormat,
-            content: &'a FramedContent,
-            signature: &'a MessageSignature,
-        }
-
         let input = ConfirmedTranscriptHashInput {
             wire_format: content.wire_format,
             content: &content.content,
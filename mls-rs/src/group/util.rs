@@ -2,6 +2,7 @@
 // Copyright by contributors to this project.
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
+use alloc::vec::Vec;
 use mls_rs_core::{
     error::IntoAnyError, identity::IdentityProvider, key_package::KeyPackageStorage,
 };
@@ -9,6 +10,7 @@ use mls_rs_core::{
 use crate::{
     cipher_suite::CipherSuite,
     client::MlsError,
+    client_config::ClientConfig,
     extension::RatchetTreeExt,
     key_package::KeyPackageGeneration,
     protocol_version::ProtocolVersion,
@@ -40,7 +42,10 @@ pub(crate) async fn validate_group_info_common<C: CipherSuiteProvider>(
     }
 
     if group_info.group_context.cipher_suite != cs.cipher_suite() {
-        return Err(MlsError::CipherSuiteMismatch);
+        return Err(MlsError::CipherSuiteMismatch {
+            expected: cs.cipher_suite(),
+            found: group_info.group_context.cipher_suite,
+        });
     }
 
     let sender_leaf = &tree.get_leaf_node(group_info.signer)?;
@@ -52,6 +57,45 @@ pub(crate) async fn validate_group_info_common<C: CipherSuiteProvider>(
     Ok(())
 }
 
+/// Verify the signatures of several group infos, each against the signer's
+/// leaf in the accompanying tree.
+///
+/// The result holds one entry per item, `false` meaning that the signature
+/// does not verify. The cipher suite provider exposes no batch verification
+/// primitive, so signatures are checked one at a time. An error is returned if
+/// a group info uses another cipher suite than `cs` or if its signer is not in
+/// the tree.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub async fn verify_group_infos<C: CipherSuiteProvider>(
+    items: &[(GroupInfo, ExportedTree<'_>)],
+    cs: &C,
+) -> Result<Vec<bool>, MlsError> {
+    let mut results = Vec::with_capacity(items.len());
+
+    for (group_info, tree) in items {
+        if group_info.group_context.cipher_suite != cs.cipher_suite() {
+            return Err(MlsError::CipherSuiteMismatch {
+                expected: cs.cipher_suite(),
+                found: group_info.group_context.cipher_suite,
+            });
+        }
+
+        let sender_leaf = tree.0.borrow_as_leaf(group_info.signer)?;
+
+        let res = group_info
+            .verify(cs, &sender_leaf.signing_identity.signature_key, &())
+            .await;
+
+        match res {
+            Ok(()) => results.push(true),
+            Err(MlsError::InvalidSignature) => results.push(false),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(results)
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 pub(crate) async fn validate_group_info_member<C: CipherSuiteProvider>(
     self_state: &GroupState,
@@ -109,8 +153,7 @@ where
         // TODO do joiners verify group against current time??
         ext_senders
             .verify_all(id_provider, None, &context.extensions)
-            .await
-            .map_err(|e| MlsError::IdentityProviderError(e.into_any_error()))?;
+            .await?;
     }
 
     validate_group_info_common(msg_version, group_info, &tree, cs).await?;
@@ -182,13 +225,38 @@ pub(crate) async fn find_key_package_generation<'a, K: KeyPackageStorage>(
                 }
             })?
         {
-            return Ok(val);
+            // A second entry for the same key package could carry different secrets
+            let duplicates = secrets
+                .iter()
+                .filter(|s| s.new_member == secret.new_member)
+                .count();
+
+            return (duplicates == 1)
+                .then_some(val)
+                .ok_or(MlsError::AmbiguousWelcome);
         }
     }
 
     Err(MlsError::WelcomeKeyPackageNotFound)
 }
 
+/// Check that `cipher_suite` meets the minimum strength required by `config`
+/// to join a group.
+pub(crate) fn check_cipher_suite_strength<C: ClientConfig>(
+    config: &C,
+    cipher_suite: CipherSuite,
+) -> Result<(), MlsError> {
+    let Some(min_strength) = config.min_cipher_suite_strength() else {
+        return Ok(());
+    };
+
+    cipher_suite
+        .security_level()
+        .map_or(false, |level| level >= min_strength)
+        .then_some(())
+        .ok_or(MlsError::CipherSuiteTooWeak(cipher_suite))
+}
+
 pub(crate) fn cipher_suite_provider<P>(
     crypto: P,
     cipher_suite: CipherSuite,
@@ -200,3 +268,52 @@ where
         .cipher_suite_provider(cipher_suite)
         .ok_or(MlsError::UnsupportedCipherSuite(cipher_suite))
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        client::test_utils::{TEST_CIPHER_SUITE, TEST_PROTOCOL_VERSION},
+        crypto::test_utils::test_cipher_suite_provider,
+        group::{test_utils::test_group, GroupInfo},
+    };
+
+    use super::verify_group_infos;
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn verify_group_infos_flags_bad_signature() {
+        let mut groups = Vec::new();
+
+        for _ in 0..3 {
+            groups.push(test_group(TEST_PROTOCOL_VERSION, TEST_CIPHER_SUITE).await);
+        }
+
+        let mut group_infos = Vec::new();
+
+        for group in &groups {
+            let group_info = group
+                .group
+                .group_info_message(false)
+                .await
+                .unwrap()
+                .into_group_info()
+                .unwrap();
+
+            group_infos.push(group_info);
+        }
+
+        group_infos[1].signature[0] ^= 1;
+
+        let items = group_infos
+            .into_iter()
+            .zip(groups.iter())
+            .map(|(group_info, group)| (group_info, group.group.export_tree()))
+            .collect::<Vec<(GroupInfo, _)>>();
+
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let results = verify_group_infos(&items, &cs).await.unwrap();
+
+        assert_eq!(results, [true, false, true]);
+    }
+}
@@ -20,8 +20,10 @@ pub use mls_rs_core::group::RosterUpdate;
 #[cfg(test)]
 pub(crate) mod test_utils {
     use alloc::boxed::Box;
+    use alloc::sync::Arc;
     use alloc::vec;
     use alloc::vec::Vec;
+    use core::sync::atomic::{AtomicUsize, Ordering};
     use mls_rs_core::{
         crypto::{CipherSuite, CipherSuiteProvider, SignatureSecretKey},
         error::IntoAnyError,
@@ -54,6 +56,9 @@ pub(crate) mod test_utils {
         pub(crate) basic: BasicIdentityProvider,
         pub(crate) allow_any_custom: bool,
         supported_cred_types: Vec<CredentialType>,
+        pub(crate) validate_member_calls: Arc<AtomicUsize>,
+        pub(crate) max_credential_size: Option<usize>,
+        pub(crate) credential_expiration: Option<MlsTime>,
     }
 
     impl BasicWithCustomProvider {
@@ -67,6 +72,9 @@ pub(crate) mod test_utils {
                     CredentialType::BASIC,
                     Self::CUSTOM_CREDENTIAL_TYPE.into(),
                 ],
+                validate_member_calls: Default::default(),
+                max_credential_size: None,
+                credential_expiration: None,
             }
         }
 
@@ -122,6 +130,8 @@ pub(crate) mod test_utils {
             _timestamp: Option<MlsTime>,
             _extensions: Option<&ExtensionList>,
         ) -> Result<(), Self::Error> {
+            self.validate_member_calls.fetch_add(1, Ordering::Relaxed);
+
             //TODO: Is it actually beneficial to check the key, or does that already happen elsewhere before
             //this point?
             Ok(())
@@ -161,6 +171,14 @@ pub(crate) mod test_utils {
         fn supported_types(&self) -> Vec<CredentialType> {
             self.supported_cred_types.clone()
         }
+
+        fn max_credential_size(&self) -> Option<usize> {
+            self.max_credential_size
+        }
+
+        fn credential_expiration(&self, _signing_identity: &SigningIdentity) -> Option<MlsTime> {
+            self.credential_expiration
+        }
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -117,7 +117,10 @@ impl KeyPackage {
         cipher_suite_provider: &CP,
     ) -> Result<KeyPackageRef, MlsError> {
         if cipher_suite_provider.cipher_suite() != self.cipher_suite {
-            return Err(MlsError::CipherSuiteMismatch);
+            return Err(MlsError::CipherSuiteMismatch {
+                expected: cipher_suite_provider.cipher_suite(),
+                found: self.cipher_suite,
+            });
         }
 
         Ok(KeyPackageRef(
@@ -325,7 +328,11 @@ mod tests {
             if let Some(cs) = try_test_cipher_suite_provider(*another_cipher_suite) {
                 let res = key_package.to_reference(&cs).await;
 
-                assert_matches!(res, Err(MlsError::CipherSuiteMismatch));
+                assert_matches!(
+                    res,
+                    Err(MlsError::CipherSuiteMismatch { expected, found })
+                        if expected == cs.cipher_suite() && found == TEST_CIPHER_SUITE
+                );
             }
         }
     }
@@ -12,20 +12,23 @@ pub(crate) async fn validate_key_package_properties<CSP: CipherSuiteProvider>(
     version: ProtocolVersion,
     cs: &CSP,
 ) -> Result<(), MlsError> {
-    package
-        .verify(cs, &package.leaf_node.signing_identity.signature_key, &())
-        .await?;
-
     // Verify that the protocol version matches
     if package.version != version {
         return Err(MlsError::ProtocolVersionMismatch);
     }
 
-    // Verify that the cipher suite matches
+    // Verify that the cipher suite matches before checking a signature made with it
     if package.cipher_suite != cs.cipher_suite() {
-        return Err(MlsError::CipherSuiteMismatch);
+        return Err(MlsError::CipherSuiteMismatch {
+            expected: cs.cipher_suite(),
+            found: package.cipher_suite,
+        });
     }
 
+    package
+        .verify(cs, &package.leaf_node.signing_identity.signature_key, &())
+        .await?;
+
     // Verify that the public init key is a valid format for this cipher suite
     cs.kem_public_key_validate(&package.hpke_init_key)
         .map_err(|_| MlsError::InvalidInitKey)?;
@@ -62,7 +62,7 @@ pub(crate) enum JustPreSharedKeyID {
 #[derive(Clone, Eq, Hash, Ord, PartialOrd, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub(crate) struct PskGroupId(
+pub struct PskGroupId(
     #[mls_codec(with = "mls_rs_codec::byte_vec")]
     #[cfg_attr(feature = "serde", serde(with = "mls_rs_core::vec_serde"))]
     pub Vec<u8>,
@@ -98,16 +98,28 @@ impl PskNonce {
     pub fn random<P: CipherSuiteProvider>(
         cipher_suite_provider: &P,
     ) -> Result<Self, <P as CipherSuiteProvider>::Error> {
-        Ok(Self(cipher_suite_provider.random_bytes_vec(
-            cipher_suite_provider.kdf_extract_size(),
-        )?))
+        Self::random_with(cipher_suite_provider, |out| {
+            cipher_suite_provider.random_bytes(out)
+        })
+    }
+
+    /// Generate a nonce of the size required by `cipher_suite_provider`, using
+    /// `fill_random` as the source of randomness.
+    pub fn random_with<P, F, E>(cipher_suite_provider: &P, mut fill_random: F) -> Result<Self, E>
+    where
+        P: CipherSuiteProvider,
+        F: FnMut(&mut [u8]) -> Result<(), E>,
+    {
+        let mut nonce = vec![0u8; cipher_suite_provider.kdf_extract_size()];
+        fill_random(&mut nonce)?;
+        Ok(Self(nonce))
     }
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialOrd, PartialEq, MlsSize, MlsEncode, MlsDecode)]
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub(crate) struct ResumptionPsk {
+pub struct ResumptionPsk {
     pub usage: ResumptionPSKUsage,
     pub psk_group_id: PskGroupId,
     pub psk_epoch: u64,
@@ -117,7 +129,7 @@ pub(crate) struct ResumptionPsk {
 #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
-pub(crate) enum ResumptionPSKUsage {
+pub enum ResumptionPSKUsage {
     Application = 1u8,
     Reinit = 2u8,
     Branch = 3u8,
@@ -150,14 +162,20 @@ impl PreSharedKeyStorage for AlwaysFoundPskStorage {
 pub(crate) mod test_utils {
     use crate::crypto::test_utils::test_cipher_suite_provider;
 
+    use crate::storage_provider::in_memory::InMemoryPreSharedKeyStorage;
+
     use super::PskNonce;
-    use mls_rs_core::crypto::CipherSuite;
+    use alloc::vec::Vec;
+    use mls_rs_core::{
+        crypto::{CipherSuite, CipherSuiteProvider},
+        error::IntoAnyError,
+        psk::{ExternalPskId, PreSharedKey, PreSharedKeyStorage},
+    };
 
-    #[cfg(not(mls_build_async))]
-    use mls_rs_core::{crypto::CipherSuiteProvider, psk::ExternalPskId};
+    #[cfg(feature = "std")]
+    use alloc::boxed::Box;
 
     #[cfg_attr(coverage_nightly, coverage(off))]
-    #[cfg(not(mls_build_async))]
     pub(crate) fn make_external_psk_id<P: CipherSuiteProvider>(
         cipher_suite_provider: &P,
     ) -> ExternalPskId {
@@ -171,18 +189,67 @@ pub(crate) mod test_utils {
     pub(crate) fn make_nonce(cipher_suite: CipherSuite) -> PskNonce {
         PskNonce::random(&test_cipher_suite_provider(cipher_suite)).unwrap()
     }
+
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(thiserror::Error))]
+    #[cfg_attr(feature = "std", error("psk store failure"))]
+    pub(crate) struct PskStoreFailure;
+
+    impl IntoAnyError for PskStoreFailure {
+        #[cfg(feature = "std")]
+        fn into_dyn_error(self) -> Result<Box<dyn std::error::Error + Send + Sync>, Self> {
+            Ok(self.into())
+        }
+    }
+
+    /// PSK storage that behaves like [`InMemoryPreSharedKeyStorage`] except that
+    /// lookups of the configured ids fail with [`PskStoreFailure`].
+    #[derive(Clone, Debug, Default)]
+    pub(crate) struct FaultyPskStore {
+        inner: InMemoryPreSharedKeyStorage,
+        failing_ids: Vec<ExternalPskId>,
+    }
+
+    impl FaultyPskStore {
+        pub(crate) fn insert(&mut self, id: ExternalPskId, psk: PreSharedKey) {
+            self.inner.insert(id, psk);
+        }
+
+        pub(crate) fn fail_on(&mut self, id: ExternalPskId) {
+            self.failing_ids.push(id);
+        }
+    }
+
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    #[cfg_attr(mls_build_async, maybe_async::must_be_async)]
+    impl PreSharedKeyStorage for FaultyPskStore {
+        type Error = PskStoreFailure;
+
+        async fn get(&self, id: &ExternalPskId) -> Result<Option<PreSharedKey>, Self::Error> {
+            if self.failing_ids.contains(id) {
+                Err(PskStoreFailure)
+            } else {
+                Ok(self.inner.get(id))
+            }
+        }
+    }
 }
 
 #[cfg(feature = "psk")]
 #[cfg(test)]
 mod tests {
-    use crate::crypto::test_utils::TestCryptoProvider;
-    use core::iter;
+    use crate::{
+        client::test_utils::TEST_CIPHER_SUITE,
+        crypto::test_utils::{test_cipher_suite_provider, TestCryptoProvider},
+    };
+    use alloc::vec::Vec;
+    use core::{convert::Infallible, iter};
+    use mls_rs_core::crypto::CipherSuiteProvider;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as test;
 
-    use super::test_utils::make_nonce;
+    use super::{test_utils::make_nonce, PskNonce};
 
     #[test]
     fn random_generation_of_nonces_is_random() {
@@ -197,4 +264,35 @@ mod tests {
 
         assert!(good);
     }
+
+    #[test]
+    fn nonce_generation_with_deterministic_rng_is_reproducible() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let counting_rng = || {
+            let mut counter = 0u8;
+
+            move |out: &mut [u8]| {
+                out.iter_mut().for_each(|b| {
+                    *b = counter;
+                    counter = counter.wrapping_add(1);
+                });
+
+                Ok::<_, Infallible>(())
+            }
+        };
+
+        let mut rng = counting_rng();
+        let first = PskNonce::random_with(&cs, &mut rng).unwrap();
+        let second = PskNonce::random_with(&cs, &mut rng).unwrap();
+
+        let size = cs.kdf_extract_size();
+        let expected = |start: usize| (start..start + size).map(|i| i as u8).collect::<Vec<_>>();
+
+        assert_eq!(first.0, expected(0));
+        assert_eq!(second.0, expected(size));
+
+        assert_eq!(PskNonce::random_with(&cs, counting_rng()).unwrap(), first);
+        assert_ne!(make_nonce(TEST_CIPHER_SUITE), make_nonce(TEST_CIPHER_SUITE));
+    }
 }
@@ -62,17 +62,27 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
             .ok_or(MlsError::MissingRequiredPsk)
     }
 
+    /// Resolve all PSKs in `id`. Instead of failing on the first resumption PSK whose
+    /// secret is unavailable, all such PSKs are reported in
+    /// [`MlsError::ResumptionPsksNotFound`] so that they can be fetched together.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn resolve(&self, id: &[PreSharedKeyID]) -> Result<Vec<PskSecretInput>, MlsError> {
         let mut secret_inputs = Vec::new();
+        let mut missing = Vec::new();
 
         for id in id {
             let psk = match &id.key_id {
-                JustPreSharedKeyID::External(external) => self.resolve_external(external).await,
+                JustPreSharedKeyID::External(external) => self.resolve_external(external).await?,
                 JustPreSharedKeyID::Resumption(resumption) => {
-                    self.resolve_resumption(resumption).await
+                    match self.resolve_resumption(resumption).await {
+                        Err(MlsError::OldGroupStateNotFound) => {
+                            missing.push(resumption.clone());
+                            continue;
+                        }
+                        res => res?,
+                    }
                 }
-            }?;
+            };
 
             secret_inputs.push(PskSecretInput {
                 id: id.clone(),
@@ -80,7 +90,10 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
             })
         }
 
-        Ok(secret_inputs)
+        missing
+            .is_empty()
+            .then_some(secret_inputs)
+            .ok_or(MlsError::ResumptionPsksNotFound(missing))
     }
 
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
@@ -93,3 +106,102 @@ impl<GS: GroupStateStorage, K: KeyPackageStorage, PS: PreSharedKeyStorage>
         PskSecret::calculate(&psk, cipher_suite_provider).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use assert_matches::assert_matches;
+
+    use crate::{
+        client::{test_utils::TEST_CIPHER_SUITE, MlsError},
+        crypto::test_utils::test_cipher_suite_provider,
+        psk::{
+            test_utils::{make_external_psk_id, make_nonce, FaultyPskStore},
+            ExternalPskId, JustPreSharedKeyID, PreSharedKeyID, PskGroupId, ResumptionPSKUsage,
+            ResumptionPsk,
+        },
+        storage_provider::in_memory::{InMemoryGroupStateStorage, InMemoryKeyPackageStorage},
+    };
+
+    use super::PskResolver;
+
+    fn resumption_psk(psk_epoch: u64) -> PreSharedKeyID {
+        PreSharedKeyID {
+            key_id: JustPreSharedKeyID::Resumption(ResumptionPsk {
+                usage: ResumptionPSKUsage::Branch,
+                psk_group_id: PskGroupId(b"group".to_vec()),
+                psk_epoch,
+            }),
+            psk_nonce: make_nonce(TEST_CIPHER_SUITE),
+        }
+    }
+
+    fn external_psk(id: ExternalPskId) -> PreSharedKeyID {
+        PreSharedKeyID {
+            key_id: JustPreSharedKeyID::External(id),
+            psk_nonce: make_nonce(TEST_CIPHER_SUITE),
+        }
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn psk_store_errors_are_surfaced() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let good_id = make_external_psk_id(&cs);
+        let bad_id = make_external_psk_id(&cs);
+
+        let mut store = FaultyPskStore::default();
+        store.insert(good_id.clone(), vec![1; 32].into());
+        store.insert(bad_id.clone(), vec![2; 32].into());
+        store.fail_on(bad_id.clone());
+
+        let resolver = PskResolver::<InMemoryGroupStateStorage, InMemoryKeyPackageStorage, _> {
+            group_context: None,
+            current_epoch: None,
+            prior_epochs: None,
+            psk_store: &store,
+        };
+
+        resolver
+            .resolve_to_secret(&[external_psk(good_id.clone())], &cs)
+            .await
+            .unwrap();
+
+        let res = resolver
+            .resolve_to_secret(&[external_psk(good_id), external_psk(bad_id)], &cs)
+            .await;
+
+        assert_matches!(res, Err(MlsError::PskStoreError(_)));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn all_missing_resumption_epochs_are_reported() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let external_id = make_external_psk_id(&cs);
+        let mut store = FaultyPskStore::default();
+        store.insert(external_id.clone(), vec![1; 32].into());
+
+        let resolver = PskResolver::<InMemoryGroupStateStorage, InMemoryKeyPackageStorage, _> {
+            group_context: None,
+            current_epoch: None,
+            prior_epochs: None,
+            psk_store: &store,
+        };
+
+        let ids = [
+            resumption_psk(3),
+            external_psk(external_id),
+            resumption_psk(7),
+        ];
+
+        let res = resolver.resolve_to_secret(&ids, &cs).await;
+
+        let expected = [&ids[0], &ids[2]].map(|id| match &id.key_id {
+            JustPreSharedKeyID::Resumption(psk) => psk.clone(),
+            _ => unreachable!(),
+        });
+
+        assert_matches!(res, Err(MlsError::ResumptionPsksNotFound(missing)) if missing == expected);
+    }
+}
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: (Apache-2.0 OR MIT)
 
 use crate::client::MlsError;
-use crate::crypto::{CipherSuiteProvider, SignatureSecretKey};
+use crate::crypto::{CipherSuiteProvider, HpkePublicKey, HpkeSecretKey, SignatureSecretKey};
 use crate::group::GroupContext;
 use crate::identity::SigningIdentity;
 use crate::iter::wrap_iter;
@@ -44,6 +44,24 @@ pub struct EncapGeneration {
     pub update_path: UpdatePath,
     pub path_secrets: Vec<Option<PathSecret>>,
     pub commit_secret: PathSecret,
+    #[cfg(test)]
+    recipients: Vec<(NodeIndex, Vec<LeafIndex>)>,
+}
+
+#[cfg(test)]
+impl EncapGeneration {
+    /// Leaves able to decrypt the path secret of `node_index`, a node on the
+    /// direct path of the committer.
+    ///
+    /// These are the members in the subtree of the copath child of
+    /// `node_index`, excluding the members added by the commit.
+    pub fn recipients_of(&self, node_index: NodeIndex) -> Vec<LeafIndex> {
+        self.recipients
+            .iter()
+            .find(|(path, _)| *path == node_index)
+            .map(|(_, recipients)| recipients.clone())
+            .unwrap_or_default()
+    }
 }
 
 impl<'a> TreeKem<'a> {
@@ -145,6 +163,24 @@ impl<'a> TreeKem<'a> {
 
         let context_bytes = context.mls_encode_to_vec()?;
 
+        #[cfg(test)]
+        let recipients = path
+            .iter()
+            .zip(&filtered)
+            .filter(|(_, f)| !**f)
+            .map(|(node, _)| {
+                let (start, end) = tree_math::subtree(node.copath);
+
+                let leaves = (*start..*end)
+                    .map(LeafIndex)
+                    .filter(|leaf| !excluding.contains(leaf))
+                    .filter(|leaf| self.tree_kem_public.nodes.borrow_as_leaf(*leaf).is_ok())
+                    .collect();
+
+                (node.path, leaves)
+            })
+            .collect();
+
         let node_updates = self
             .encrypt_path_secrets(
                 path,
@@ -168,6 +204,8 @@ impl<'a> TreeKem<'a> {
             update_path,
             path_secrets,
             commit_secret: secret_generator.next_secret().await?,
+            #[cfg(test)]
+            recipients,
         })
     }
 
@@ -287,7 +325,19 @@ impl<'a> TreeKem<'a> {
             .public_key();
 
         let lca_path_secret =
-            PathSecret::decrypt(cipher_suite_provider, secret, public, context_bytes, ct).await?;
+            match PathSecret::decrypt(cipher_suite_provider, secret, public, context_bytes, ct)
+                .await
+            {
+                // If our secret key does not belong to the public key in our tree, the committer
+                // encrypted to a key pair we no longer hold, e.g. because it did not see our
+                // latest update.
+                Err(MlsError::CryptoProviderError(_))
+                    if !is_key_pair(cipher_suite_provider, secret, public).await =>
+                {
+                    return Err(MlsError::StalePathEncryption)
+                }
+                res => res?,
+            };
 
         // Derive the rest of the secrets for the tree and assign to the proper nodes
         let mut node_secret_gen =
@@ -409,6 +459,26 @@ impl<'a> TreeKem<'a> {
     }
 }
 
+/// Check whether `secret` is the secret key of `public` by opening a probe encrypted to `public`.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+async fn is_key_pair<CP: CipherSuiteProvider>(
+    cipher_suite_provider: &CP,
+    secret: &HpkeSecretKey,
+    public: &HpkePublicKey,
+) -> bool {
+    let Ok(probe) = cipher_suite_provider
+        .hpke_seal(public, &[], None, &[0])
+        .await
+    else {
+        return true;
+    };
+
+    cipher_suite_provider
+        .hpke_open(&probe, secret, public, &[], None)
+        .await
+        .is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::{tree_math, TreeKem};
@@ -420,11 +490,13 @@ mod tests {
         group::test_utils::{get_test_group_context, random_bytes},
         identity::basic::BasicIdentityProvider,
         tree_kem::{
+            hpke_encryption::HpkeEncryptable,
             leaf_node::{
                 test_utils::{get_basic_test_node_sig_key, get_test_capabilities},
                 ConfigProperties,
             },
             node::LeafIndex,
+            path_secret::PathSecret,
             Capabilities, TreeKemPrivate, TreeKemPublic, UpdatePath, ValidatedUpdatePath,
         },
         ExtensionList,
@@ -696,4 +768,144 @@ mod tests {
 
         encap_decap(cipher_suite, 10, Some(capabilities), Some(extensions)).await;
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn encap_recipients_match_resolution() {
+        let cipher_suite_provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let (encap_node, encap_hpke_secret, encap_signer) =
+            get_basic_test_node_sig_key(TEST_CIPHER_SUITE, "encap").await;
+
+        let (mut tree, mut private_key) = TreeKemPublic::derive(
+            encap_node,
+            encap_hpke_secret,
+            &BasicIdentityProvider,
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+
+        let mut leaf_nodes = Vec::new();
+        let mut leaf_keys = Vec::new();
+
+        for index in 1..6 {
+            let (leaf_node, secret_key, _) =
+                get_basic_test_node_sig_key(TEST_CIPHER_SUITE, &format!("{index}")).await;
+
+            leaf_keys.push((LeafIndex(index), secret_key, leaf_node.public_key.clone()));
+            leaf_nodes.push(leaf_node);
+        }
+
+        tree.add_leaves(leaf_nodes, &BasicIdentityProvider, &cipher_suite_provider)
+            .await
+            .unwrap();
+
+        // Leave a blank at leaf 3, then fill it with a member added by the commit
+        tree.remove_leaves(
+            vec![LeafIndex(3)],
+            &BasicIdentityProvider,
+            &cipher_suite_provider,
+        )
+        .await
+        .unwrap();
+
+        let (new_leaf, new_secret_key, _) =
+            get_basic_test_node_sig_key(TEST_CIPHER_SUITE, "new").await;
+
+        leaf_keys[2] = (LeafIndex(3), new_secret_key, new_leaf.public_key.clone());
+
+        let added = tree
+            .add_leaves(
+                vec![new_leaf],
+                &BasicIdentityProvider,
+                &cipher_suite_provider,
+            )
+            .await
+            .unwrap();
+
+        let original_tree = tree.clone();
+        let mut context = get_test_group_context(42, TEST_CIPHER_SUITE).await;
+
+        let encap_gen = TreeKem::new(&mut tree, &mut private_key)
+            .encap(
+                &mut context,
+                &added,
+                &encap_signer,
+                ConfigProperties {
+                    capabilities: get_test_capabilities(),
+                    extensions: Default::default(),
+                },
+                None,
+                &cipher_suite_provider,
+                #[cfg(test)]
+                &Default::default(),
+            )
+            .await
+            .unwrap();
+
+        let context_bytes = context.mls_encode_to_vec().unwrap();
+
+        // Leaves holding a key that decrypts one of the ciphertexts of each update path node
+        let mut recipients = Vec::new();
+
+        for node in &encap_gen.update_path.nodes {
+            let mut node_recipients = Vec::new();
+
+            for (leaf, secret_key, public_key) in &leaf_keys {
+                for ct in &node.encrypted_path_secret {
+                    let res = PathSecret::decrypt(
+                        &cipher_suite_provider,
+                        secret_key,
+                        public_key,
+                        &context_bytes,
+                        ct,
+                    )
+                    .await;
+
+                    if res.is_ok() {
+                        node_recipients.push(*leaf);
+                    }
+                }
+            }
+
+            recipients.push(node_recipients);
+        }
+
+        let copath = original_tree.nodes.direct_copath(LeafIndex(0));
+        let filtered = original_tree.nodes.filtered(LeafIndex(0)).unwrap();
+
+        let path_nodes = copath
+            .iter()
+            .zip(filtered)
+            .filter(|(_, f)| !f)
+            .map(|(node, _)| node);
+
+        for (node, recipients) in path_nodes.zip(&recipients) {
+            // No commit was made yet so the resolution consists of leaves only
+            let mut expected = original_tree
+                .nodes
+                .get_resolution_index(node.copath)
+                .unwrap()
+                .into_iter()
+                .map(|index| LeafIndex(index / 2))
+                .filter(|leaf| !added.contains(leaf))
+                .collect::<Vec<_>>();
+
+            expected.sort();
+
+            assert_eq!(recipients, &expected);
+            assert_eq!(&encap_gen.recipients_of(node.path), recipients);
+        }
+
+        assert_eq!(added, vec![LeafIndex(3)]);
+
+        assert_eq!(
+            recipients,
+            [
+                vec![LeafIndex(1)],
+                vec![LeafIndex(2)],
+                vec![LeafIndex(4), LeafIndex(5)]
+            ]
+        );
+    }
 }
@@ -113,6 +113,31 @@ impl LeafNode {
             .map_err(|e| MlsError::CryptoProviderError(e.into_any_error()))?;
 
         self.public_key = public;
+
+        self.update_signing_identity(
+            cipher_suite_provider,
+            group_id,
+            leaf_index,
+            new_properties,
+            signing_identity,
+            signer,
+        )
+        .await?;
+
+        Ok(secret)
+    }
+
+    /// Re-sign this leaf as an update that keeps the current HPKE key.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub async fn update_signing_identity<P: CipherSuiteProvider>(
+        &mut self,
+        cipher_suite_provider: &P,
+        group_id: &[u8],
+        leaf_index: u32,
+        new_properties: ConfigProperties,
+        signing_identity: Option<SigningIdentity>,
+        signer: &SignatureSecretKey,
+    ) -> Result<(), MlsError> {
         self.capabilities = new_properties.capabilities;
         self.extensions = new_properties.extensions;
         self.leaf_node_source = LeafNodeSource::Update;
@@ -128,9 +153,7 @@ impl LeafNode {
             signer,
             &(group_id, leaf_index).into(),
         )
-        .await?;
-
-        Ok(secret)
+        .await
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -274,6 +297,27 @@ pub(crate) mod test_utils {
 
     use super::*;
 
+    impl LeafNode {
+        /// Unsigned leaf assembled from explicit parts, for tests that need a
+        /// leaf in a specific state.
+        #[allow(unused)]
+        pub fn from_parts(
+            public_key: HpkePublicKey,
+            signing_identity: SigningIdentity,
+            capabilities: Capabilities,
+            leaf_node_source: LeafNodeSource,
+        ) -> Self {
+            LeafNode {
+                public_key,
+                signing_identity,
+                capabilities,
+                leaf_node_source,
+                extensions: Default::default(),
+                signature: Default::default(),
+            }
+        }
+    }
+
     #[allow(unused)]
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub async fn get_test_node(
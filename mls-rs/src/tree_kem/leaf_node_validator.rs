@@ -6,6 +6,7 @@ use super::leaf_node::{LeafNode, LeafNodeSigningContext, LeafNodeSource};
 use crate::client::MlsError;
 use crate::CipherSuiteProvider;
 use crate::{signer::Signable, time::MlsTime};
+use mls_rs_codec::MlsSize;
 use mls_rs_core::{error::IntoAnyError, extension::ExtensionList, identity::IdentityProvider};
 
 use crate::extension::RequiredCapabilitiesExt;
@@ -177,6 +178,15 @@ impl<'a, C: IdentityProvider, CP: CipherSuiteProvider> LeafNodeValidator<'a, C,
         // Check that we are validating within the proper context
         self.check_context(leaf_node, &context)?;
 
+        // Bound the input handed to the identity provider
+        if let Some(max_size) = self.identity_provider.max_credential_size() {
+            let size = leaf_node.signing_identity.credential.mls_encoded_len();
+
+            if size > max_size {
+                return Err(MlsError::CredentialTooLarge(size));
+            }
+        }
+
         // Verify the credential
         self.identity_provider
             .validate_member(
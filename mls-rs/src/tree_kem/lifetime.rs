@@ -24,26 +24,41 @@ impl Lifetime {
 
     pub fn seconds(s: u64) -> Result<Self, MlsError> {
         #[cfg(feature = "std")]
-        let not_before = MlsTime::now().seconds_since_epoch();
+        let now = MlsTime::now();
         #[cfg(not(feature = "std"))]
         // There is no clock on no_std, this is here just so that we can run tests.
-        let not_before = 3600u64;
+        let now = MlsTime::from(3600);
 
+        Self::seconds_from(now, s)
+    }
+
+    pub fn days(d: u32) -> Result<Self, MlsError> {
+        Self::seconds((d * 86400) as u64)
+    }
+
+    pub fn years(y: u8) -> Result<Self, MlsError> {
+        Self::days(365 * y as u32)
+    }
+
+    /// Lifetime of `s` seconds starting at `time`, which does not require reading
+    /// the system clock.
+    pub fn seconds_from(time: MlsTime, s: u64) -> Result<Self, MlsError> {
+        let not_before = time.seconds_since_epoch();
         let not_after = not_before.checked_add(s).ok_or(MlsError::TimeOverflow)?;
 
         Ok(Lifetime {
             // Subtract 1 hour to address time difference between machines
-            not_before: not_before - 3600,
+            not_before: not_before.saturating_sub(3600),
             not_after,
         })
     }
 
-    pub fn days(d: u32) -> Result<Self, MlsError> {
-        Self::seconds((d * 86400) as u64)
+    pub fn days_from(time: MlsTime, d: u32) -> Result<Self, MlsError> {
+        Self::seconds_from(time, u64::from(d) * 86400)
     }
 
-    pub fn years(y: u8) -> Result<Self, MlsError> {
-        Self::days(365 * y as u32)
+    pub fn years_from(time: MlsTime, y: u8) -> Result<Self, MlsError> {
+        Self::days_from(time, 365 * y as u32)
     }
 
     pub(crate) fn within_lifetime(&self, time: MlsTime) -> bool {
@@ -59,6 +74,9 @@ mod tests {
     use super::*;
     use assert_matches::assert_matches;
 
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
     #[test]
     fn test_lifetime_overflow() {
         let res = Lifetime::seconds(u64::MAX);
@@ -116,4 +134,35 @@ mod tests {
         assert!(test_lifetime
             .within_lifetime(MlsTime::from_duration_since_epoch(Duration::from_secs(6))));
     }
+
+    #[test]
+    fn test_lifetime_from_time() {
+        let start = MlsTime::from(100_000);
+        let lifetime = Lifetime::days_from(start, 1).unwrap();
+
+        assert_eq!(lifetime.not_before, 100_000 - 3600);
+        assert_eq!(lifetime.not_after, 100_000 + 86400);
+
+        assert!(lifetime.within_lifetime(start));
+        assert!(lifetime.within_lifetime(MlsTime::from(100_000 + 86400)));
+        assert!(!lifetime.within_lifetime(MlsTime::from(100_000 + 86401)));
+
+        assert_eq!(
+            Lifetime::years_from(start, 1).unwrap().not_after,
+            100_000 + 86400 * 365
+        );
+
+        assert_matches!(
+            Lifetime::seconds_from(MlsTime::from(u64::MAX), 1),
+            Err(MlsError::TimeOverflow)
+        );
+    }
+
+    #[test]
+    fn test_days_do_not_overflow() {
+        let start = MlsTime::from(100_000);
+        let lifetime = Lifetime::days_from(start, u32::MAX).unwrap();
+
+        assert_eq!(lifetime.not_after, 100_000 + u64::from(u32::MAX) * 86400);
+    }
 }
@@ -47,6 +47,7 @@ pub mod update_path;
 pub use capabilities::*;
 pub use lifetime::*;
 pub(crate) use private::*;
+pub(crate) use tree_hash::tree_hash_of;
 pub use update_path::*;
 
 use tree_index::*;
@@ -62,7 +63,7 @@ pub(crate) mod tree_utils;
 #[cfg(test)]
 mod interop_test_vectors;
 
-#[cfg(feature = "custom_proposal")]
+#[cfg(any(feature = "custom_proposal", feature = "app_ack"))]
 use crate::group::proposal::ProposalType;
 
 #[derive(Clone, Debug, MlsEncode, MlsDecode, MlsSize, Default)]
@@ -182,6 +183,12 @@ impl TreeKemPublic {
         self.nodes.total_leaf_count()
     }
 
+    /// Length in bytes of the serialized ratchet tree, computed without serializing it.
+    #[cfg(any(test, feature = "external_client"))]
+    pub fn serialized_size(&self) -> usize {
+        self.nodes.mls_encoded_len()
+    }
+
     #[cfg(any(test, all(feature = "custom_proposal", feature = "tree_index")))]
     pub fn occupied_leaf_count(&self) -> u32 {
         self.nodes.occupied_leaf_count()
@@ -203,12 +210,12 @@ impl TreeKemPublic {
         )
     }
 
-    #[cfg(feature = "custom_proposal")]
+    #[cfg(any(feature = "custom_proposal", feature = "app_ack"))]
     pub fn can_support_proposal(&self, proposal_type: ProposalType) -> bool {
-        #[cfg(feature = "tree_index")]
+        #[cfg(all(feature = "tree_index", feature = "custom_proposal"))]
         return self.index.count_supporting_proposal(proposal_type) == self.occupied_leaf_count();
 
-        #[cfg(not(feature = "tree_index"))]
+        #[cfg(not(all(feature = "tree_index", feature = "custom_proposal")))]
         self.nodes
             .non_empty_leaves()
             .all(|(_, l)| l.capabilities.proposals.contains(&proposal_type))
@@ -343,14 +350,25 @@ impl TreeKemPublic {
         I: IdentityProvider,
         CP: CipherSuiteProvider,
     {
+        let mut removed = Vec::new();
+
         // Apply removes (they commute with updates because they don't touch the same leaves)
         for i in (0..proposal_bundle.remove_proposals().len()).rev() {
             let index = proposal_bundle.remove_proposals()[i].proposal.to_remove;
+
+            // When sending, a second removal of a leaf removed earlier in this commit is redundant
+            // and dropped. A receiver must reject a commit removing the same leaf twice.
+            if filter && removed.contains(&index) {
+                proposal_bundle.remove::<RemoveProposal>(i);
+                continue;
+            }
+
             let res = self.nodes.blank_leaf_node(index);
 
             if res.is_ok() {
                 // This shouldn't fail if `blank_leaf_node` succedded.
                 self.nodes.blank_direct_path(index)?;
+                removed.push(index);
             }
 
             #[cfg(feature = "tree_index")]
@@ -940,16 +958,18 @@ mod tests {
     #[cfg(feature = "custom_proposal")]
     use crate::group::proposal::ProposalType;
 
+    use crate::group::ExportedTree;
     use crate::identity::basic::BasicIdentityProvider;
     use crate::tree_kem::leaf_node::LeafNode;
     use crate::tree_kem::node::{LeafIndex, Node, NodeIndex, NodeTypeResolver, Parent};
-    use crate::tree_kem::parent_hash::ParentHash;
-    use crate::tree_kem::test_utils::{get_test_leaf_nodes, get_test_tree};
+    use crate::tree_kem::parent_hash::{test_utils::get_test_tree_fig_12, ParentHash};
+    use crate::tree_kem::test_utils::{get_test_leaf_nodes, get_test_tree, TreeWithSigners};
     use crate::tree_kem::{MlsError, TreeKemPublic};
     use alloc::borrow::ToOwned;
     use alloc::vec;
     use alloc::vec::Vec;
     use assert_matches::assert_matches;
+    use mls_rs_codec::MlsEncode;
 
     #[cfg(feature = "by_ref_proposal")]
     use alloc::boxed::Box;
@@ -1487,4 +1507,22 @@ mod tests {
 
         assert!(!tree.can_support_proposal(test_proposal_type));
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn serialized_size_matches_encoding() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let trees = [
+            get_test_tree_fig_12(TEST_CIPHER_SUITE).await,
+            TreeWithSigners::make_full_tree(32, &cs).await.tree,
+        ];
+
+        for tree in trees {
+            let encoded = tree.nodes.mls_encode_to_vec().unwrap();
+            assert_eq!(tree.serialized_size(), encoded.len());
+
+            let exported = ExportedTree::new_borrowed(&tree.nodes).to_bytes().unwrap();
+            assert_eq!(exported, encoded);
+        }
+    }
 }
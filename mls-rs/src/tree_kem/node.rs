@@ -447,6 +447,8 @@ mod tests {
             leaf_node::test_utils::get_basic_test_node, node::test_utils::get_test_node_vec,
         },
     };
+    use assert_matches::assert_matches;
+    use mls_rs_codec::VarInt;
 
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn node_key_getters() {
@@ -574,4 +576,22 @@ mod tests {
         assert_eq!(test_vec.occupied_leaf_count(), 3);
         assert_eq!(test_vec.total_leaf_count(), 4);
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn node_vec_with_oversized_length_is_rejected() {
+        let node_vec = get_test_node_vec().await;
+        let encoded = node_vec.mls_encode_to_vec().unwrap();
+
+        // Replace the length prefix with the largest length a VarInt can hold
+        let mut body = &*encoded;
+        VarInt::mls_decode(&mut body).unwrap();
+
+        let mut data = VarInt((1 << 30) - 1).mls_encode_to_vec().unwrap();
+        data.extend_from_slice(body);
+
+        assert_matches!(
+            NodeVec::mls_decode(&mut &*data),
+            Err(mls_rs_codec::Error::UnexpectedEOF)
+        );
+    }
 }
@@ -177,6 +177,62 @@ impl TreeKemPublic {
         self.update_hashes(&[index], cipher_suite_provider).await
     }
 
+    // Validates the parent hash chain from the leaf at `index` up to the root. Every non-blank
+    // node on the leaf's direct path must be part of the chain, which holds right after the
+    // leaf committed with an update path. This only hashes the copath, making it a cheap
+    // integrity check after a commit. Imported trees still require `validate_parent_hashes`.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn validate_parent_hashes_for_path<P: CipherSuiteProvider>(
+        &self,
+        index: LeafIndex,
+        cipher_suite_provider: &P,
+    ) -> Result<(), MlsError> {
+        let LeafNodeSource::Commit(parent_hash) =
+            &self.nodes.borrow_as_leaf(index)?.leaf_node_source
+        else {
+            return Err(MlsError::InvalidLeafNodeSource);
+        };
+
+        let num_leaves = self.total_leaf_count();
+        let mut parent_hash = parent_hash;
+        let mut n = NodeIndex::from(index);
+
+        while let Some(ps) = n.parent_sibling(&num_leaves) {
+            n = ps.parent;
+
+            if self.nodes.is_blank(ps.parent)? {
+                continue;
+            }
+
+            let parent = self.nodes.borrow_as_parent(ps.parent)?;
+
+            let original_sibling_hash = self
+                .original_subtree_hash(ps.parent, ps.sibling, cipher_suite_provider)
+                .await?;
+
+            let calculated = ParentHash::new(
+                cipher_suite_provider,
+                &parent.public_key,
+                &parent.parent_hash,
+                &original_sibling_hash,
+            )
+            .await?;
+
+            if !parent_hash.matches(&calculated) {
+                return Err(MlsError::ParentHashMismatch);
+            }
+
+            parent_hash = &parent.parent_hash;
+        }
+
+        // The chain must end at the root
+        if parent_hash.matches(&ParentHash::empty()) {
+            Ok(())
+        } else {
+            Err(MlsError::ParentHashMismatch)
+        }
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(super) async fn validate_parent_hashes<P: CipherSuiteProvider>(
         &self,
@@ -363,12 +419,16 @@ mod tests {
     use super::*;
     use crate::client::test_utils::TEST_CIPHER_SUITE;
     use crate::crypto::test_utils::test_cipher_suite_provider;
-    use crate::tree_kem::leaf_node::test_utils::get_basic_test_node;
-    use crate::tree_kem::leaf_node::LeafNodeSource;
+    use crate::group::test_utils::random_bytes;
+    use crate::identity::test_utils::get_test_signing_identity;
+    use crate::tree_kem::leaf_node::test_utils::{get_basic_test_node, get_test_capabilities};
+    use crate::tree_kem::leaf_node::{LeafNode, LeafNodeSource};
     use crate::tree_kem::test_utils::TreeWithSigners;
     use crate::tree_kem::MlsError;
     use assert_matches::assert_matches;
 
+    use alloc::vec;
+
     #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
     async fn test_missing_parent_hash() {
         let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
@@ -428,4 +488,101 @@ mod tests {
 
         assert_matches!(res, Err(MlsError::ParentHashMismatch));
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn validate_parent_hashes_for_path_checks_chain() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        // Leaf 5 commits last, then leaf 7 is added and unmerged at nodes 7 and 11 on its path
+        let mut test_tree = TreeWithSigners::make_full_tree(7, &cs).await;
+        test_tree.add_member("Alice7", &cs).await;
+
+        let mut tree = test_tree.tree;
+        tree.tree_hashes.current = vec![];
+        tree.tree_hash(&cs).await.unwrap();
+
+        let unmerged = &tree.nodes.borrow_as_parent(11).unwrap().unmerged_leaves;
+        assert_eq!(unmerged, &vec![LeafIndex(7)]);
+
+        tree.validate_parent_hashes_for_path(LeafIndex(5), &cs)
+            .await
+            .unwrap();
+
+        tree.nodes.borrow_as_parent_mut(11).unwrap().public_key = random_bytes(32).into();
+
+        let res = tree
+            .validate_parent_hashes_for_path(LeafIndex(5), &cs)
+            .await;
+
+        assert_matches!(res, Err(MlsError::ParentHashMismatch));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn validate_parent_hashes_for_path_without_cached_hashes() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+
+        let mut tree = TreeWithSigners::make_full_tree(8, &cs).await.tree;
+        tree.tree_hashes.current = vec![];
+
+        tree.validate_parent_hashes_for_path(LeafIndex(6), &cs)
+            .await
+            .unwrap();
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn leaf_from_parts_with_commit_source_joins_parent_hash_chain() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = TreeWithSigners::make_full_tree(8, &cs).await.tree;
+
+        let (signing_identity, _) = get_test_signing_identity(TEST_CIPHER_SUITE, b"foo").await;
+        let (_, public_key) = cs.kem_generate().await.unwrap();
+
+        let make_leaf = |parent_hash| {
+            LeafNode::from_parts(
+                public_key.clone(),
+                signing_identity.clone(),
+                get_test_capabilities(),
+                LeafNodeSource::Commit(parent_hash),
+            )
+        };
+
+        // Compute the parent hash leaf 0 would commit to on a copy of the tree
+        let mut expected_tree = tree.clone();
+
+        *expected_tree
+            .nodes
+            .borrow_as_leaf_mut(LeafIndex(0))
+            .unwrap() = make_leaf(ParentHash::empty());
+
+        expected_tree
+            .update_parent_hashes(LeafIndex(0), false, &cs)
+            .await
+            .unwrap();
+
+        let LeafNodeSource::Commit(parent_hash) = expected_tree
+            .nodes
+            .borrow_as_leaf(LeafIndex(0))
+            .unwrap()
+            .leaf_node_source
+            .clone()
+        else {
+            panic!("expected a commit leaf");
+        };
+
+        // A wrong parent hash breaks the chain from leaf 0
+        let mut bad_tree = tree.clone();
+        *bad_tree.nodes.borrow_as_leaf_mut(LeafIndex(0)).unwrap() = make_leaf(hex!("f00d").into());
+
+        let res = bad_tree.update_parent_hashes(LeafIndex(0), true, &cs).await;
+        assert_matches!(res, Err(MlsError::ParentHashMismatch));
+
+        // The leaf built with the right parent hash is accepted
+        *tree.nodes.borrow_as_leaf_mut(LeafIndex(0)).unwrap() = make_leaf(parent_hash);
+
+        tree.update_parent_hashes(LeafIndex(0), true, &cs)
+            .await
+            .unwrap();
+
+        tree.validate_parent_hashes(&cs).await.unwrap();
+    }
 }
@@ -120,6 +120,15 @@ impl TreeKemPublic {
         Ok(())
     }
 
+    /// Clone of this tree without its cached tree hashes, which are then fully recomputed
+    /// on next use. A regular clone keeps the cache so that only modified paths are rehashed.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub(crate) fn clone_fresh(&self) -> TreeKemPublic {
+        let mut tree = self.clone();
+        tree.tree_hashes = Default::default();
+        tree
+    }
+
     // Initialize all hashes after creating / importing a tree.
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     async fn initialize_hashes<P>(&mut self, cipher_suite_provider: &P) -> Result<(), MlsError>
@@ -168,6 +177,42 @@ impl TreeKemPublic {
                 != self.nodes.borrow_as_parent(descendant)?.unmerged_leaves)
     }
 
+    /// Tree hash of the subtree rooted at `subtree_root` with the unmerged leaves of its
+    /// ancestor `ancestor` filtered out. Cached hashes are used when available.
+    #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+    pub(crate) async fn original_subtree_hash<P: CipherSuiteProvider>(
+        &self,
+        ancestor: u32,
+        subtree_root: u32,
+        cipher_suite: &P,
+    ) -> Result<TreeHash, MlsError> {
+        let unmerged = self.unmerged_in_subtree(ancestor, subtree_root)?;
+
+        let cached = unmerged
+            .is_empty()
+            .then(|| self.tree_hashes.current.get(subtree_root as usize))
+            .flatten();
+
+        if let Some(hash) = cached {
+            return Ok(hash.clone());
+        }
+
+        let (start_leaf, end_leaf) = tree_math::subtree(subtree_root);
+        let mut hashes = vec![];
+
+        tree_hash(
+            &mut hashes,
+            &self.nodes,
+            Some((*start_leaf..*end_leaf).map(LeafIndex).collect_vec()),
+            unmerged,
+            self.nodes.total_leaf_count(),
+            cipher_suite,
+        )
+        .await?;
+
+        Ok(hashes[subtree_root as usize].clone())
+    }
+
     #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
     pub(crate) async fn compute_original_hashes<P: CipherSuiteProvider>(
         &self,
@@ -256,6 +301,35 @@ impl TreeKemPublic {
     }
 }
 
+/// Compute the tree hash of `nodes` without importing them into a [`TreeKemPublic`].
+///
+/// The result is identical to [`TreeKemPublic::tree_hash`] for a tree containing
+/// the same nodes, but no identity validation is performed and no hashes are cached.
+#[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
+pub(crate) async fn tree_hash_of<P: CipherSuiteProvider>(
+    nodes: &NodeVec,
+    cipher_suite_provider: &P,
+) -> Result<Vec<u8>, MlsError> {
+    if nodes.is_empty() {
+        return Err(MlsError::UnexpectedEmptyTree);
+    }
+
+    let num_leaves = nodes.total_leaf_count();
+    let mut hashes = Vec::new();
+
+    tree_hash(
+        &mut hashes,
+        nodes,
+        None,
+        &[],
+        num_leaves,
+        cipher_suite_provider,
+    )
+    .await?;
+
+    Ok(hashes[num_leaves.root() as usize].to_vec())
+}
+
 #[cfg_attr(not(mls_build_async), maybe_async::must_be_sync)]
 async fn tree_hash<P: CipherSuiteProvider>(
     hashes: &mut Vec<TreeHash>,
@@ -354,11 +428,14 @@ async fn hash_for_parent<P: CipherSuiteProvider>(
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use mls_rs_codec::MlsDecode;
 
     use crate::{
         cipher_suite::CipherSuite,
+        client::test_utils::TEST_CIPHER_SUITE,
         crypto::test_utils::{test_cipher_suite_provider, try_test_cipher_suite_provider},
+        group::ExportedTree,
         identity::basic::BasicIdentityProvider,
         tree_kem::{node::NodeVec, parent_hash::test_utils::get_test_tree_fig_12},
     };
@@ -429,4 +506,61 @@ mod tests {
             assert_eq!(calculated_hash, one_case.tree_hash);
         }
     }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_hash_of_node_vec_matches_tree() {
+        let cipher_suite_provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree_fig_12(TEST_CIPHER_SUITE).await;
+
+        let expected = tree.tree_hash(&cipher_suite_provider).await.unwrap();
+
+        let calculated = ExportedTree::new_borrowed(&tree.nodes)
+            .tree_hash(&cipher_suite_provider)
+            .await
+            .unwrap();
+
+        assert_eq!(calculated, expected);
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn tree_hash_of_empty_node_vec_fails() {
+        let cipher_suite_provider = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let res = tree_hash_of(&NodeVec::default(), &cipher_suite_provider).await;
+
+        assert_matches!(res, Err(MlsError::UnexpectedEmptyTree));
+    }
+
+    #[maybe_async::test(not(mls_build_async), async(mls_build_async, crate::futures_test))]
+    async fn provisional_clone_reuses_cached_hashes_off_the_changed_path() {
+        let cs = test_cipher_suite_provider(TEST_CIPHER_SUITE);
+        let mut tree = get_test_tree_fig_12(TEST_CIPHER_SUITE).await;
+        let original_root = tree.tree_hash(&cs).await.unwrap();
+
+        // Node 11 covers leaves 4 to 7 and is not on the direct path of leaf 0
+        let marker = TreeHash(vec![0xff; 32]);
+        tree.tree_hashes.current[11] = marker.clone();
+
+        let mut provisional = tree.clone();
+
+        provisional
+            .nodes
+            .borrow_as_leaf_mut(LeafIndex(0))
+            .unwrap()
+            .public_key = vec![1; 32].into();
+
+        provisional
+            .update_hashes(&[LeafIndex(0)], &cs)
+            .await
+            .unwrap();
+
+        assert_eq!(provisional.tree_hashes.current[11], marker);
+        assert_ne!(*provisional.tree_hashes.current[7], *original_root);
+
+        // A fresh clone recomputes every hash
+        let mut fresh = provisional.clone_fresh();
+        assert!(fresh.tree_hashes.current.is_empty());
+
+        fresh.tree_hash(&cs).await.unwrap();
+        assert_ne!(fresh.tree_hashes.current[11], marker);
+    }
 }